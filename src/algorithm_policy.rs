@@ -0,0 +1,457 @@
+use pki_types::{alg_id, AlgorithmIdentifier};
+
+use crate::der::{self, FromDer, NormalizedAlgorithmIdentifier};
+use crate::error::Error;
+use crate::signed_data::SubjectPublicKeyInfo;
+
+/// Restrictions on the public keys and signature algorithms that are acceptable in a path.
+///
+/// This applies on top of the supported signature algorithms given for verification. By
+/// default nothing is restricted. Separate policies can be set for each role using
+/// [`VerificationOptions::with_end_entity_algorithm_policy()`],
+/// [`VerificationOptions::with_intermediate_algorithm_policy()`],
+/// [`VerificationOptions::with_trust_anchor_algorithm_policy()`] and
+/// [`VerificationOptions::with_crl_algorithm_policy()`]. Each role is only subject to its own
+/// policy: e.g. a minimum RSA modulus size set for intermediates does not apply to the keys of
+/// trust anchors.
+///
+/// [`VerificationOptions::with_end_entity_algorithm_policy()`]: crate::VerificationOptions::with_end_entity_algorithm_policy
+/// [`VerificationOptions::with_intermediate_algorithm_policy()`]: crate::VerificationOptions::with_intermediate_algorithm_policy
+/// [`VerificationOptions::with_trust_anchor_algorithm_policy()`]: crate::VerificationOptions::with_trust_anchor_algorithm_policy
+/// [`VerificationOptions::with_crl_algorithm_policy()`]: crate::VerificationOptions::with_crl_algorithm_policy
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AlgorithmPolicy<'a> {
+    min_rsa_modulus_bits: usize,
+    ec_curves: Option<&'a [EcCurve]>,
+    hash_algorithms: Option<&'a [HashAlgorithm]>,
+}
+
+impl<'a> AlgorithmPolicy<'a> {
+    /// Create a policy that allows every key and signature algorithm.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject RSA keys with a modulus smaller than `bits`, with [`Error::RsaModulusTooSmall`].
+    ///
+    /// This only applies to the keys of the role the policy is set for. In particular, the
+    /// keys of trust anchors are only checked by the trust anchor policy.
+    pub fn with_min_rsa_modulus_bits(mut self, bits: usize) -> Self {
+        self.min_rsa_modulus_bits = bits;
+        self
+    }
+
+    /// Reject elliptic curve keys on any curve not in `curves`, with
+    /// [`Error::EcCurveNotAllowed`].
+    pub fn with_ec_curves(mut self, curves: &'a [EcCurve]) -> Self {
+        self.ec_curves = Some(curves);
+        self
+    }
+
+    /// Reject signatures using any hash function not in `hashes`, with
+    /// [`Error::HashAlgorithmNotAllowed`].
+    ///
+    /// Ed25519 signatures are not subject to this restriction, as they do not use a separate
+    /// hash function. Signatures using any other algorithm are rejected.
+    pub fn with_hash_algorithms(mut self, hashes: &'a [HashAlgorithm]) -> Self {
+        self.hash_algorithms = Some(hashes);
+        self
+    }
+
+    /// Check the DER-encoded SubjectPublicKeyInfo `spki_value` against the policy.
+    pub(crate) fn check_key(&self, spki_value: untrusted::Input<'_>) -> Result<(), Error> {
+        if self.min_rsa_modulus_bits == 0 && self.ec_curves.is_none() {
+            return Ok(());
+        }
+
+        let spki = der::read_all::<SubjectPublicKeyInfo<'_>>(spki_value)?;
+        let key_alg = spki
+            .algorithm_id_value
+            .read_all(Error::BadDer, NormalizedAlgorithmIdentifier::from_der)?;
+
+        if key_alg == normalize(&alg_id::RSA_ENCRYPTION) {
+            let bits = rsa_modulus_bits(spki.key_value)?;
+            if bits < self.min_rsa_modulus_bits {
+                return Err(Error::RsaModulusTooSmall {
+                    bits,
+                    min_bits: self.min_rsa_modulus_bits,
+                });
+            }
+        } else if key_alg.algorithm.as_slice_less_safe() == EC_PUBLIC_KEY {
+            if let Some(curves) = self.ec_curves {
+                if !curves
+                    .iter()
+                    .any(|curve| key_alg == normalize(&curve.alg_id()))
+                {
+                    return Err(Error::EcCurveNotAllowed);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the hash function used by the signature algorithm identified by the
+    /// `AlgorithmIdentifier` value `algorithm` against the policy.
+    pub(crate) fn check_signature_algorithm(
+        &self,
+        algorithm: untrusted::Input<'_>,
+    ) -> Result<(), Error> {
+        let hashes = match self.hash_algorithms {
+            Some(hashes) => hashes,
+            None => return Ok(()),
+        };
+
+        let signature_alg =
+            algorithm.read_all(Error::BadDer, NormalizedAlgorithmIdentifier::from_der)?;
+        if signature_alg == normalize(&alg_id::ED25519) {
+            return Ok(());
+        }
+
+        match hashes
+            .iter()
+            .flat_map(|hash| hash.signature_alg_ids())
+            .any(|alg_id| signature_alg == normalize(alg_id))
+        {
+            true => Ok(()),
+            false => Err(Error::HashAlgorithmNotAllowed),
+        }
+    }
+}
+
+/// The [`AlgorithmPolicy`] for each kind of signed object in a path.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct AlgorithmPolicies<'a> {
+    pub(crate) end_entity: AlgorithmPolicy<'a>,
+    pub(crate) intermediate: AlgorithmPolicy<'a>,
+    pub(crate) trust_anchor: AlgorithmPolicy<'a>,
+    pub(crate) crl: AlgorithmPolicy<'a>,
+}
+
+/// An elliptic curve for [`AlgorithmPolicy::with_ec_curves()`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EcCurve {
+    /// NIST P-256, a.k.a. secp256r1.
+    P256,
+    /// NIST P-384, a.k.a. secp384r1.
+    P384,
+    /// NIST P-521, a.k.a. secp521r1.
+    P521,
+}
+
+impl EcCurve {
+    fn alg_id(self) -> AlgorithmIdentifier {
+        match self {
+            Self::P256 => alg_id::ECDSA_P256,
+            Self::P384 => alg_id::ECDSA_P384,
+            Self::P521 => alg_id::ECDSA_P521,
+        }
+    }
+}
+
+/// A hash function for [`AlgorithmPolicy::with_hash_algorithms()`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// SHA-256.
+    Sha256,
+    /// SHA-384.
+    Sha384,
+    /// SHA-512.
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// The signature algorithms that use this hash function.
+    fn signature_alg_ids(self) -> &'static [AlgorithmIdentifier] {
+        match self {
+            Self::Sha256 => &[
+                alg_id::ECDSA_SHA256,
+                alg_id::RSA_PKCS1_SHA256,
+                alg_id::RSA_PSS_SHA256,
+            ],
+            Self::Sha384 => &[
+                alg_id::ECDSA_SHA384,
+                alg_id::RSA_PKCS1_SHA384,
+                alg_id::RSA_PSS_SHA384,
+            ],
+            Self::Sha512 => &[
+                alg_id::ECDSA_SHA512,
+                alg_id::RSA_PKCS1_SHA512,
+                alg_id::RSA_PSS_SHA512,
+            ],
+        }
+    }
+}
+
+/// The size in bits of the modulus of the DER-encoded RSAPublicKey `key_value`.
+fn rsa_modulus_bits(key_value: untrusted::Input<'_>) -> Result<usize, Error> {
+    let modulus = key_value.read_all(Error::BadDer, |key| {
+        der::nested(key, der::Tag::Sequence, Error::BadDer, |key| {
+            let modulus = der::nonnegative_integer(key)?;
+            der::nonnegative_integer(key)?;
+            Ok(modulus)
+        })
+    })?;
+
+    let modulus = modulus.as_slice_less_safe();
+    let top_bits = match modulus.first() {
+        Some(&first) => (0..8usize)
+            .rev()
+            .find(|&bit| first >> bit != 0)
+            .map_or(0, |bit| bit + 1),
+        None => 0,
+    };
+    Ok(modulus.len().saturating_sub(1) * 8 + top_bits)
+}
+
+fn normalize(alg_id: &AlgorithmIdentifier) -> NormalizedAlgorithmIdentifier<'_> {
+    // The `AlgorithmIdentifier` constants are always well-formed.
+    untrusted::Input::from(alg_id.as_ref())
+        .read_all(Error::BadDer, NormalizedAlgorithmIdentifier::from_der)
+        .unwrap()
+}
+
+// id-ecPublicKey (1.2.840.10045.2.1), from RFC 5480.
+const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::prelude::v1::*;
+
+    fn spki_value(spki: &[u8]) -> untrusted::Input<'_> {
+        untrusted::Input::from(spki)
+            .read_all(Error::BadDer, |spki| {
+                der::expect_tag(spki, der::Tag::Sequence)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn check_key() {
+        let rsa_1024 = include_bytes!("../tests/signatures/rsa_1024_not_supported.spki.der");
+        let rsa_2048 = include_bytes!("../tests/signatures/rsa_2048.spki.der");
+        let rsa_3072 = include_bytes!("../tests/signatures/rsa_3072.spki.der");
+        let p256 = include_bytes!("../tests/signatures/ecdsa_p256.spki.der");
+        let p384 = include_bytes!("../tests/signatures/ecdsa_p384.spki.der");
+        let ed25519 = include_bytes!("../tests/signatures/ed25519.spki.der");
+
+        let policy = AlgorithmPolicy::new().with_min_rsa_modulus_bits(2048);
+        assert_eq!(
+            policy.check_key(spki_value(rsa_1024)),
+            Err(Error::RsaModulusTooSmall {
+                bits: 1024,
+                min_bits: 2048
+            })
+        );
+        assert_eq!(policy.check_key(spki_value(rsa_2048)), Ok(()));
+        assert_eq!(policy.check_key(spki_value(rsa_3072)), Ok(()));
+        assert_eq!(policy.check_key(spki_value(p256)), Ok(()));
+
+        let policy = AlgorithmPolicy::new().with_ec_curves(&[EcCurve::P384]);
+        assert_eq!(
+            policy.check_key(spki_value(p256)),
+            Err(Error::EcCurveNotAllowed)
+        );
+        assert_eq!(policy.check_key(spki_value(p384)), Ok(()));
+        assert_eq!(policy.check_key(spki_value(rsa_1024)), Ok(()));
+        assert_eq!(policy.check_key(spki_value(ed25519)), Ok(()));
+
+        let policy = AlgorithmPolicy::new().with_ec_curves(&[]);
+        assert_eq!(
+            policy.check_key(spki_value(p384)),
+            Err(Error::EcCurveNotAllowed)
+        );
+    }
+
+    #[test]
+    fn rsa_modulus_bits_counts_leading_bits() {
+        // SEQUENCE { INTEGER modulus, INTEGER 65537 }
+        let key = |modulus: &[u8]| {
+            let mut key = vec![0x30, u8::try_from(modulus.len() + 7).unwrap(), 0x02];
+            key.push(u8::try_from(modulus.len()).unwrap());
+            key.extend_from_slice(modulus);
+            key.extend_from_slice(&[0x02, 0x03, 0x01, 0x00, 0x01]);
+            key
+        };
+
+        for (modulus, bits) in [
+            (&[0x00, 0x80, 0x01][..], 16),
+            (&[0x01, 0x00, 0x01], 17),
+            (&[0x7f, 0xff], 15),
+            (&[0x00], 0),
+        ] {
+            let key = key(modulus);
+            assert_eq!(
+                rsa_modulus_bits(untrusted::Input::from(&key)),
+                Ok(bits),
+                "{modulus:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn check_signature_algorithm() {
+        let check = |policy: &AlgorithmPolicy<'_>, alg_id: AlgorithmIdentifier| {
+            policy.check_signature_algorithm(untrusted::Input::from(alg_id.as_ref()))
+        };
+
+        let policy = AlgorithmPolicy::new();
+        assert_eq!(check(&policy, alg_id::ECDSA_SHA256), Ok(()));
+
+        let policy = AlgorithmPolicy::new()
+            .with_hash_algorithms(&[HashAlgorithm::Sha384, HashAlgorithm::Sha512]);
+        for allowed in [
+            alg_id::ECDSA_SHA384,
+            alg_id::ECDSA_SHA512,
+            alg_id::RSA_PKCS1_SHA384,
+            alg_id::RSA_PSS_SHA512,
+            alg_id::ED25519,
+        ] {
+            assert_eq!(check(&policy, allowed), Ok(()));
+        }
+        for disallowed in [
+            alg_id::ECDSA_SHA256,
+            alg_id::RSA_PKCS1_SHA256,
+            alg_id::RSA_PSS_SHA256,
+            // Not a signature algorithm at all.
+            alg_id::RSA_ENCRYPTION,
+        ] {
+            assert_eq!(
+                check(&policy, disallowed),
+                Err(Error::HashAlgorithmNotAllowed)
+            );
+        }
+    }
+
+    #[cfg(all(feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+    #[test]
+    fn policy_per_role() {
+        use crate::crl::{BorrowedCertRevocationList, CertRevocationList};
+        use crate::crl::{RevocationCheckDepth, RevocationOptionsBuilder};
+        use crate::end_entity::EndEntityCert;
+        use crate::test_utils::{issuer_params, make_end_entity, make_issuer};
+        use crate::trust_anchor::anchor_from_trusted_cert;
+        use crate::verify_cert::{KeyUsage, VerificationOptions};
+        use pki_types::UnixTime;
+        use rcgen::KeyPair;
+
+        // A P-256 trust anchor signs a P-384 intermediate using SHA-256, which signs a P-256
+        // end-entity certificate and a CRL using SHA-384.
+        let trust_anchor = make_issuer("Trust Anchor");
+        let anchors = [anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap()];
+        let intermediate_key = KeyPair::generate_for(&rcgen::PKCS_ECDSA_P384_SHA384).unwrap();
+        let intermediate = issuer_params("Intermediate")
+            .signed_by(
+                &intermediate_key,
+                &trust_anchor.cert,
+                &trust_anchor.key_pair,
+            )
+            .unwrap();
+        let ee = make_end_entity(&intermediate, &intermediate_key);
+        let ee_cert = EndEntityCert::try_from(ee.cert.der()).unwrap();
+        let intermediates = [intermediate.der().clone()];
+
+        let crl = rcgen::CertificateRevocationListParams {
+            this_update: rcgen::date_time_ymd(1986, 1, 1),
+            next_update: rcgen::date_time_ymd(1987, 1, 1),
+            crl_number: rcgen::SerialNumber::from(1),
+            issuing_distribution_point: None,
+            revoked_certs: Vec::new(),
+            key_identifier_method: rcgen::KeyIdMethod::Sha256,
+        }
+        .signed_by(&intermediate, &intermediate_key)
+        .unwrap();
+        let crl =
+            CertRevocationList::from(BorrowedCertRevocationList::from_der(crl.der()).unwrap());
+        let crls = [&crl];
+        let revocation = RevocationOptionsBuilder::new(&crls)
+            .unwrap()
+            .with_depth(RevocationCheckDepth::EndEntity)
+            .build();
+
+        let verify = |options: VerificationOptions<'_>| {
+            ee_cert
+                .verify_for_usage_with_options(
+                    crate::ALL_VERIFICATION_ALGS,
+                    &anchors,
+                    &intermediates,
+                    UnixTime::since_unix_epoch(core::time::Duration::from_secs(0x1fed_f00d)),
+                    KeyUsage::server_auth(),
+                    Some(revocation),
+                    None,
+                    &options,
+                )
+                .map(|_| ())
+        };
+
+        let p256 = AlgorithmPolicy::new().with_ec_curves(&[EcCurve::P256]);
+        let p384 = AlgorithmPolicy::new().with_ec_curves(&[EcCurve::P384]);
+        let sha256 = AlgorithmPolicy::new().with_hash_algorithms(&[HashAlgorithm::Sha256]);
+        let sha384 = AlgorithmPolicy::new().with_hash_algorithms(&[HashAlgorithm::Sha384]);
+        let options = VerificationOptions::new();
+
+        assert_eq!(verify(options), Ok(()));
+
+        assert_eq!(
+            verify(options.with_end_entity_algorithm_policy(p256)),
+            Ok(())
+        );
+        assert_eq!(
+            verify(options.with_end_entity_algorithm_policy(sha384)),
+            Ok(())
+        );
+        assert_eq!(
+            verify(options.with_end_entity_algorithm_policy(p384)),
+            Err(Error::EcCurveNotAllowed)
+        );
+        assert_eq!(
+            verify(options.with_end_entity_algorithm_policy(sha256)),
+            Err(Error::HashAlgorithmNotAllowed)
+        );
+
+        assert_eq!(
+            verify(options.with_intermediate_algorithm_policy(p384)),
+            Ok(())
+        );
+        assert_eq!(
+            verify(options.with_intermediate_algorithm_policy(sha256)),
+            Ok(())
+        );
+        assert_eq!(
+            verify(options.with_intermediate_algorithm_policy(p256)),
+            Err(Error::EcCurveNotAllowed)
+        );
+        assert_eq!(
+            verify(options.with_intermediate_algorithm_policy(sha384)),
+            Err(Error::HashAlgorithmNotAllowed)
+        );
+
+        // The trust anchor has a P-256 key, and isn't subject to the intermediate policy.
+        assert_eq!(
+            verify(options.with_trust_anchor_algorithm_policy(p256)),
+            Ok(())
+        );
+        assert_eq!(
+            verify(options.with_trust_anchor_algorithm_policy(sha384)),
+            Ok(())
+        );
+        assert_eq!(
+            verify(options.with_trust_anchor_algorithm_policy(p384)),
+            Err(Error::EcCurveNotAllowed)
+        );
+
+        assert_eq!(verify(options.with_crl_algorithm_policy(p384)), Ok(()));
+        assert_eq!(verify(options.with_crl_algorithm_policy(sha384)), Ok(()));
+        assert_eq!(
+            verify(options.with_crl_algorithm_policy(p256)),
+            Err(Error::EcCurveNotAllowed)
+        );
+        assert_eq!(
+            verify(options.with_crl_algorithm_policy(sha256)),
+            Err(Error::HashAlgorithmNotAllowed)
+        );
+    }
+}
@@ -14,7 +14,7 @@
 
 #[cfg(feature = "alloc")]
 use pki_types::SubjectPublicKeyInfoDer;
-use pki_types::{CertificateDer, DnsName};
+use pki_types::{CertificateDer, DnsName, UnixTime};
 
 use crate::der::{self, DerIterator, FromDer, Tag, CONSTRUCTED, CONTEXT_SPECIFIC};
 use crate::error::{DerTypeId, Error};
@@ -24,6 +24,7 @@ use crate::subject_name::{GeneralName, NameIterator, WildcardDnsNameRef};
 use crate::x509::{remember_extension, set_extension_once, DistributionPointName, Extension};
 
 /// A parsed X509 certificate.
+#[derive(Clone)]
 pub struct Cert<'a> {
     pub(crate) serial: untrusted::Input<'a>,
     pub(crate) signed_data: SignedData<'a>,
@@ -42,6 +43,26 @@ pub struct Cert<'a> {
     pub(crate) name_constraints: Option<untrusted::Input<'a>>,
     pub(crate) subject_alt_name: Option<untrusted::Input<'a>>,
     pub(crate) crl_distribution_points: Option<untrusted::Input<'a>>,
+    // authority key identifier (AKID) and subject key identifier (SKID) extensions (if any). These
+    // are only used as hints to prioritize candidate issuers during path building, never to
+    // reject a path (for more detail see `KeyIdMatch` in `verify_cert.rs`).
+    pub(crate) authority_key_id: Option<untrusted::Input<'a>>,
+    pub(crate) subject_key_id: Option<untrusted::Input<'a>>,
+    // certificate policy extensions (if any). These are only processed when the caller asks for
+    // policy processing (for more detail see `policy.rs`); otherwise a path containing a
+    // certificate with any of them marked critical is rejected. A malformed non-critical one is
+    // remembered as an error that is only reported by policy processing.
+    pub(crate) certificate_policies: Option<untrusted::Input<'a>>,
+    pub(crate) policy_mappings: Option<untrusted::Input<'a>>,
+    pub(crate) policy_constraints: Option<untrusted::Input<'a>>,
+    pub(crate) inhibit_any_policy: Option<untrusted::Input<'a>>,
+    pub(crate) critical_policy_extension: bool,
+    pub(crate) invalid_policy_extension: Option<Error>,
+    // TLS feature extension (if any), the contents of a SEQUENCE OF INTEGER validated when
+    // parsing (for more detail see `Cert::tls_features`). A malformed or repeated non-critical
+    // one is remembered, and treated as requiring a stapled OCSP response.
+    pub(crate) tls_feature: Option<untrusted::Input<'a>>,
+    pub(crate) invalid_tls_feature: bool,
 
     der: CertificateDer<'a>,
 }
@@ -100,6 +121,16 @@ impl<'a> Cert<'a> {
                     name_constraints: None,
                     subject_alt_name: None,
                     crl_distribution_points: None,
+                    authority_key_id: None,
+                    subject_key_id: None,
+                    certificate_policies: None,
+                    policy_mappings: None,
+                    policy_constraints: None,
+                    inhibit_any_policy: None,
+                    critical_policy_extension: false,
+                    invalid_policy_extension: None,
+                    tls_feature: None,
+                    invalid_tls_feature: false,
 
                     der: CertificateDer::from(cert_der.as_slice_less_safe()),
                 };
@@ -186,6 +217,23 @@ impl<'a> Cert<'a> {
         ))
     }
 
+    /// Returns the start of the certificate's validity period (notBefore).
+    pub(crate) fn not_before(&self) -> Result<UnixTime, Error> {
+        self.validity.read_all(Error::BadDer, |value| {
+            let not_before = UnixTime::from_der(value)?;
+            UnixTime::from_der(value)?; // notAfter
+            Ok(not_before)
+        })
+    }
+
+    /// Returns the end of the certificate's validity period (notAfter).
+    pub(crate) fn not_after(&self) -> Result<UnixTime, Error> {
+        self.validity.read_all(Error::BadDer, |value| {
+            UnixTime::from_der(value)?; // notBefore
+            UnixTime::from_der(value)
+        })
+    }
+
     /// Returns an iterator over the certificate's cRLDistributionPoints extension values, if any.
     pub(crate) fn crl_distribution_points(
         &self,
@@ -193,6 +241,59 @@ impl<'a> Cert<'a> {
         self.crl_distribution_points.map(DerIterator::new)
     }
 
+    /// Returns the keyIdentifier of the certificate's authorityKeyIdentifier extension, if any.
+    ///
+    /// A malformed extension value is treated as if the keyIdentifier were absent: the value is
+    /// only ever used as a hint for path building.
+    pub(crate) fn authority_key_identifier(&self) -> Option<untrusted::Input<'a>> {
+        // RFC 5280 section §4.2.1.1:
+        //   AuthorityKeyIdentifier ::= SEQUENCE {
+        //      keyIdentifier             [0] KeyIdentifier           OPTIONAL,
+        //      authorityCertIssuer       [1] GeneralNames            OPTIONAL,
+        //      authorityCertSerialNumber [2] CertificateSerialNumber OPTIONAL  }
+        const KEY_IDENTIFIER_TAG: u8 = CONTEXT_SPECIFIC;
+
+        let mut reader = untrusted::Reader::new(self.authority_key_id?);
+        match der::read_tag_and_get_value(&mut reader) {
+            Ok((KEY_IDENTIFIER_TAG, key_id)) => Some(key_id),
+            _ => None,
+        }
+    }
+
+    /// Returns the certificate's subjectKeyIdentifier extension value, if any.
+    pub(crate) fn subject_key_identifier(&self) -> Option<untrusted::Input<'a>> {
+        self.subject_key_id
+    }
+
+    /// Returns the TLS extension types listed in the certificate's TLS feature extension, if any.
+    ///
+    /// Nothing is listed for a malformed TLS feature extension. See RFC 7633[^1] for more detail.
+    ///
+    /// [^1]: <https://www.rfc-editor.org/rfc/rfc7633>
+    pub fn tls_features(&self) -> impl Iterator<Item = u16> + '_ {
+        let mut reader =
+            untrusted::Reader::new(self.tls_feature.unwrap_or(untrusted::Input::from(&[])));
+        // The features were validated when parsing, so reading them can't fail.
+        core::iter::from_fn(move || match reader.at_end() {
+            true => None,
+            false => tls_feature(&mut reader).ok(),
+        })
+    }
+
+    /// Returns `true` if the certificate's TLS feature extension requires a stapled OCSP
+    /// response (i.e. the `status_request` or `status_request_v2` extensions), also known as
+    /// "OCSP Must-Staple".
+    ///
+    /// A malformed or repeated TLS feature extension may be hiding such a requirement, so it is
+    /// treated as requiring a stapled OCSP response. Certificates with one marked critical fail
+    /// to parse.
+    pub fn must_staple(&self) -> bool {
+        self.invalid_tls_feature
+            || self
+                .tls_features()
+                .any(|feature| matches!(feature, STATUS_REQUEST | STATUS_REQUEST_V2))
+    }
+
     /// Raw DER encoded representation of the certificate.
     pub fn der(&self) -> CertificateDer<'a> {
         self.der.clone() // This is cheap, just cloning a reference.
@@ -237,12 +338,44 @@ fn remember_cert_extension<'a>(
     cert: &mut Cert<'a>,
     extension: &Extension<'a>,
 ) -> Result<(), Error> {
-    // We don't do anything with certificate policies so we can safely ignore
-    // all policy-related stuff. We assume that the policy-related extensions
-    // are not marked critical.
+    // The TLS feature extension is not under the id-ce arc handled by `remember_extension`.
+    if public_values_eq(extension.id, ID_PE_TLS_FEATURE.into()) {
+        let result = set_extension_once(&mut cert.tls_feature, || {
+            extension.value.read_all(Error::BadDer, |value| {
+                // RFC 7633 section 6:
+                //   Features ::= SEQUENCE OF INTEGER
+                let features = der::expect_tag(value, Tag::Sequence)?;
+                features.read_all(Error::BadDer, |features| {
+                    while !features.at_end() {
+                        tls_feature(features)?;
+                    }
+                    Ok(())
+                })?;
+                Ok(features)
+            })
+        });
+
+        return match result {
+            // Unlike the key identifiers, a malformed or repeated non-critical TLS feature
+            // extension can't just be ignored, since it may require a stapled OCSP response. It
+            // is remembered so that `Cert::must_staple` fails closed.
+            Err(_) if !extension.critical => {
+                cert.invalid_tls_feature = true;
+                Ok(())
+            }
+            result => result,
+        };
+    }
 
     remember_extension(extension, |id| {
+        if matches!(id, 32 | 33 | 36 | 54) && extension.critical {
+            cert.critical_policy_extension = true;
+        }
+
         let out = match id {
+            // id-ce-subjectKeyIdentifier 2.5.29.14
+            14 => &mut cert.subject_key_id,
+
             // id-ce-keyUsage 2.5.29.15.
             15 => &mut cert.key_usage,
 
@@ -258,25 +391,74 @@ fn remember_cert_extension<'a>(
             // id-ce-cRLDistributionPoints 2.5.29.31
             31 => &mut cert.crl_distribution_points,
 
+            // id-ce-certificatePolicies 2.5.29.32
+            32 => &mut cert.certificate_policies,
+
+            // id-ce-policyMappings 2.5.29.33
+            33 => &mut cert.policy_mappings,
+
+            // id-ce-authorityKeyIdentifier 2.5.29.35
+            35 => &mut cert.authority_key_id,
+
+            // id-ce-policyConstraints 2.5.29.36
+            36 => &mut cert.policy_constraints,
+
             // id-ce-extKeyUsage 2.5.29.37
             37 => &mut cert.eku,
 
+            // id-ce-inhibitAnyPolicy 2.5.29.54
+            54 => &mut cert.inhibit_any_policy,
+
             // Unsupported extension
             _ => return extension.unsupported(),
         };
 
-        set_extension_once(out, || {
+        let result = set_extension_once(out, || {
             extension.value.read_all(Error::BadDer, |value| match id {
                 // Unlike the other extensions we remember KU is a BitString and not a Sequence. We
                 // read the raw bytes here and parse at the time of use.
                 15 => Ok(value.read_bytes_to_end()),
+                // The SKID is a bare KeyIdentifier OCTET STRING.
+                14 => der::expect_tag(value, Tag::OctetString),
+                // inhibitAnyPolicy is a bare SkipCerts INTEGER.
+                54 => der::expect_tag(value, Tag::Integer),
                 // All other remembered certificate extensions are wrapped in a Sequence.
                 _ => der::expect_tag(value, Tag::Sequence),
             })
-        })
+        });
+
+        match (id, result) {
+            // The key identifiers are only hints for path building: a malformed or repeated
+            // non-critical one is ignored, just as it would be if it were unsupported.
+            (14 | 35, Err(_)) if !extension.critical => Ok(()),
+            // The policy extensions are only consulted when policy processing is enabled, so a
+            // malformed or repeated non-critical one is only an error in that case.
+            (32 | 33 | 36 | 54, Err(err)) if !extension.critical => {
+                cert.invalid_policy_extension.get_or_insert(err);
+                Ok(())
+            }
+            (_, result) => result,
+        }
     })
 }
 
+/// Read a TLS extension type from the TLS feature extension.
+fn tls_feature(reader: &mut untrusted::Reader<'_>) -> Result<u16, Error> {
+    match *der::nonnegative_integer(reader)?.as_slice_less_safe() {
+        [b] => Ok(u16::from(b)),
+        [hi, lo] => Ok(u16::from_be_bytes([hi, lo])),
+        _ => Err(Error::BadDer),
+    }
+}
+
+// id-pe-tlsfeature OBJECT IDENTIFIER ::= { id-pe 24 }
+const ID_PE_TLS_FEATURE: &[u8] = &oid!(1, 3, 6, 1, 5, 5, 7, 1, 24);
+
+// The TLS extension types of the status_request (RFC 6066) and status_request_v2 (RFC 6961)
+// extensions.
+const STATUS_REQUEST: u16 = 5;
+const STATUS_REQUEST_V2: u16 = 17;
+
 /// A certificate revocation list (CRL) distribution point, describing a source of
 /// CRL information for a given certificate as described in RFC 5280 section 4.2.3.13[^1].
 ///
@@ -378,6 +560,157 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_key_identifiers_read() {
+        let ee = include_bytes!("../tests/netflix/ee.der");
+        let inter = include_bytes!("../tests/netflix/inter.der");
+        let ee = Cert::from_der(untrusted::Input::from(ee)).expect("failed to parse EE cert");
+        let inter =
+            Cert::from_der(untrusted::Input::from(inter)).expect("failed to parse certificate");
+
+        let expected_key_id = [
+            0x5f, 0x60, 0xcf, 0x61, 0x90, 0x55, 0xdf, 0x84, 0x43, 0x14, 0x8a, 0x60, 0x2a, 0xb2,
+            0xf5, 0x7a, 0xf4, 0x43, 0x18, 0xef,
+        ];
+        assert_eq!(
+            ee.authority_key_identifier()
+                .map(|id| id.as_slice_less_safe()),
+            Some(expected_key_id.as_slice())
+        );
+        assert_eq!(
+            inter
+                .subject_key_identifier()
+                .map(|id| id.as_slice_less_safe()),
+            Some(expected_key_id.as_slice())
+        );
+        assert!(ee.subject_key_identifier().is_none());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_key_identifiers_lenient() {
+        use crate::test_utils::{end_entity_params, RCGEN_SIGNATURE_ALG};
+
+        let with_extensions = |extensions: &[(u64, bool, &[u8])]| {
+            let mut params = end_entity_params(vec!["example.com".into()]);
+            params.is_ca = rcgen::IsCa::NoCa;
+            params.custom_extensions = extensions
+                .iter()
+                .map(|&(id, critical, value)| {
+                    let mut ext =
+                        rcgen::CustomExtension::from_oid_content(&[2, 5, 29, id], value.to_vec());
+                    ext.set_criticality(critical);
+                    ext
+                })
+                .collect();
+            let key_pair = rcgen::KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+            params.self_signed(&key_pair).unwrap().der().to_vec()
+        };
+
+        const SKID: u64 = 14;
+        const AKID: u64 = 35;
+        let skid = [0x04, 0x01, 0xaa];
+        let akid = [0x30, 0x03, 0x80, 0x01, 0xbb];
+
+        // Malformed non-critical key identifiers are ignored.
+        let der = with_extensions(&[(SKID, false, &[0x30, 0x00]), (AKID, false, &[0x04, 0x00])]);
+        let cert = Cert::from_der(untrusted::Input::from(&der)).unwrap();
+        assert!(cert.subject_key_identifier().is_none());
+        assert!(cert.authority_key_identifier().is_none());
+
+        // Repeated non-critical key identifiers are ignored after the first.
+        let der = with_extensions(&[
+            (SKID, false, &skid),
+            (SKID, false, &[0x04, 0x01, 0xcc]),
+            (AKID, false, &akid),
+            (AKID, false, &[0x30, 0x00]),
+        ]);
+        let cert = Cert::from_der(untrusted::Input::from(&der)).unwrap();
+        assert_eq!(
+            cert.subject_key_identifier()
+                .map(|id| id.as_slice_less_safe()),
+            Some([0xaa].as_slice())
+        );
+        assert_eq!(
+            cert.authority_key_identifier()
+                .map(|id| id.as_slice_less_safe()),
+            Some([0xbb].as_slice())
+        );
+
+        // Critical ones must still be well-formed.
+        let der = with_extensions(&[(SKID, true, &[0x30, 0x00])]);
+        assert_eq!(
+            Cert::from_der(untrusted::Input::from(&der)).err(),
+            Some(Error::BadDer)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_tls_features_read() {
+        use crate::test_utils::{end_entity_params, RCGEN_SIGNATURE_ALG};
+
+        let with_extensions = |extensions: &[(&[u8], bool)]| {
+            let mut params = end_entity_params(vec!["example.com".into()]);
+            params.custom_extensions = extensions
+                .iter()
+                .map(|(features, critical)| {
+                    let mut extension = rcgen::CustomExtension::from_oid_content(
+                        &[1, 3, 6, 1, 5, 5, 7, 1, 24],
+                        [&[0x30, features.len().try_into().unwrap()], *features].concat(),
+                    );
+                    extension.set_criticality(*critical);
+                    extension
+                })
+                .collect();
+            let key_pair = rcgen::KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+            params.self_signed(&key_pair).unwrap().der().to_vec()
+        };
+        let with_features = |features: &[u8]| with_extensions(&[(features, false)]);
+
+        // status_request
+        let der = with_features(&[0x02, 0x01, 0x05]);
+        let cert = Cert::from_der(untrusted::Input::from(&der)).unwrap();
+        assert_eq!(cert.tls_features().collect::<Vec<_>>(), [5]);
+        assert!(cert.must_staple());
+
+        // status_request_v2, and a feature with a necessary leading zero.
+        let der = with_features(&[0x02, 0x01, 0x11, 0x02, 0x03, 0x00, 0xff, 0x01]);
+        let cert = Cert::from_der(untrusted::Input::from(&der)).unwrap();
+        assert_eq!(cert.tls_features().collect::<Vec<_>>(), [17, 0xff01]);
+        assert!(cert.must_staple());
+
+        // Other features do not require stapling.
+        let der = with_features(&[0x02, 0x01, 0x12]);
+        let cert = Cert::from_der(untrusted::Input::from(&der)).unwrap();
+        assert!(!cert.must_staple());
+
+        // Negative or oversized features make the extension malformed. Unless it is critical,
+        // that is treated as requiring a stapled response.
+        for features in [&[0x02, 0x01, 0xff][..], &[0x02, 0x03, 0x01, 0x00, 0x00]] {
+            let der = with_features(features);
+            let cert = Cert::from_der(untrusted::Input::from(&der)).unwrap();
+            assert_eq!(cert.tls_features().count(), 0);
+            assert!(cert.must_staple());
+
+            let der = with_extensions(&[(features, true)]);
+            assert_eq!(
+                Cert::from_der(untrusted::Input::from(&der)).err(),
+                Some(Error::BadDer)
+            );
+        }
+
+        // So is a repeated extension.
+        let der = with_extensions(&[(&[0x02, 0x01, 0x12], false), (&[0x02, 0x01, 0x12], false)]);
+        let cert = Cert::from_der(untrusted::Input::from(&der)).unwrap();
+        assert!(cert.must_staple());
+
+        let ee = include_bytes!("../tests/netflix/ee.der");
+        let cert = Cert::from_der(untrusted::Input::from(ee)).unwrap();
+        assert_eq!(cert.tls_features().count(), 0);
+        assert!(!cert.must_staple());
+    }
+
     #[cfg(feature = "alloc")]
     #[test]
     fn test_spki_read() {
@@ -14,13 +14,22 @@
 
 use pki_types::{SignatureVerificationAlgorithm, UnixTime};
 
+use crate::algorithm_policy::AlgorithmPolicy;
 use crate::error::Error;
 use crate::verify_cert::{Budget, PathNode, Role};
 use crate::{der, public_values_eq};
 
 use core::fmt::Debug;
 
+mod ocsp;
+use ocsp::CertStatus;
+#[cfg(feature = "alloc")]
+pub use ocsp::OwnedOcspResponse;
+pub(crate) use ocsp::StapledOcsp;
+pub use ocsp::{BorrowedOcspResponse, OcspHasher, OcspResponse};
+
 mod types;
+use types::compare_crl_numbers;
 pub use types::{
     BorrowedCertRevocationList, BorrowedRevokedCert, CertRevocationList, RevocationReason,
 };
@@ -32,6 +41,8 @@ pub use types::{OwnedCertRevocationList, OwnedRevokedCert};
 pub struct RevocationOptionsBuilder<'a> {
     crls: &'a [&'a CertRevocationList<'a>],
 
+    ocsp_responses: &'a [&'a OcspResponse<'a>],
+
     depth: RevocationCheckDepth,
 
     status_policy: UnknownStatusPolicy,
@@ -43,6 +54,19 @@ impl<'a> RevocationOptionsBuilder<'a> {
     /// Create a builder that will perform revocation checking using the provided certificate
     /// revocation lists (CRLs). At least one CRL must be provided.
     ///
+    /// The CRLs may include delta CRLs. The revocation status of a certificate is determined
+    /// using a complete CRL, combined with the most recent delta CRL updating it, if any.
+    ///
+    /// The CRLs may also include indirect CRLs, issued by a CRL issuer named in the cRLIssuer
+    /// field of a certificate's CRL distribution point. The CRL issuer's certificate must be
+    /// among the trust anchors or intermediate certificates given for verification, and is
+    /// validated by building a path from it to a trust anchor.
+    ///
+    /// The revocation status of the CRL issuer's certificate, and of the certificates in its
+    /// path, is not checked: a CRL issuer whose certificate has been revoked is still trusted
+    /// to issue indirect CRLs until its certificate expires. Only use indirect CRLs from CRL
+    /// issuers whose keys are not expected to be compromised.
+    ///
     /// Use [RevocationOptionsBuilder::build] to create a [RevocationOptions] instance.
     ///
     /// By default revocation checking will be performed on both the end-entity (leaf) certificate
@@ -63,12 +87,57 @@ impl<'a> RevocationOptionsBuilder<'a> {
 
         Ok(Self {
             crls,
+            ocsp_responses: &[],
+            depth: RevocationCheckDepth::Chain,
+            status_policy: UnknownStatusPolicy::Deny,
+            expiration_policy: ExpirationPolicy::Ignore,
+        })
+    }
+
+    /// Create a builder that will perform revocation checking using the provided OCSP
+    /// responses, without any CRLs. At least one response must be provided.
+    ///
+    /// The defaults are the same as for [RevocationOptionsBuilder::new].
+    pub fn from_ocsp_responses(
+        responses: &'a [&'a OcspResponse<'a>],
+    ) -> Result<Self, OcspResponsesRequired> {
+        if responses.is_empty() {
+            return Err(OcspResponsesRequired(()));
+        }
+
+        Ok(Self {
+            crls: &[],
+            ocsp_responses: responses,
             depth: RevocationCheckDepth::Chain,
             status_policy: UnknownStatusPolicy::Deny,
             expiration_policy: ExpirationPolicy::Ignore,
         })
     }
 
+    /// Customize the OCSP responses used to check revocation status, in addition to CRLs.
+    ///
+    /// For each certificate, the OCSP responses are consulted first. A certificate is
+    /// considered not revoked if a response covering it says it is good, and revoked if one
+    /// says it is revoked. Otherwise, its revocation status is determined using the CRLs.
+    ///
+    /// A response covering a certificate is skipped unless its `CertID` matches the
+    /// certificate's issuer, it is signed by the issuer or by a responder the issuer delegated
+    /// to, and it is valid at the verification time. Matching the `CertID` requires the hash
+    /// functions set using [`VerificationOptions::with_ocsp_hashers()`]. See
+    /// [`BorrowedOcspResponse::from_der()`] for the details.
+    ///
+    /// The nextUpdate field of OCSP responses is always enforced, regardless of the
+    /// [ExpirationPolicy]. A response without a nextUpdate field indicates that newer
+    /// information is always available, but webpki can't tell how old is too old, so it is
+    /// accepted from its thisUpdate time onwards, without any maximum age. Callers should only
+    /// pass responses that they consider recent enough.
+    ///
+    /// [`VerificationOptions::with_ocsp_hashers()`]: crate::VerificationOptions::with_ocsp_hashers
+    pub fn with_ocsp_responses(mut self, responses: &'a [&'a OcspResponse<'a>]) -> Self {
+        self.ocsp_responses = responses;
+        self
+    }
+
     /// Customize the depth at which revocation checking will be performed, controlling
     /// whether only the end-entity (leaf) certificate in the chain to a trust anchor will
     /// have its revocation status checked, or whether the intermediate certificates will as well.
@@ -93,6 +162,7 @@ impl<'a> RevocationOptionsBuilder<'a> {
     pub fn build(self) -> RevocationOptions<'a> {
         RevocationOptions {
             crls: self.crls,
+            ocsp_responses: self.ocsp_responses,
             depth: self.depth,
             status_policy: self.status_policy,
             expiration_policy: self.expiration_policy,
@@ -105,12 +175,18 @@ impl<'a> RevocationOptionsBuilder<'a> {
 #[derive(Debug, Copy, Clone)]
 pub struct RevocationOptions<'a> {
     pub(crate) crls: &'a [&'a CertRevocationList<'a>],
+    pub(crate) ocsp_responses: &'a [&'a OcspResponse<'a>],
     pub(crate) depth: RevocationCheckDepth,
     pub(crate) status_policy: UnknownStatusPolicy,
     pub(crate) expiration_policy: ExpirationPolicy,
 }
 
 impl RevocationOptions<'_> {
+    /// Check the revocation status of the certificate at `path`.
+    ///
+    /// CRLs issued by the certificate issuer are verified using `issuer_spki`, while the
+    /// signatures of indirect CRLs issued by another CRL issuer are verified by
+    /// `verify_indirect_crl`.
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn check(
         &self,
@@ -118,7 +194,10 @@ impl RevocationOptions<'_> {
         issuer_subject: untrusted::Input<'_>,
         issuer_spki: untrusted::Input<'_>,
         issuer_ku: Option<untrusted::Input<'_>>,
+        verify_indirect_crl: &dyn Fn(&CertRevocationList<'_>, &mut Budget) -> Result<(), Error>,
+        ocsp_hashers: &[&dyn OcspHasher],
         supported_sig_algs: &[&dyn SignatureVerificationAlgorithm],
+        algorithms: &AlgorithmPolicy<'_>,
         budget: &mut Budget,
         time: UnixTime,
     ) -> Result<Option<CertNotRevoked>, Error> {
@@ -130,10 +209,41 @@ impl RevocationOptions<'_> {
             return Ok(None);
         }
 
-        let crl = self
-            .crls
-            .iter()
-            .find(|candidate_crl| candidate_crl.authoritative(path));
+        // Responses that can't be used for this certificate, e.g. because they were signed by
+        // another issuer using the same serial number, or have expired, are skipped. If the
+        // status remains unknown, the most specific reason is reported instead.
+        let mut ocsp_error: Option<Error> = None;
+        for response in self.ocsp_responses {
+            let status = response.status(
+                path.cert.serial,
+                issuer_subject,
+                issuer_spki,
+                ocsp_hashers,
+                supported_sig_algs,
+                algorithms,
+                budget,
+                time,
+            );
+
+            match status {
+                Ok(Some(CertStatus::Good)) => return Ok(Some(CertNotRevoked::assertion())),
+                Ok(Some(CertStatus::Revoked)) => return Err(Error::CertRevoked),
+                // A response with unknown status, or none at all, defers to the CRLs.
+                Ok(Some(CertStatus::Unknown) | None) => {}
+                Err(err) if err.is_fatal() => return Err(err),
+                Err(err) => {
+                    ocsp_error = Some(match ocsp_error {
+                        Some(prev) => prev.most_specific(err),
+                        None => err,
+                    })
+                }
+            }
+        }
+
+        // Delta CRLs only list changes, so can't determine the status of a certificate alone.
+        let crl = self.crls.iter().find(|candidate_crl| {
+            candidate_crl.base_crl_number().is_none() && candidate_crl.authoritative(path)
+        });
 
         use UnknownStatusPolicy::*;
         let crl = match (crl, self.status_policy) {
@@ -142,26 +252,66 @@ impl RevocationOptions<'_> {
             // was not confirmed as CertNotRevoked, but that this isn't an error condition.
             (None, Allow) => return Ok(None),
             // Otherwise, this is an error condition based on the provided policy.
-            (None, _) => return Err(Error::UnknownRevocationStatus),
+            (None, _) => return Err(ocsp_error.unwrap_or(Error::UnknownRevocationStatus)),
         };
 
-        // Verify the CRL signature with the issuer SPKI.
-        // TODO(XXX): consider whether we can refactor so this happens once up-front, instead
-        //            of per-lookup.
-        //            https://github.com/rustls/webpki/issues/81
-        crl.verify_signature(supported_sig_algs, issuer_spki, budget)
-            .map_err(crl_signature_err)?;
+        let verify_crl = |crl: &CertRevocationList<'_>, budget: &mut Budget| {
+            if !public_values_eq(untrusted::Input::from(crl.issuer()), issuer_subject) {
+                return verify_indirect_crl(crl, budget);
+            }
+
+            // Verify the CRL signature with the issuer SPKI.
+            // TODO(XXX): consider whether we can refactor so this happens once up-front, instead
+            //            of per-lookup.
+            //            https://github.com/rustls/webpki/issues/81
+            crl.verify_signature(supported_sig_algs, algorithms, issuer_spki, budget)
+                .map_err(crl_signature_err)?;
+
+            // Verify that if the issuer has a KeyUsage bitstring it asserts cRLSign.
+            KeyUsageMode::CrlSign.check(issuer_ku)
+        };
+
+        verify_crl(crl, budget)?;
 
         if self.expiration_policy == ExpirationPolicy::Enforce {
             crl.check_expiration(time)?;
         }
 
-        // Verify that if the issuer has a KeyUsage bitstring it asserts cRLSign.
-        KeyUsageMode::CrlSign.check(issuer_ku)?;
+        // Combine the CRL with the most recent delta CRL updating it, if any.
+        let cert_serial = path.cert.serial.as_slice_less_safe();
+        let cert_issuer = path.cert.issuer.as_slice_less_safe();
+        let delta = self
+            .crls
+            .iter()
+            .filter(|candidate| candidate.is_delta_for(crl) && candidate.authoritative(path))
+            .max_by(|a, b| {
+                compare_crl_numbers(
+                    a.crl_number().unwrap_or_default(),
+                    b.crl_number().unwrap_or_default(),
+                )
+            });
+        if let Some(delta) = delta {
+            verify_crl(delta, budget)?;
+
+            if self.expiration_policy == ExpirationPolicy::Enforce {
+                delta.check_expiration(time)?;
+            }
+
+            // RFC 5280 §5.3.1:
+            //   removeFromCRL (8) is used only in delta CRLs and indicates that a
+            //   certificate is to be removed from a CRL, either because the
+            //   certificate expired or was removed from hold.
+            // Any other entry in the delta CRL means the certificate is revoked.
+            if let Some(revoked_cert) = delta.find_revoked_cert(cert_serial, cert_issuer)? {
+                return match revoked_cert.reason_code {
+                    Some(RevocationReason::RemoveFromCrl) => Ok(Some(CertNotRevoked::assertion())),
+                    _ => Err(Error::CertRevoked),
+                };
+            }
+        }
 
         // Try to find the cert serial in the verified CRL contents.
-        let cert_serial = path.cert.serial.as_slice_less_safe();
-        match crl.find_serial(cert_serial)? {
+        match crl.find_revoked_cert(cert_serial, cert_issuer)? {
             None => Ok(Some(CertNotRevoked::assertion())),
             Some(_) => Err(Error::CertRevoked),
         }
@@ -171,13 +321,13 @@ impl RevocationOptions<'_> {
 // https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.3
 #[repr(u8)]
 #[derive(Clone, Copy)]
-enum KeyUsageMode {
-    // DigitalSignature = 0,
+pub(crate) enum KeyUsageMode {
+    DigitalSignature = 0,
     // ContentCommitment = 1,
-    // KeyEncipherment = 2,
+    KeyEncipherment = 2,
     // DataEncipherment = 3,
     // KeyAgreement = 4,
-    // CertSign = 5,
+    CertSign = 5,
     CrlSign = 6,
     // EncipherOnly = 7,
     // DecipherOnly = 8,
@@ -185,7 +335,7 @@ enum KeyUsageMode {
 
 impl KeyUsageMode {
     // https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.3
-    fn check(self, input: Option<untrusted::Input<'_>>) -> Result<(), Error> {
+    pub(crate) fn check(self, input: Option<untrusted::Input<'_>>) -> Result<(), Error> {
         let bit_string = match input {
             Some(input) => {
                 der::expect_tag(&mut untrusted::Reader::new(input), der::Tag::BitString)?
@@ -198,9 +348,13 @@ impl KeyUsageMode {
 
         let flags = der::bit_string_flags(bit_string)?;
         #[allow(clippy::as_conversions)] // u8 always fits in usize.
-        match flags.bit_set(self as usize) {
-            true => Ok(()),
-            false => Err(Error::IssuerNotCrlSigner),
+        match (flags.bit_set(self as usize), self) {
+            (true, _) => Ok(()),
+            (false, Self::CrlSign) => Err(Error::IssuerNotCrlSigner),
+            (false, Self::CertSign) => Err(Error::IssuerNotCertSigner),
+            (false, Self::DigitalSignature | Self::KeyEncipherment) => {
+                Err(Error::RequiredKeyUsageNotFound)
+            }
         }
     }
 }
@@ -249,13 +403,25 @@ pub enum ExpirationPolicy {
     Ignore,
 }
 
+/// Describes how to handle end-entity certificates whose TLS feature extension requires a
+/// stapled OCSP response ("OCSP Must-Staple", see RFC 7633).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum MustStaplePolicy {
+    /// Require a stapled OCSP response saying that such certificates are good, treating its
+    /// absence as an error condition yielding [Error::RequiredOcspStapleNotFound].
+    Enforce,
+    /// Ignore the TLS feature extension.
+    #[default]
+    Ignore,
+}
+
 // Zero-sized marker type representing positive assertion that revocation status was checked
 // for a certificate and the result was that the certificate is not revoked.
 pub(crate) struct CertNotRevoked(());
 
 impl CertNotRevoked {
     // Construct a CertNotRevoked marker.
-    fn assertion() -> Self {
+    pub(crate) fn assertion() -> Self {
         Self(())
     }
 }
@@ -265,6 +431,11 @@ impl CertNotRevoked {
 /// [RevocationOptions] instance.
 pub struct CrlsRequired(pub(crate) ());
 
+#[derive(Debug, Copy, Clone)]
+/// An opaque error indicating the caller must provide at least one OCSP response when building
+/// a [RevocationOptions] instance using [RevocationOptionsBuilder::from_ocsp_responses].
+pub struct OcspResponsesRequired(pub(crate) ());
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -363,4 +534,424 @@ mod tests {
             std::println!("{:?}", opts.clone());
         }
     }
+
+    #[test]
+    #[cfg(all(feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+    fn delta_crls() {
+        use crate::test_utils::{make_end_entity, make_issuer};
+        use std::prelude::v1::*;
+
+        const KEY_COMPROMISE: Option<u8> = Some(1);
+        const CERTIFICATE_HOLD: Option<u8> = Some(6);
+        const REMOVE_FROM_CRL: Option<u8> = Some(8);
+
+        let issuer = make_issuer("Issuer");
+        let ee = make_end_entity(&issuer.cert, &issuer.key_pair);
+        let serial = serial(&ee.cert);
+        let crl = |number, base: Option<u8>, reason: Option<Option<u8>>| {
+            let mut extensions = vec![crl_extension(20, false, &[0x02, 0x01, number])];
+            if let Some(base) = base {
+                extensions.push(crl_extension(27, true, &[0x02, 0x01, base]));
+            }
+            let entries = match reason {
+                Some(Some(reason)) => vec![crl_entry(
+                    &serial,
+                    &[crl_extension(21, false, &[0x0a, 0x01, reason])],
+                )],
+                Some(None) => vec![crl_entry(&serial, &[])],
+                None => Vec::new(),
+            };
+            let der = signed_crl(&issuer, &entries, &extensions);
+            CertRevocationList::from(OwnedCertRevocationList::from_der(&der).unwrap())
+        };
+        let verify = |crls: &[&CertRevocationList<'_>]| {
+            verify_with_crls(&issuer.cert, &[], ee.cert.der(), crls)
+        };
+
+        let base = crl(1, None, None);
+        assert_eq!(base.crl_number(), Some(&[1][..]));
+        assert_eq!(base.base_crl_number(), None);
+        let delta = crl(2, Some(1), Some(KEY_COMPROMISE));
+        assert_eq!(delta.crl_number(), Some(&[2][..]));
+        assert_eq!(delta.base_crl_number(), Some(&[1][..]));
+
+        // A certificate revoked since the complete CRL is revoked, in either order.
+        assert_eq!(verify(&[&base]), Ok(()));
+        assert_eq!(verify(&[&base, &delta]), Err(Error::CertRevoked));
+        assert_eq!(verify(&[&delta, &base]), Err(Error::CertRevoked));
+
+        // A delta CRL alone doesn't determine revocation status.
+        assert_eq!(verify(&[&delta]), Err(Error::UnknownRevocationStatus));
+
+        // A certificate on hold in the complete CRL remains revoked, unless the delta CRL
+        // removes it.
+        let held = crl(1, None, Some(CERTIFICATE_HOLD));
+        let unchanged = crl(2, Some(1), None);
+        let removed = crl(3, Some(1), Some(REMOVE_FROM_CRL));
+        assert_eq!(verify(&[&held, &unchanged]), Err(Error::CertRevoked));
+        assert_eq!(verify(&[&held, &removed]), Ok(()));
+
+        // The most recent delta CRL is used.
+        let revoked_again = crl(4, Some(1), Some(CERTIFICATE_HOLD));
+        assert_eq!(
+            verify(&[&held, &revoked_again, &removed]),
+            Err(Error::CertRevoked)
+        );
+        assert_eq!(verify(&[&held, &unchanged, &removed]), Ok(()));
+
+        // Delta CRLs that aren't more recent than the complete CRL, or that update a more
+        // recent complete CRL, are not used.
+        let base = crl(2, None, None);
+        assert_eq!(verify(&[&base, &delta]), Ok(()));
+        let delta = crl(4, Some(3), Some(KEY_COMPROMISE));
+        assert_eq!(verify(&[&base, &delta]), Ok(()));
+
+        // A delta CRL must have a CRL number.
+        let der = signed_crl(&issuer, &[], &[crl_extension(27, true, &[0x02, 0x01, 1])]);
+        assert_eq!(
+            BorrowedCertRevocationList::from_der(&der).err(),
+            Some(Error::UnsupportedDeltaCrl)
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+    fn indirect_crls() {
+        use crate::test_utils::{
+            end_entity_params, make_end_entity, make_issuer, tlv, RCGEN_SIGNATURE_ALG,
+        };
+        use rcgen::{CertifiedKey, CustomExtension, KeyPair, KeyUsagePurpose};
+        use std::prelude::v1::*;
+
+        const URI: &[u8] = b"http://crl.example.com/indirect.crl";
+        let full_name = tlv(0xa0, &[&tlv(0xa0, &[&tlv(0x86, &[URI])])]);
+
+        // The CRL issuer need not be a CA, but must be allowed to sign CRLs.
+        let trust_anchor = make_issuer("Trust Anchor");
+        let crl_issuer_params = |key_usage| {
+            let mut params = end_entity_params(Vec::new());
+            params
+                .distinguished_name
+                .push(rcgen::DnType::OrganizationName, "CRL Issuer");
+            params.key_usages = vec![key_usage];
+            params
+        };
+        let key_pair = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let crl_issuer = CertifiedKey {
+            cert: crl_issuer_params(KeyUsagePurpose::CrlSign)
+                .signed_by(&key_pair, &trust_anchor.cert, &trust_anchor.key_pair)
+                .unwrap(),
+            key_pair,
+        };
+
+        // The end-entity certificate names the CRL issuer in its CRL distribution point.
+        let mut ee_params = end_entity_params(vec!["example.com".into()]);
+        let crl_dp = tlv(
+            0x30,
+            &[
+                &full_name,
+                &tlv(0xa2, &[&tlv(0xa4, &[&name(&crl_issuer.cert)])]),
+            ],
+        );
+        ee_params.custom_extensions = vec![CustomExtension::from_oid_content(
+            &[2, 5, 29, 31],
+            tlv(0x30, &[&crl_dp]),
+        )];
+        let ee_key = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let ee = ee_params
+            .signed_by(&ee_key, &trust_anchor.cert, &trust_anchor.key_pair)
+            .unwrap();
+
+        let crl_issuer_serial = serial(&crl_issuer.cert);
+        let serial = serial(&ee);
+        let other_serial = [0x01];
+        let certificate_issuer = |cert: &rcgen::Certificate| {
+            crl_extension(29, true, &tlv(0x30, &[&tlv(0xa4, &[&name(cert)])]))
+        };
+        let idp = crl_extension(28, true, &tlv(0x30, &[&full_name, &[0x84, 0x01, 0xff]]));
+        let crl = |entries: &[Vec<u8>]| {
+            signed_crl(
+                &crl_issuer,
+                entries,
+                &[crl_extension(20, false, &[0x02, 0x01, 1]), idp.clone()],
+            )
+        };
+
+        // Both representations of the CRL should give the same result.
+        let verify = |crl: &[u8], intermediates: &[pki_types::CertificateDer<'_>], ee| {
+            let borrowed = BorrowedCertRevocationList::from_der(crl).unwrap().into();
+            let owned = OwnedCertRevocationList::from_der(crl).unwrap().into();
+            let result = verify_with_crls(&trust_anchor.cert, intermediates, ee, &[&borrowed]);
+            assert_eq!(
+                result,
+                verify_with_crls(&trust_anchor.cert, intermediates, ee, &[&owned])
+            );
+            result
+        };
+        let intermediates = [crl_issuer.cert.der().clone()];
+
+        // Entries are for the CRL issuer's certificates, up to the first entry with a
+        // certificate issuer.
+        let revoked = crl(&[
+            crl_entry(&other_serial, &[]),
+            crl_entry(&serial, &[certificate_issuer(&trust_anchor.cert)]),
+        ]);
+        assert_eq!(
+            verify(&revoked, &intermediates, ee.der()),
+            Err(Error::CertRevoked)
+        );
+        let not_revoked = crl(&[crl_entry(&serial, &[])]);
+        assert_eq!(verify(&not_revoked, &intermediates, ee.der()), Ok(()));
+
+        // The certificate issuer of an entry applies to the following entries.
+        let revoked = crl(&[
+            crl_entry(&other_serial, &[certificate_issuer(&trust_anchor.cert)]),
+            crl_entry(&serial, &[]),
+        ]);
+        assert_eq!(
+            verify(&revoked, &intermediates, ee.der()),
+            Err(Error::CertRevoked)
+        );
+        let not_revoked = crl(&[
+            crl_entry(&other_serial, &[certificate_issuer(&trust_anchor.cert)]),
+            crl_entry(&other_serial, &[certificate_issuer(&crl_issuer.cert)]),
+            crl_entry(&serial, &[]),
+        ]);
+        assert_eq!(verify(&not_revoked, &intermediates, ee.der()), Ok(()));
+
+        // The indirect CRL isn't authoritative for certificates that don't name its issuer.
+        let other_ee = make_end_entity(&trust_anchor.cert, &trust_anchor.key_pair);
+        assert_eq!(
+            verify(&revoked, &intermediates, other_ee.cert.der()),
+            Err(Error::UnknownRevocationStatus)
+        );
+
+        // The CRL issuer must have a valid path to a trust anchor.
+        assert_eq!(
+            verify(&revoked, &[], ee.der()),
+            Err(Error::UnknownCrlIssuer)
+        );
+        let untrusted = make_issuer("Untrusted");
+        let untrusted_crl_issuer = crl_issuer_params(KeyUsagePurpose::CrlSign)
+            .signed_by(&crl_issuer.key_pair, &untrusted.cert, &untrusted.key_pair)
+            .unwrap();
+        assert_eq!(
+            verify(&revoked, &[untrusted_crl_issuer.der().clone()], ee.der()),
+            Err(Error::UnknownCrlIssuer)
+        );
+
+        // The CRL issuer must be allowed to sign CRLs.
+        let not_crl_signer = crl_issuer_params(KeyUsagePurpose::DigitalSignature)
+            .signed_by(
+                &crl_issuer.key_pair,
+                &trust_anchor.cert,
+                &trust_anchor.key_pair,
+            )
+            .unwrap();
+        assert_eq!(
+            verify(&revoked, &[not_crl_signer.der().clone()], ee.der()),
+            Err(Error::IssuerNotCrlSigner)
+        );
+
+        // The revocation status of the CRL issuer is not checked, even given a CRL revoking it.
+        // That CRL's distribution point keeps it from being authoritative for the end-entity
+        // certificate, whose distribution point names the CRL issuer.
+        let other_full_name = tlv(
+            0xa0,
+            &[&tlv(
+                0xa0,
+                &[&tlv(0x86, &[b"http://crl.example.com/ca.crl"])],
+            )],
+        );
+        let crl_issuer_revoked = OwnedCertRevocationList::from_der(&signed_crl(
+            &trust_anchor,
+            &[crl_entry(&crl_issuer_serial, &[])],
+            &[
+                crl_extension(20, false, &[0x02, 0x01, 1]),
+                crl_extension(28, true, &tlv(0x30, &[&other_full_name])),
+            ],
+        ))
+        .unwrap()
+        .into();
+        for (crl, expected) in [(&not_revoked, Ok(())), (&revoked, Err(Error::CertRevoked))] {
+            let crl = OwnedCertRevocationList::from_der(crl).unwrap().into();
+            assert_eq!(
+                verify_with_crls(
+                    &trust_anchor.cert,
+                    &intermediates,
+                    ee.der(),
+                    &[&crl_issuer_revoked, &crl]
+                ),
+                expected
+            );
+        }
+
+        // The CRL issuer's basic constraints must be well-formed.
+        let mut params = crl_issuer_params(KeyUsagePurpose::CrlSign);
+        params.is_ca = rcgen::IsCa::NoCa;
+        params.custom_extensions = vec![CustomExtension::from_oid_content(
+            &[2, 5, 29, 19],
+            tlv(0x30, &[&[0x01, 0x01, 0x07]]),
+        )];
+        let malformed = params
+            .signed_by(
+                &crl_issuer.key_pair,
+                &trust_anchor.cert,
+                &trust_anchor.key_pair,
+            )
+            .unwrap();
+        assert_eq!(
+            verify(&revoked, &[malformed.der().clone()], ee.der()),
+            Err(Error::UnknownCrlIssuer)
+        );
+
+        // The extended key usage of the CRL issuer is not checked against the usage the
+        // end-entity certificate is verified for.
+        let mut params = crl_issuer_params(KeyUsagePurpose::CrlSign);
+        params.extended_key_usages = vec![rcgen::ExtendedKeyUsagePurpose::CodeSigning];
+        let code_signer = params
+            .signed_by(
+                &crl_issuer.key_pair,
+                &trust_anchor.cert,
+                &trust_anchor.key_pair,
+            )
+            .unwrap();
+        let intermediates = [code_signer.der().clone()];
+        assert_eq!(verify(&not_revoked, &intermediates, ee.der()), Ok(()));
+        assert_eq!(
+            verify(&revoked, &intermediates, ee.der()),
+            Err(Error::CertRevoked)
+        );
+
+        // The certificate issuer extension may only be used in indirect CRLs.
+        let direct = signed_crl(
+            &crl_issuer,
+            &[crl_entry(
+                &serial,
+                &[certificate_issuer(&trust_anchor.cert)],
+            )],
+            &[crl_extension(20, false, &[0x02, 0x01, 1])],
+        );
+        assert_eq!(
+            OwnedCertRevocationList::from_der(&direct).err(),
+            Some(Error::MalformedExtensions)
+        );
+    }
+
+    #[cfg(all(feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+    fn verify_with_crls(
+        trust_anchor: &rcgen::Certificate,
+        intermediates: &[pki_types::CertificateDer<'_>],
+        ee: &pki_types::CertificateDer<'_>,
+        crls: &[&CertRevocationList<'_>],
+    ) -> Result<(), Error> {
+        let anchors = [crate::anchor_from_trusted_cert(trust_anchor.der()).unwrap()];
+        let ee = crate::EndEntityCert::try_from(ee).unwrap();
+        let revocation = RevocationOptionsBuilder::new(crls).unwrap().build();
+        crate::test_utils::verify_server_auth(
+            &ee,
+            &anchors,
+            intermediates,
+            Some(revocation),
+            &crate::VerificationOptions::new(),
+        )
+        .map(|_| ())
+    }
+
+    /// A CRL signed by `issuer`, with the given revoked certificate entries and extensions.
+    #[cfg(all(feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+    fn signed_crl(
+        issuer: &rcgen::CertifiedKey,
+        entries: &[std::vec::Vec<u8>],
+        extensions: &[std::vec::Vec<u8>],
+    ) -> std::vec::Vec<u8> {
+        use crate::test_utils::{sign, tlv};
+        use std::prelude::v1::*;
+
+        const ECDSA_SHA256_ALG_ID: &[u8] =
+            &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+        let time = |time: &str| tlv(0x18, &[time.as_bytes()]);
+        let entries = entries.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let revoked = match entries.is_empty() {
+            true => Vec::new(),
+            false => tlv(0x30, &entries),
+        };
+        let extensions = extensions.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        let tbs = tlv(
+            0x30,
+            &[
+                &[0x02, 0x01, 0x01],
+                &tlv(0x30, &[ECDSA_SHA256_ALG_ID]),
+                &name(&issuer.cert),
+                &time("19861201000000Z"),
+                &time("19870101000000Z"),
+                &revoked,
+                &tlv(0xa0, &[&tlv(0x30, &extensions)]),
+            ],
+        );
+
+        let signature = sign(&issuer.key_pair, &tbs);
+        tlv(
+            0x30,
+            &[
+                &tbs,
+                &tlv(0x30, &[ECDSA_SHA256_ALG_ID]),
+                &tlv(0x03, &[&[0], &signature]),
+            ],
+        )
+    }
+
+    /// A revoked certificate entry with the given CRL entry extensions.
+    #[cfg(all(feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+    fn crl_entry(serial: &[u8], extensions: &[std::vec::Vec<u8>]) -> std::vec::Vec<u8> {
+        use crate::test_utils::tlv;
+        use std::prelude::v1::*;
+
+        let extensions = extensions.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let extensions = match extensions.is_empty() {
+            true => Vec::new(),
+            false => tlv(0x30, &extensions),
+        };
+        tlv(
+            0x30,
+            &[
+                &tlv(0x02, &[serial]),
+                &tlv(0x18, &[b"19861201000000Z"]),
+                &extensions,
+            ],
+        )
+    }
+
+    /// A CRL or CRL entry extension, with the OID `2.5.29.<id>`.
+    #[cfg(all(feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+    fn crl_extension(id: u8, critical: bool, value: &[u8]) -> std::vec::Vec<u8> {
+        use crate::test_utils::tlv;
+
+        let critical: &[u8] = match critical {
+            true => &[0x01, 0x01, 0xff],
+            false => &[],
+        };
+        tlv(
+            0x30,
+            &[
+                &tlv(0x06, &[&[0x55, 0x1d, id]]),
+                critical,
+                &tlv(0x04, &[value]),
+            ],
+        )
+    }
+
+    /// The DER encoded subject name of `cert`.
+    #[cfg(all(feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+    fn name(cert: &rcgen::Certificate) -> std::vec::Vec<u8> {
+        let cert = crate::cert::Cert::from_der(untrusted::Input::from(cert.der())).unwrap();
+        crate::test_utils::tlv(0x30, &[cert.subject.as_slice_less_safe()])
+    }
+
+    #[cfg(all(feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+    fn serial(cert: &rcgen::Certificate) -> std::vec::Vec<u8> {
+        let cert = crate::cert::Cert::from_der(untrusted::Input::from(cert.der())).unwrap();
+        cert.serial().to_vec()
+    }
 }
@@ -0,0 +1,1670 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use pki_types::{SignatureVerificationAlgorithm, UnixTime};
+
+use crate::algorithm_policy::AlgorithmPolicy;
+use crate::cert::{lenient_certificate_serial_number, Cert};
+use crate::crl::{CertNotRevoked, KeyUsageMode, RevocationReason};
+use crate::der::{self, DerIterator, FromDer, Tag, CONSTRUCTED, CONTEXT_SPECIFIC};
+use crate::error::{DerTypeId, Error};
+use crate::public_values_eq;
+use crate::signed_data::{self, SignedData, SubjectPublicKeyInfo};
+use crate::verify_cert::{eku_contains, Budget, PathNode, Role};
+use crate::x509::Extension;
+
+/// A hash function, used to match the `CertID` of each single response in an OCSP response to
+/// the issuer of a certificate.
+///
+/// Implementations are provided by the crypto providers this crate supports, e.g.
+/// `webpki::ring::OCSP_SHA1` and `webpki::aws_lc_rs::OCSP_SHA256`. Use with
+/// [`VerificationOptions::with_ocsp_hashers()`].
+///
+/// [`VerificationOptions::with_ocsp_hashers()`]: crate::VerificationOptions::with_ocsp_hashers
+pub trait OcspHasher: Debug + Send + Sync {
+    /// The DER encoding of the OID of the hash function, without the tag and length, as found
+    /// in the `hashAlgorithm` of a `CertID`.
+    fn oid(&self) -> &[u8];
+
+    /// Returns `true` if `digest` is the digest of the concatenation of `data`.
+    fn digest_matches(&self, data: &[&[u8]], digest: &[u8]) -> bool;
+}
+
+/// A RFC 6960[^1] OCSP response, used to check the revocation status of certificates.
+///
+/// May be either an owned, or a borrowed representation.
+///
+/// [^1]: <https://www.rfc-editor.org/rfc/rfc6960>
+#[derive(Debug)]
+pub enum OcspResponse<'a> {
+    /// An owned representation of an OCSP response.
+    #[cfg(feature = "alloc")]
+    Owned(OwnedOcspResponse),
+    /// A borrowed representation of an OCSP response.
+    Borrowed(BorrowedOcspResponse<'a>),
+}
+
+#[cfg(feature = "alloc")]
+impl From<OwnedOcspResponse> for OcspResponse<'_> {
+    fn from(response: OwnedOcspResponse) -> Self {
+        Self::Owned(response)
+    }
+}
+
+impl<'a> From<BorrowedOcspResponse<'a>> for OcspResponse<'a> {
+    fn from(response: BorrowedOcspResponse<'a>) -> Self {
+        Self::Borrowed(response)
+    }
+}
+
+impl OcspResponse<'_> {
+    /// Return the time at which the response was signed.
+    pub fn produced_at(&self) -> UnixTime {
+        self.borrow().produced_at
+    }
+
+    /// Determine the status of the certificate with the serial number `serial`, if this
+    /// response covers it.
+    ///
+    /// The certificate was issued by the issuer with the subject `issuer_subject` and key
+    /// `issuer_spki`, whose hashes in the `CertID` of the single response are checked using
+    /// `hashers`. The response must be signed by the issuer, or by a responder it delegated
+    /// to. The verification time must be within the validity period of the response for the
+    /// certificate.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn status(
+        &self,
+        serial: untrusted::Input<'_>,
+        issuer_subject: untrusted::Input<'_>,
+        issuer_spki: untrusted::Input<'_>,
+        hashers: &[&dyn OcspHasher],
+        supported_sig_algs: &[&dyn SignatureVerificationAlgorithm],
+        algorithms: &AlgorithmPolicy<'_>,
+        budget: &mut Budget,
+        time: UnixTime,
+    ) -> Result<Option<CertStatus>, Error> {
+        let response = self.borrow();
+        let single = match response.find(serial, issuer_subject, issuer_spki, hashers)? {
+            Some(single) => single,
+            None => return Ok(None),
+        };
+
+        response.verify_signature(
+            issuer_subject,
+            issuer_spki,
+            supported_sig_algs,
+            algorithms,
+            budget,
+            time,
+        )?;
+        single.check_time(time)?;
+        Ok(Some(single.status))
+    }
+
+    fn borrow(&self) -> BorrowedOcspResponse<'_> {
+        match self {
+            #[cfg(feature = "alloc")]
+            OcspResponse::Owned(response) => response.borrow(),
+            OcspResponse::Borrowed(response) => BorrowedOcspResponse {
+                signed_data: SignedData {
+                    data: response.signed_data.data,
+                    algorithm: response.signed_data.algorithm,
+                    signature: response.signed_data.signature,
+                },
+                ..*response
+            },
+        }
+    }
+}
+
+/// OCSP responses stapled to a TLS handshake, as set using
+/// [`VerificationOptions::with_stapled_ocsp()`] and
+/// [`VerificationOptions::with_stapled_intermediate_ocsp()`], or the same methods of
+/// [`PerCallOptions`].
+///
+/// [`VerificationOptions::with_stapled_ocsp()`]: crate::VerificationOptions::with_stapled_ocsp
+/// [`VerificationOptions::with_stapled_intermediate_ocsp()`]: crate::VerificationOptions::with_stapled_intermediate_ocsp
+/// [`PerCallOptions`]: crate::PerCallOptions
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct StapledOcsp<'a> {
+    pub(crate) end_entity: Option<&'a [u8]>,
+    pub(crate) intermediates: &'a [&'a [u8]],
+}
+
+impl StapledOcsp<'_> {
+    /// Check the revocation status of the certificate of `path` using the stapled responses
+    /// for its role, which must be about and signed by the issuer with the subject
+    /// `issuer_subject` and key `issuer_spki`, or by a responder it delegated to.
+    ///
+    /// Yields `None` if no stapled response determines the status of the certificate. A
+    /// response that fails to parse or verify is ignored, as if it were not stapled.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn check(
+        &self,
+        path: &PathNode<'_>,
+        issuer_subject: untrusted::Input<'_>,
+        issuer_spki: untrusted::Input<'_>,
+        hashers: &[&dyn OcspHasher],
+        supported_sig_algs: &[&dyn SignatureVerificationAlgorithm],
+        algorithms: &AlgorithmPolicy<'_>,
+        budget: &mut Budget,
+        time: UnixTime,
+    ) -> Result<Option<CertNotRevoked>, Error> {
+        let responses = match (path.role(), &self.end_entity) {
+            (Role::EndEntity, Some(response)) => core::slice::from_ref(response),
+            (Role::EndEntity, None) => &[],
+            (Role::Issuer, _) => self.intermediates,
+        };
+
+        for response in responses {
+            let status = BorrowedOcspResponse::from_der(response).and_then(|response| {
+                OcspResponse::from(response).status(
+                    path.cert.serial,
+                    issuer_subject,
+                    issuer_spki,
+                    hashers,
+                    supported_sig_algs,
+                    algorithms,
+                    budget,
+                    time,
+                )
+            });
+
+            match status {
+                Ok(Some(CertStatus::Good)) => return Ok(Some(CertNotRevoked::assertion())),
+                Ok(Some(CertStatus::Revoked)) => return Err(Error::CertRevoked),
+                Ok(Some(CertStatus::Unknown) | None) => {}
+                Err(err) if err.is_fatal() => return Err(err),
+                // Whether a missing response is an error is up to the Must-Staple and
+                // revocation policies.
+                Err(_) => {}
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Owned representation of a RFC 6960[^1] OCSP response.
+///
+/// [^1]: <https://www.rfc-editor.org/rfc/rfc6960>
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct OwnedOcspResponse {
+    signed_data: signed_data::OwnedSignedData,
+
+    responder_id: OwnedResponderId,
+
+    produced_at: UnixTime,
+
+    responses: Vec<u8>,
+
+    certs: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl OwnedOcspResponse {
+    /// Try to parse the given bytes as a RFC 6960[^1] OCSP response.
+    ///
+    /// See [BorrowedOcspResponse::from_der] for more details.
+    ///
+    /// [^1]: <https://www.rfc-editor.org/rfc/rfc6960>
+    pub fn from_der(response_der: &[u8]) -> Result<Self, Error> {
+        Ok(BorrowedOcspResponse::from_der(response_der)?.to_owned())
+    }
+
+    fn borrow(&self) -> BorrowedOcspResponse<'_> {
+        BorrowedOcspResponse {
+            signed_data: self.signed_data.borrow(),
+            responder_id: match &self.responder_id {
+                OwnedResponderId::ByName(name) => ResponderId::ByName(name.as_slice().into()),
+                OwnedResponderId::ByKey => ResponderId::ByKey,
+            },
+            produced_at: self.produced_at,
+            responses: self.responses.as_slice().into(),
+            certs: self.certs.as_slice().into(),
+        }
+    }
+}
+
+/// Borrowed representation of a RFC 6960[^1] OCSP response.
+///
+/// [^1]: <https://www.rfc-editor.org/rfc/rfc6960>
+#[derive(Debug)]
+pub struct BorrowedOcspResponse<'a> {
+    /// A `SignedData` structure that can be passed to `verify_signed_data`.
+    signed_data: SignedData<'a>,
+
+    /// Identifies the responder that signed the response.
+    responder_id: ResponderId<'a>,
+
+    /// The time at which the response was signed.
+    produced_at: UnixTime,
+
+    /// The status of each of the certificates covered by the response.
+    responses: untrusted::Input<'a>,
+
+    /// Certificates that may help to verify the signature of the response.
+    certs: untrusted::Input<'a>,
+}
+
+impl<'a> BorrowedOcspResponse<'a> {
+    /// Try to parse the given bytes as a RFC 6960[^1] OCSP response.
+    ///
+    /// `response_der` is the DER encoding of an `OCSPResponse`, as returned by an OCSP
+    /// responder or stapled to a TLS handshake.
+    ///
+    /// Webpki does not support:
+    ///   * Responses other than successful ones.
+    ///   * Response types other than `id-pkix-ocsp-basic`.
+    ///   * Response versions other than version 1.
+    ///
+    /// Single responses are matched to certificates by serial number, and by the hashes of
+    /// the issuer name and key in their `CertID`, which are computed using the hash functions
+    /// given to [`VerificationOptions::with_ocsp_hashers()`]. A single response whose hash
+    /// algorithm none of those support is not used.
+    ///
+    /// The response must be signed by the issuer of the certificate, or by a responder it
+    /// delegated to. The certificate of a delegated responder must be included in the response,
+    /// be issued by the issuer, include id-kp-OCSPSigning in its extended key usage, and be
+    /// valid at the verification time. Trusted responders, whose key is trusted independently
+    /// of the issuer, are not supported.
+    ///
+    /// [`VerificationOptions::with_ocsp_hashers()`]: crate::VerificationOptions::with_ocsp_hashers
+    ///
+    /// [^1]: <https://www.rfc-editor.org/rfc/rfc6960>
+    pub fn from_der(response_der: &'a [u8]) -> Result<Self, Error> {
+        der::read_all(untrusted::Input::from(response_der))
+    }
+
+    /// Convert the response to an [`OwnedOcspResponse`].
+    #[cfg(feature = "alloc")]
+    pub fn to_owned(&self) -> OwnedOcspResponse {
+        OwnedOcspResponse {
+            signed_data: self.signed_data.to_owned(),
+            responder_id: match self.responder_id {
+                ResponderId::ByName(name) => {
+                    OwnedResponderId::ByName(name.as_slice_less_safe().to_vec())
+                }
+                ResponderId::ByKey => OwnedResponderId::ByKey,
+            },
+            produced_at: self.produced_at,
+            responses: self.responses.as_slice_less_safe().to_vec(),
+            certs: self.certs.as_slice_less_safe().to_vec(),
+        }
+    }
+
+    /// Find the single response about the certificate with the serial number `serial`, issued
+    /// by the issuer with the subject `issuer_subject` and key `issuer_spki`.
+    ///
+    /// Fails with [`Error::UnsupportedOcspCertIdHashAlgorithm`] if single responses with the
+    /// serial number can't be matched to the issuer because none of `hashers` supports their
+    /// hash algorithm.
+    fn find(
+        &self,
+        serial: untrusted::Input<'_>,
+        issuer_subject: untrusted::Input<'_>,
+        issuer_spki: untrusted::Input<'_>,
+        hashers: &[&dyn OcspHasher],
+    ) -> Result<Option<SingleResponse<'a>>, Error> {
+        let issuer_key = der::read_all::<SubjectPublicKeyInfo<'_>>(issuer_spki)?.key_value;
+        let mut unsupported = false;
+        for single in DerIterator::<SingleResponse<'_>>::new(self.responses) {
+            let single = single?;
+            if !public_values_eq(single.cert_id.serial, serial) {
+                continue;
+            }
+
+            match single
+                .cert_id
+                .issued_by(issuer_subject, issuer_key, hashers)
+            {
+                Some(true) => return Ok(Some(single)),
+                // The same serial number, from another issuer.
+                Some(false) => {}
+                None => unsupported = true,
+            }
+        }
+
+        match unsupported {
+            true => Err(Error::UnsupportedOcspCertIdHashAlgorithm),
+            false => Ok(None),
+        }
+    }
+
+    /// Verify the signature of the response, made either by the issuer with the subject
+    /// `issuer_subject` and key `issuer_spki` or by a responder it delegated to, consuming
+    /// signature operations from the [`Budget`].
+    fn verify_signature(
+        &self,
+        issuer_subject: untrusted::Input<'_>,
+        issuer_spki: untrusted::Input<'_>,
+        supported_sig_algs: &[&dyn SignatureVerificationAlgorithm],
+        algorithms: &AlgorithmPolicy<'_>,
+        budget: &mut Budget,
+        time: UnixTime,
+    ) -> Result<(), Error> {
+        let mut error = Error::OcspResponderNotAuthorized;
+        if self.responder_id.matches(issuer_subject) {
+            match self.verify_signed_by(issuer_spki, supported_sig_algs, algorithms, budget) {
+                Ok(()) => return Ok(()),
+                Err(err) if err.is_fatal() => return Err(err),
+                Err(err) => error = error.most_specific(err),
+            }
+        }
+
+        // RFC 6960 §4.2.2.2:
+        //   The key used to sign the response MUST belong to one of the following:
+        //   - the CA who issued the certificate in question
+        //   - a Trusted Responder whose public key is trusted by the requestor
+        //   - a CA Designated Responder (Authorized Responder, defined in
+        //     Section 4.2.2.2) who holds a specially marked certificate issued
+        //     directly by the CA, indicating that the responder may issue OCSP
+        //     responses for that CA
+        // We do not support trusted responders.
+        let mut certs = untrusted::Reader::new(self.certs);
+        while !certs.at_end() {
+            let (cert_der, _) = certs.read_partial(|cert| der::expect_tag(cert, Tag::Sequence))?;
+            let responder = Cert::from_der(cert_der)?;
+            if !self.responder_id.matches(responder.subject)
+                || !authorized_responder(&responder, issuer_subject, time)
+            {
+                continue;
+            }
+
+            let result = check_delegated_responder(
+                &responder,
+                issuer_spki,
+                supported_sig_algs,
+                algorithms,
+                budget,
+            )
+            .and_then(|()| {
+                self.verify_signed_by(responder.spki, supported_sig_algs, algorithms, budget)
+            });
+            match result {
+                Ok(()) => return Ok(()),
+                Err(err) if err.is_fatal() => return Err(err),
+                Err(err) => error = error.most_specific(err),
+            }
+        }
+
+        Err(error)
+    }
+
+    fn verify_signed_by(
+        &self,
+        spki: untrusted::Input<'_>,
+        supported_sig_algs: &[&dyn SignatureVerificationAlgorithm],
+        algorithms: &AlgorithmPolicy<'_>,
+        budget: &mut Budget,
+    ) -> Result<(), Error> {
+        algorithms.check_signature_algorithm(self.signed_data.algorithm)?;
+        algorithms.check_key(spki)?;
+        signed_data::verify_signed_data(supported_sig_algs, spki, &self.signed_data, budget)
+            .map_err(ocsp_signature_err)
+    }
+
+    fn from_basic(reader: &mut untrusted::Reader<'a>) -> Result<Self, Error> {
+        der::nested_limited(
+            reader,
+            Tag::Sequence,
+            Error::TrailingData(DerTypeId::BasicOcspResponse),
+            |basic| {
+                let (tbs_response_data, signed_data) =
+                    SignedData::from_der(basic, der::MAX_DER_SIZE)?;
+
+                // RFC 6960 §4.2.1:
+                //   certs [0] EXPLICIT SEQUENCE OF Certificate OPTIONAL
+                let certs = match basic.at_end() {
+                    true => untrusted::Input::from(&[]),
+                    false => der::nested_limited(
+                        basic,
+                        Tag::ContextSpecificConstructed0,
+                        Error::BadDer,
+                        |certs| {
+                            der::expect_tag_and_get_value_limited(
+                                certs,
+                                Tag::Sequence,
+                                der::MAX_DER_SIZE,
+                            )
+                        },
+                        der::MAX_DER_SIZE,
+                    )?,
+                };
+
+                tbs_response_data.read_all(
+                    Error::TrailingData(DerTypeId::OcspResponseData),
+                    |tbs| {
+                        // RFC 6960 §4.2.1:
+                        //   version [0] EXPLICIT Version DEFAULT v1
+                        // NOTE: Encoded value of version 1 is 0.
+                        if tbs.peek(Tag::ContextSpecificConstructed0.into())
+                            && der::nested(
+                                tbs,
+                                Tag::ContextSpecificConstructed0,
+                                Error::BadDer,
+                                u8::from_der,
+                            )? != 0
+                        {
+                            return Err(Error::UnsupportedOcspVersion);
+                        }
+
+                        // RFC 6960 §4.2.1:
+                        //   ResponderID ::= CHOICE {
+                        //      byName   [1] Name,
+                        //      byKey    [2] KeyHash }
+                        const BY_NAME_TAG: u8 = CONTEXT_SPECIFIC | CONSTRUCTED | 1;
+                        const BY_KEY_TAG: u8 = CONTEXT_SPECIFIC | CONSTRUCTED | 2;
+                        let responder_id = match der::read_tag_and_get_value(tbs)? {
+                            (BY_NAME_TAG, value) => {
+                                ResponderId::ByName(value.read_all(Error::BadDer, |name| {
+                                    der::expect_tag(name, Tag::Sequence)
+                                })?)
+                            }
+                            (BY_KEY_TAG, value) => {
+                                value.read_all(Error::BadDer, |key_hash| {
+                                    der::expect_tag(key_hash, Tag::OctetString)
+                                })?;
+                                ResponderId::ByKey
+                            }
+                            _ => return Err(Error::BadDer),
+                        };
+
+                        let produced_at = UnixTime::from_der(tbs)?;
+                        let responses = der::expect_tag_and_get_value_limited(
+                            tbs,
+                            Tag::Sequence,
+                            der::MAX_DER_SIZE,
+                        )?;
+
+                        // RFC 6960 §4.2.1:
+                        //   responseExtensions [1] EXPLICIT Extensions OPTIONAL
+                        // We do not send requests with a nonce, so don't use any of the
+                        // response extensions.
+                        if tbs.peek(Tag::ContextSpecificConstructed1.into()) {
+                            unsupported_extensions(tbs)?;
+                        }
+
+                        // Parse the single responses up-front to validate that they only use
+                        // well-formed and supported features.
+                        for single in DerIterator::<SingleResponse<'_>>::new(responses) {
+                            single?;
+                        }
+
+                        Ok(Self {
+                            signed_data,
+                            responder_id,
+                            produced_at,
+                            responses,
+                            certs,
+                        })
+                    },
+                )
+            },
+            der::MAX_DER_SIZE,
+        )
+    }
+}
+
+impl<'a> FromDer<'a> for BorrowedOcspResponse<'a> {
+    /// Try to parse the given bytes as a RFC 6960[^1] OCSP response.
+    ///
+    /// [^1]: <https://www.rfc-editor.org/rfc/rfc6960>
+    fn from_der(reader: &mut untrusted::Reader<'a>) -> Result<Self, Error> {
+        der::nested_limited(
+            reader,
+            Tag::Sequence,
+            Error::TrailingData(Self::TYPE_ID),
+            |response| {
+                // RFC 6960 §4.2.1:
+                //   OCSPResponseStatus ::= ENUMERATED {
+                //       successful            (0),  -- Response has valid confirmations
+                //       ...
+                // Only successful responses include response bytes.
+                let status = der::expect_tag(response, Tag::Enum)?;
+                if status.as_slice_less_safe() != [0] {
+                    return Err(Error::OcspResponseNotSuccessful);
+                }
+
+                der::nested_limited(
+                    response,
+                    Tag::ContextSpecificConstructed0,
+                    Error::BadDer,
+                    |response_bytes| {
+                        der::nested_limited(
+                            response_bytes,
+                            Tag::Sequence,
+                            Error::BadDer,
+                            |response_bytes| {
+                                let response_type = der::expect_tag(response_bytes, Tag::OID)?;
+                                if !public_values_eq(response_type, ID_PKIX_OCSP_BASIC.into()) {
+                                    return Err(Error::UnsupportedOcspResponseType);
+                                }
+
+                                der::expect_tag_and_get_value_limited(
+                                    response_bytes,
+                                    Tag::OctetString,
+                                    der::MAX_DER_SIZE,
+                                )?
+                                .read_all(
+                                    Error::TrailingData(DerTypeId::BasicOcspResponse),
+                                    Self::from_basic,
+                                )
+                            },
+                            der::MAX_DER_SIZE,
+                        )
+                    },
+                    der::MAX_DER_SIZE,
+                )
+            },
+            der::MAX_DER_SIZE,
+        )
+    }
+
+    const TYPE_ID: DerTypeId = DerTypeId::OcspResponse;
+}
+
+/// The revocation status of a certificate, according to an OCSP response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CertStatus {
+    Good,
+    Revoked,
+    Unknown,
+}
+
+/// Whether `responder` is authorized by the issuer with the subject `issuer_subject` to sign
+/// OCSP responses at `time`.
+fn authorized_responder(
+    responder: &Cert<'_>,
+    issuer_subject: untrusted::Input<'_>,
+    time: UnixTime,
+) -> bool {
+    // RFC 6960 §4.2.2.2:
+    //   OCSP signing delegation SHALL be designated by the inclusion of
+    //   id-kp-OCSPSigning in an extended key usage certificate extension
+    //   included in the OCSP response signer's certificate.  This certificate
+    //   MUST be issued directly by the CA that is identified in the request.
+    let within_validity = match (responder.not_before(), responder.not_after()) {
+        (Ok(not_before), Ok(not_after)) => not_before <= time && time <= not_after,
+        _ => false,
+    };
+
+    public_values_eq(responder.issuer, issuer_subject)
+        && responder
+            .eku
+            .is_some_and(|eku| eku_contains(eku, ID_KP_OCSP_SIGNING.into(), false))
+        && within_validity
+        && KeyUsageMode::DigitalSignature
+            .check(responder.key_usage)
+            .is_ok()
+}
+
+/// Verify that `responder` was issued by the issuer with key `issuer_spki`.
+fn check_delegated_responder(
+    responder: &Cert<'_>,
+    issuer_spki: untrusted::Input<'_>,
+    supported_sig_algs: &[&dyn SignatureVerificationAlgorithm],
+    algorithms: &AlgorithmPolicy<'_>,
+    budget: &mut Budget,
+) -> Result<(), Error> {
+    algorithms.check_signature_algorithm(responder.signed_data.algorithm)?;
+    algorithms.check_key(issuer_spki)?;
+    signed_data::verify_signed_data(
+        supported_sig_algs,
+        issuer_spki,
+        &responder.signed_data,
+        budget,
+    )
+    .map_err(|err| match err {
+        Error::InvalidSignatureForPublicKey => Error::OcspResponderNotAuthorized,
+        err => err,
+    })
+}
+
+/// Identifies the responder that signed an OCSP response.
+#[derive(Clone, Copy, Debug)]
+enum ResponderId<'a> {
+    /// The subject name of the responder.
+    ByName(untrusted::Input<'a>),
+    /// The SHA-1 hash of the responder's public key, which we don't check.
+    ByKey,
+}
+
+impl ResponderId<'_> {
+    /// Whether the responder may be the one with the subject `name`.
+    ///
+    /// Any responder may match a `ByKey` responder ID, since computing its key hash requires
+    /// SHA-1. Its signature is what establishes the responder.
+    fn matches(&self, name: untrusted::Input<'_>) -> bool {
+        match self {
+            Self::ByName(responder_name) => public_values_eq(*responder_name, name),
+            Self::ByKey => true,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+enum OwnedResponderId {
+    ByName(Vec<u8>),
+    ByKey,
+}
+
+/// Identifies the certificate that a single response in an OCSP response is about.
+struct CertId<'a> {
+    /// The OID of the hash algorithm of `issuer_name_hash` and `issuer_key_hash`.
+    hash_algorithm: untrusted::Input<'a>,
+    issuer_name_hash: untrusted::Input<'a>,
+    issuer_key_hash: untrusted::Input<'a>,
+    serial: untrusted::Input<'a>,
+}
+
+impl CertId<'_> {
+    /// Whether the certificate was issued by the issuer with the subject `issuer_subject` and
+    /// the public key `issuer_key`, or `None` if none of `hashers` supports the hash algorithm.
+    fn issued_by(
+        &self,
+        issuer_subject: untrusted::Input<'_>,
+        issuer_key: untrusted::Input<'_>,
+        hashers: &[&dyn OcspHasher],
+    ) -> Option<bool> {
+        let hasher = hashers
+            .iter()
+            .find(|hasher| public_values_eq(hasher.oid().into(), self.hash_algorithm))?;
+
+        // RFC 6960 §4.1.1:
+        //   issuerNameHash is the hash of the issuer's distinguished name
+        //   (DN).  The hash shall be calculated over the DER encoding of the
+        //   issuer's name field in the certificate being checked.
+        //   issuerKeyHash is the hash of the issuer's public key.  The hash
+        //   shall be calculated over the value (excluding tag and length) of
+        //   the subject public key field in the issuer's certificate.
+        // We only have the value of the issuer's name, so encode its tag and length again.
+        let (header, header_len) = sequence_header(issuer_subject.len())?;
+        Some(
+            hasher.digest_matches(
+                &[&header[..header_len], issuer_subject.as_slice_less_safe()],
+                self.issuer_name_hash.as_slice_less_safe(),
+            ) && hasher.digest_matches(
+                &[issuer_key.as_slice_less_safe()],
+                self.issuer_key_hash.as_slice_less_safe(),
+            ),
+        )
+    }
+}
+
+/// The DER encoding of the tag and length of a `SEQUENCE` with a value of `len` bytes, and
+/// the length of that encoding.
+fn sequence_header(len: usize) -> Option<([u8; 6], usize)> {
+    let mut header = [0u8; 6];
+    header[0] = u8::from(Tag::Sequence);
+    if let Ok(len @ 0..=0x7f) = u8::try_from(len) {
+        header[1] = len;
+        return Some((header, 2));
+    }
+
+    // Long form: the number of length bytes, followed by the length itself.
+    let len = u32::try_from(len).ok()?.to_be_bytes();
+    let len = &len[len.iter().position(|&byte| byte != 0)?..];
+    header[1] = 0x80 | u8::try_from(len.len()).ok()?;
+    header[2..2 + len.len()].copy_from_slice(len);
+    Some((header, 2 + len.len()))
+}
+
+/// The status of one certificate in an OCSP response.
+struct SingleResponse<'a> {
+    cert_id: CertId<'a>,
+    status: CertStatus,
+    this_update: UnixTime,
+    next_update: Option<UnixTime>,
+}
+
+impl SingleResponse<'_> {
+    /// Check that `time` is within the validity period of the response.
+    fn check_time(&self, time: UnixTime) -> Result<(), Error> {
+        if time < self.this_update {
+            return Err(Error::OcspResponseNotYetValid {
+                time,
+                this_update: self.this_update,
+            });
+        }
+
+        // RFC 6960 §4.2.2.1:
+        //   If nextUpdate is not set, the responder is indicating that newer
+        //   revocation information is available all the time.
+        match self.next_update {
+            Some(next_update) if time >= next_update => {
+                Err(Error::OcspResponseExpired { time, next_update })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<'a> FromDer<'a> for SingleResponse<'a> {
+    fn from_der(reader: &mut untrusted::Reader<'a>) -> Result<Self, Error> {
+        der::nested(
+            reader,
+            Tag::Sequence,
+            Error::TrailingData(Self::TYPE_ID),
+            |der| {
+                // RFC 6960 §4.1.1:
+                //   CertID          ::=     SEQUENCE {
+                //       hashAlgorithm       AlgorithmIdentifier,
+                //       issuerNameHash      OCTET STRING, -- Hash of issuer's DN
+                //       issuerKeyHash       OCTET STRING, -- Hash of issuer's public key
+                //       serialNumber        CertificateSerialNumber }
+                let cert_id = der::nested(
+                    der,
+                    Tag::Sequence,
+                    Error::TrailingData(DerTypeId::OcspCertId),
+                    |cert_id| {
+                        // The parameters of the hash algorithm, absent or NULL for the hash
+                        // functions in use, are ignored.
+                        let hash_algorithm =
+                            der::nested(cert_id, Tag::Sequence, Error::BadDer, |algorithm| {
+                                let oid = der::expect_tag(algorithm, Tag::OID)?;
+                                algorithm.skip_to_end();
+                                Ok(oid)
+                            })?;
+                        Ok(CertId {
+                            hash_algorithm,
+                            issuer_name_hash: der::expect_tag(cert_id, Tag::OctetString)?,
+                            issuer_key_hash: der::expect_tag(cert_id, Tag::OctetString)?,
+                            // Like the handling of CRLs, we choose to be lenient about the
+                            // serial number.
+                            serial: lenient_certificate_serial_number(cert_id)
+                                .map_err(|_| Error::InvalidSerialNumber)?,
+                        })
+                    },
+                )?;
+
+                // RFC 6960 §4.2.1:
+                //   CertStatus ::= CHOICE {
+                //       good        [0]     IMPLICIT NULL,
+                //       revoked     [1]     IMPLICIT RevokedInfo,
+                //       unknown     [2]     IMPLICIT UnknownInfo }
+                const GOOD_TAG: u8 = CONTEXT_SPECIFIC;
+                const REVOKED_TAG: u8 = CONTEXT_SPECIFIC | CONSTRUCTED | 1;
+                const UNKNOWN_TAG: u8 = CONTEXT_SPECIFIC | 2;
+                let status = match der::read_tag_and_get_value(der)? {
+                    (GOOD_TAG, value) if value.is_empty() => CertStatus::Good,
+                    (REVOKED_TAG, value) => {
+                        // RevokedInfo ::= SEQUENCE {
+                        //     revocationTime              GeneralizedTime,
+                        //     revocationReason    [0]     EXPLICIT CRLReason OPTIONAL }
+                        value.read_all(Error::BadDer, |revoked_info| {
+                            UnixTime::from_der(revoked_info)?;
+                            if !revoked_info.at_end() {
+                                der::nested(
+                                    revoked_info,
+                                    Tag::ContextSpecificConstructed0,
+                                    Error::BadDer,
+                                    RevocationReason::from_der,
+                                )?;
+                            }
+                            Ok(())
+                        })?;
+                        CertStatus::Revoked
+                    }
+                    (UNKNOWN_TAG, value) if value.is_empty() => CertStatus::Unknown,
+                    _ => return Err(Error::BadDer),
+                };
+
+                let this_update = UnixTime::from_der(der)?;
+                let next_update = match der.peek(Tag::ContextSpecificConstructed0.into()) {
+                    true => Some(der::nested(
+                        der,
+                        Tag::ContextSpecificConstructed0,
+                        Error::BadDer,
+                        UnixTime::from_der,
+                    )?),
+                    false => None,
+                };
+
+                // RFC 6960 §4.2.1:
+                //   singleExtensions   [1]       EXPLICIT Extensions OPTIONAL
+                if der.peek(Tag::ContextSpecificConstructed1.into()) {
+                    unsupported_extensions(der)?;
+                }
+
+                Ok(SingleResponse {
+                    cert_id,
+                    status,
+                    this_update,
+                    next_update,
+                })
+            },
+        )
+    }
+
+    const TYPE_ID: DerTypeId = DerTypeId::OcspSingleResponse;
+}
+
+/// Read `[1] EXPLICIT Extensions`, none of which we support.
+fn unsupported_extensions(reader: &mut untrusted::Reader<'_>) -> Result<(), Error> {
+    der::nested(
+        reader,
+        Tag::ContextSpecificConstructed1,
+        Error::MalformedExtensions,
+        |tagged| {
+            der::nested_of_mut(
+                tagged,
+                Tag::Sequence,
+                Tag::Sequence,
+                Error::TrailingData(DerTypeId::Extension),
+                // Unrecognized non-critical extensions are ignored.
+                |extension| Extension::from_der(extension)?.unsupported(),
+            )
+        },
+    )
+}
+
+// When verifying OCSP response signed data we want to disambiguate the context of possible
+// errors by mapping them to OCSP specific variants, as is done for CRLs.
+fn ocsp_signature_err(err: Error) -> Error {
+    match err {
+        Error::UnsupportedSignatureAlgorithm => Error::UnsupportedOcspSignatureAlgorithm,
+        Error::UnsupportedSignatureAlgorithmForPublicKey => {
+            Error::UnsupportedOcspSignatureAlgorithmForPublicKey
+        }
+        Error::InvalidSignatureForPublicKey => Error::InvalidOcspSignatureForPublicKey,
+        _ => err,
+    }
+}
+
+// id-pkix-ocsp-basic OBJECT IDENTIFIER ::= { id-pkix-ocsp 1 }
+const ID_PKIX_OCSP_BASIC: &[u8] = &oid!(1, 3, 6, 1, 5, 5, 7, 48, 1, 1);
+
+// id-kp-OCSPSigning OBJECT IDENTIFIER ::= { id-kp 9 }
+const ID_KP_OCSP_SIGNING: &[u8] = &oid!(1, 3, 6, 1, 5, 5, 7, 3, 9);
+
+#[cfg(all(test, feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+mod tests {
+    use super::*;
+    use crate::cert::Cert;
+    use crate::crl::{
+        MustStaplePolicy, RevocationOptions, RevocationOptionsBuilder, UnknownStatusPolicy,
+    };
+    use crate::end_entity::EndEntityCert;
+    use crate::test_utils::{
+        end_entity_params, issuer_params, make_end_entity, make_issuer, sign, test_time, tlv,
+        verify_server_auth, RCGEN_SIGNATURE_ALG,
+    };
+    use crate::trust_anchor::anchor_from_trusted_cert;
+    use crate::verify_cert::{KeyUsage, VerificationOptions};
+    use rcgen::{CertifiedKey, KeyPair};
+    use std::prelude::v1::*;
+
+    #[cfg(feature = "aws-lc-rs")]
+    use crate::aws_lc_rs as provider;
+    #[cfg(all(feature = "ring", not(feature = "aws-lc-rs")))]
+    use crate::ring as provider;
+
+    #[test]
+    fn ocsp_good() {
+        let pki = Pki::new();
+        let der = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            Some(NEXT_UPDATE),
+            &[],
+        );
+
+        let borrowed = OcspResponse::from(BorrowedOcspResponse::from_der(&der).unwrap());
+        assert_eq!(pki.verify(&[&borrowed], UnknownStatusPolicy::Deny), Ok(()));
+
+        let owned = OcspResponse::from(OwnedOcspResponse::from_der(&der).unwrap());
+        assert_eq!(pki.verify(&[&owned], UnknownStatusPolicy::Deny), Ok(()));
+        assert_eq!(owned.produced_at(), borrowed.produced_at());
+    }
+
+    #[test]
+    fn ocsp_revoked() {
+        let pki = Pki::new();
+        let revoked = tlv(
+            0xa1,
+            &[&time(THIS_UPDATE), &tlv(0xa0, &[&[0x0a, 0x01, 0x01]])],
+        );
+        let der = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            &revoked,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert_eq!(
+            pki.verify(&[&response], UnknownStatusPolicy::Allow),
+            Err(Error::CertRevoked)
+        );
+    }
+
+    #[test]
+    fn ocsp_unknown_defers_to_crls() {
+        let pki = Pki::new();
+        let der = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            UNKNOWN,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert_eq!(
+            pki.verify(&[&response], UnknownStatusPolicy::Deny),
+            Err(Error::UnknownRevocationStatus)
+        );
+        assert_eq!(pki.verify(&[&response], UnknownStatusPolicy::Allow), Ok(()));
+
+        // A response that does not cover the certificate is ignored.
+        let other = Pki::new();
+        let der = other.response(
+            &other.issuer.cert,
+            &other.issuer.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert_eq!(
+            pki.verify(&[&response], UnknownStatusPolicy::Deny),
+            Err(Error::UnknownRevocationStatus)
+        );
+    }
+
+    #[test]
+    fn ocsp_validity() {
+        let pki = Pki::new();
+        let der = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            Some(THIS_UPDATE),
+            &[],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert!(matches!(
+            pki.verify(&[&response], UnknownStatusPolicy::Deny),
+            Err(Error::OcspResponseExpired { .. })
+        ));
+        // An unusable response is skipped, leaving the status unknown.
+        assert_eq!(pki.verify(&[&response], UnknownStatusPolicy::Allow), Ok(()));
+
+        let der = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            GOOD,
+            NEXT_UPDATE,
+            None,
+            &[],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert!(matches!(
+            pki.verify(&[&response], UnknownStatusPolicy::Deny),
+            Err(Error::OcspResponseNotYetValid { .. })
+        ));
+    }
+
+    #[test]
+    fn ocsp_delegated_responder() {
+        let pki = Pki::new();
+        let responder = pki.responder(vec![rcgen::ExtendedKeyUsagePurpose::OcspSigning]);
+        let der = pki.response(
+            &responder.cert,
+            &responder.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            None,
+            &[responder.cert.der()],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert_eq!(pki.verify(&[&response], UnknownStatusPolicy::Deny), Ok(()));
+
+        // Without id-kp-OCSPSigning, the responder is not authorized.
+        let responder = pki.responder(vec![rcgen::ExtendedKeyUsagePurpose::ServerAuth]);
+        let der = pki.response(
+            &responder.cert,
+            &responder.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            None,
+            &[responder.cert.der()],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert_eq!(
+            pki.verify(&[&response], UnknownStatusPolicy::Deny),
+            Err(Error::OcspResponderNotAuthorized)
+        );
+        assert_eq!(pki.verify(&[&response], UnknownStatusPolicy::Allow), Ok(()));
+
+        // Nor is a responder that does not include its certificate.
+        let responder = pki.responder(vec![rcgen::ExtendedKeyUsagePurpose::OcspSigning]);
+        let der = pki.response(
+            &responder.cert,
+            &responder.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert_eq!(
+            pki.verify(&[&response], UnknownStatusPolicy::Deny),
+            Err(Error::OcspResponderNotAuthorized)
+        );
+
+        // Nor is a responder whose certificate has expired.
+        let mut params = responder_params(vec![rcgen::ExtendedKeyUsagePurpose::OcspSigning]);
+        params.not_before = rcgen::date_time_ymd(1975, 1, 1);
+        params.not_after = rcgen::date_time_ymd(1976, 1, 1);
+        let key_pair = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let expired = params
+            .signed_by(&key_pair, &pki.issuer.cert, &pki.issuer.key_pair)
+            .unwrap();
+        let der = pki.response(
+            &expired,
+            &key_pair,
+            GOOD,
+            THIS_UPDATE,
+            None,
+            &[expired.der()],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert_eq!(
+            pki.verify(&[&response], UnknownStatusPolicy::Deny),
+            Err(Error::OcspResponderNotAuthorized)
+        );
+
+        // Nor is a responder delegated to by another issuer with the same name.
+        let other = Pki::new();
+        let responder = other.responder(vec![rcgen::ExtendedKeyUsagePurpose::OcspSigning]);
+        let der = pki.response(
+            &responder.cert,
+            &responder.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            None,
+            &[responder.cert.der()],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert_eq!(
+            pki.verify(&[&response], UnknownStatusPolicy::Deny),
+            Err(Error::OcspResponderNotAuthorized)
+        );
+    }
+
+    #[test]
+    fn ocsp_cert_id_hash_algorithms() {
+        let pki = Pki::new();
+        let der = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+
+        // Without a hasher for its hash algorithm, the response can't be matched to the issuer.
+        for hashers in [&[][..], &[provider::OCSP_SHA1]] {
+            assert_eq!(
+                pki.verify_with_options(
+                    &[&response],
+                    UnknownStatusPolicy::Deny,
+                    &VerificationOptions::new().with_ocsp_hashers(hashers),
+                ),
+                Err(Error::UnsupportedOcspCertIdHashAlgorithm)
+            );
+        }
+
+        // "abc", from FIPS 180-2 appendix A.1.
+        let digest = [
+            0xa9, 0x99, 0x3e, 0x36, 0x47, 0x06, 0x81, 0x6a, 0xba, 0x3e, 0x25, 0x71, 0x78, 0x50,
+            0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d,
+        ];
+        assert!(provider::OCSP_SHA1.digest_matches(&[b"a", b"bc"], &digest));
+        assert!(!provider::OCSP_SHA1.digest_matches(&[b"abd"], &digest));
+        assert!(!provider::OCSP_SHA1.digest_matches(&[b"abc"], &digest[..19]));
+    }
+
+    #[test]
+    fn ocsp_cert_id_header() {
+        for (len, header) in [
+            (0, &[0x30, 0x00][..]),
+            (0x7f, &[0x30, 0x7f]),
+            (0x80, &[0x30, 0x81, 0x80]),
+            (0x1234, &[0x30, 0x82, 0x12, 0x34]),
+        ] {
+            let (bytes, header_len) = sequence_header(len).unwrap();
+            assert_eq!(&bytes[..header_len], header);
+        }
+    }
+
+    #[test]
+    fn ocsp_same_serial_other_issuer() {
+        // Two issuers with the same name have each issued a certificate with the same serial.
+        let pki = Pki::new();
+        let ee = Cert::from_der(untrusted::Input::from(pki.ee.cert.der())).unwrap();
+        let other_issuer = make_issuer("Issuer");
+        let mut params = end_entity_params(vec!["example.com".into()]);
+        params.serial_number = Some(rcgen::SerialNumber::from_slice(ee.serial()));
+        let key_pair = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let cert = params
+            .signed_by(&key_pair, &other_issuer.cert, &other_issuer.key_pair)
+            .unwrap();
+        let other = Pki {
+            issuer: other_issuer,
+            ee: CertifiedKey { cert, key_pair },
+        };
+
+        // The other issuer's response says its certificate is revoked.
+        let revoked = tlv(0xa1, &[&time(THIS_UPDATE)]);
+        let der = other.response(
+            &other.issuer.cert,
+            &other.issuer.key_pair,
+            &revoked,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert_eq!(
+            other.verify(&[&response], UnknownStatusPolicy::Deny),
+            Err(Error::CertRevoked)
+        );
+
+        // The response does not cover the certificate of the first issuer, since its CertID
+        // identifies the other issuer's key, so the first issuer's CRL is used instead.
+        let crl = rcgen::CertificateRevocationListParams {
+            this_update: rcgen::date_time_ymd(1986, 1, 1),
+            next_update: rcgen::date_time_ymd(1987, 1, 1),
+            crl_number: rcgen::SerialNumber::from(1),
+            issuing_distribution_point: None,
+            revoked_certs: Vec::new(),
+            key_identifier_method: rcgen::KeyIdMethod::Sha256,
+        }
+        .signed_by(&pki.issuer.cert, &pki.issuer.key_pair)
+        .unwrap();
+        let crl = crate::crl::BorrowedCertRevocationList::from_der(crl.der())
+            .unwrap()
+            .into();
+        let anchors = [anchor_from_trusted_cert(pki.issuer.cert.der()).unwrap()];
+        let ee = EndEntityCert::try_from(pki.ee.cert.der()).unwrap();
+        let verify = |responses: &[&OcspResponse<'_>]| {
+            let revocation = RevocationOptions {
+                crls: &[&crl],
+                ocsp_responses: responses,
+                depth: crate::crl::RevocationCheckDepth::Chain,
+                status_policy: UnknownStatusPolicy::Deny,
+                expiration_policy: crate::crl::ExpirationPolicy::Ignore,
+            };
+            verify_server_auth(&ee, &anchors, &[], Some(revocation), &ocsp_options()).map(|_| ())
+        };
+        assert_eq!(verify(&[&response]), Ok(()));
+        assert_eq!(
+            pki.verify(&[&response], UnknownStatusPolicy::Deny),
+            Err(Error::UnknownRevocationStatus)
+        );
+
+        // A later response from the right issuer is still used.
+        let der = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            &revoked,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let revoked = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert_eq!(verify(&[&response, &revoked]), Err(Error::CertRevoked));
+    }
+
+    #[test]
+    fn ocsp_invalid_signature() {
+        let pki = Pki::new();
+        let impostor = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let der = pki.response(&pki.issuer.cert, &impostor, GOOD, THIS_UPDATE, None, &[]);
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        assert_eq!(
+            pki.verify(&[&response], UnknownStatusPolicy::Deny),
+            Err(Error::InvalidOcspSignatureForPublicKey)
+        );
+    }
+
+    #[test]
+    fn ocsp_response_parsing() {
+        // tryLater (3), without response bytes.
+        assert!(matches!(
+            BorrowedOcspResponse::from_der(&tlv(0x30, &[&[0x0a, 0x01, 0x03]])),
+            Err(Error::OcspResponseNotSuccessful)
+        ));
+
+        let pki = Pki::new();
+        let der = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap();
+        assert!(matches!(response.responder_id, ResponderId::ByName(_)));
+
+        // The response type must be id-pkix-ocsp-basic.
+        let mut der = der;
+        let oid = der
+            .windows(ID_PKIX_OCSP_BASIC.len())
+            .position(|window| window == ID_PKIX_OCSP_BASIC)
+            .unwrap();
+        der[oid + ID_PKIX_OCSP_BASIC.len() - 1] = 2;
+        assert!(matches!(
+            BorrowedOcspResponse::from_der(&der),
+            Err(Error::UnsupportedOcspResponseType)
+        ));
+    }
+
+    #[test]
+    fn stapled_ocsp() {
+        let pki = Pki::new();
+        let good = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let verify = |options: &VerificationOptions<'_>, status_policy| {
+            pki.verify_with_options(&[], status_policy, options)
+        };
+
+        // A good stapled response satisfies revocation checking of the end-entity certificate.
+        let options = ocsp_options().with_stapled_ocsp(&good);
+        assert_eq!(verify(&options, UnknownStatusPolicy::Deny), Ok(()));
+        assert_eq!(
+            verify(&ocsp_options(), UnknownStatusPolicy::Deny),
+            Err(Error::UnknownRevocationStatus)
+        );
+
+        // ... and is not used for intermediates.
+        let responses = [&good[..]];
+        let options = ocsp_options().with_stapled_intermediate_ocsp(&responses);
+        assert_eq!(
+            verify(&options, UnknownStatusPolicy::Deny),
+            Err(Error::UnknownRevocationStatus)
+        );
+
+        let revoked = tlv(0xa1, &[&time(THIS_UPDATE)]);
+        let revoked = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            &revoked,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let options = ocsp_options().with_stapled_ocsp(&revoked);
+        assert_eq!(
+            verify(&options, UnknownStatusPolicy::Allow),
+            Err(Error::CertRevoked)
+        );
+
+        // An unusable response is ignored, as if it were not stapled.
+        let options = ocsp_options().with_stapled_ocsp(&[0x30, 0x00]);
+        assert_eq!(verify(&options, UnknownStatusPolicy::Allow), Ok(()));
+        assert_eq!(
+            verify(&options, UnknownStatusPolicy::Deny),
+            Err(Error::UnknownRevocationStatus)
+        );
+
+        let impostor = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let forged = pki.response(&pki.issuer.cert, &impostor, GOOD, THIS_UPDATE, None, &[]);
+        let options = ocsp_options().with_stapled_ocsp(&forged);
+        assert_eq!(verify(&options, UnknownStatusPolicy::Allow), Ok(()));
+        assert_eq!(
+            verify(&options, UnknownStatusPolicy::Deny),
+            Err(Error::UnknownRevocationStatus)
+        );
+    }
+
+    #[test]
+    fn verifier_stapled_ocsp() {
+        let pki = Pki::new();
+        let good = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let anchors = [anchor_from_trusted_cert(pki.issuer.cert.der()).unwrap()];
+        let ee = EndEntityCert::try_from(pki.ee.cert.der()).unwrap();
+        let revocation = RevocationOptions {
+            crls: &[],
+            ocsp_responses: &[],
+            depth: crate::crl::RevocationCheckDepth::Chain,
+            status_policy: UnknownStatusPolicy::Deny,
+            expiration_policy: crate::crl::ExpirationPolicy::Ignore,
+        };
+        let verifier = crate::Verifier::builder(
+            crate::ALL_VERIFICATION_ALGS,
+            &anchors,
+            KeyUsage::server_auth(),
+        )
+        .with_revocation(revocation)
+        .with_options(ocsp_options())
+        .build();
+        let time = test_time();
+
+        // Each verification uses the responses stapled for it.
+        assert_eq!(
+            verifier.verify(&ee, &[], time).map(|_| ()),
+            Err(Error::UnknownRevocationStatus)
+        );
+        let stapled = crate::PerCallOptions::new().with_stapled_ocsp(&good);
+        assert!(verifier.verify_with(&ee, &[], time, stapled).is_ok());
+
+        // ... including when tracing the verification.
+        let trace = crate::trace::TraceLog::new();
+        let options = stapled.with_trace(&trace);
+        assert!(verifier.verify_with(&ee, &[], time, options).is_ok());
+        assert_eq!(trace.into_entries().len(), 1);
+        let stapled = crate::PerCallOptions::new().with_stapled_ocsp(&[0x30, 0x00]);
+        assert_eq!(
+            verifier.verify_with(&ee, &[], time, stapled).map(|_| ()),
+            Err(Error::UnknownRevocationStatus)
+        );
+    }
+
+    #[test]
+    fn stapled_intermediate_ocsp() {
+        let trust_anchor = make_issuer("Trust Anchor");
+        let anchor_der = trust_anchor.cert.der().clone();
+        let anchors = [anchor_from_trusted_cert(&anchor_der).unwrap()];
+        let intermediate_key = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let intermediate = issuer_params("Intermediate")
+            .signed_by(
+                &intermediate_key,
+                &trust_anchor.cert,
+                &trust_anchor.key_pair,
+            )
+            .unwrap();
+        let intermediates = [intermediate.der().clone()];
+        let ee = make_end_entity(&intermediate, &intermediate_key);
+        let ee_der = ee.cert.der().clone();
+        let ee_cert = EndEntityCert::try_from(&ee_der).unwrap();
+
+        // A response about the intermediate, signed by the trust anchor.
+        let pki = Pki {
+            issuer: trust_anchor,
+            ee: CertifiedKey {
+                cert: intermediate,
+                key_pair: intermediate_key,
+            },
+        };
+        let (issuer, key_pair) = (&pki.issuer.cert, &pki.issuer.key_pair);
+        let intermediate_response = pki.response(issuer, key_pair, GOOD, THIS_UPDATE, None, &[]);
+
+        // A response about the end-entity certificate, signed by the intermediate.
+        let pki = Pki { issuer: pki.ee, ee };
+        let (issuer, key_pair) = (&pki.issuer.cert, &pki.issuer.key_pair);
+        let ee_response = pki.response(issuer, key_pair, GOOD, THIS_UPDATE, None, &[]);
+
+        let verify = |options: &VerificationOptions<'_>| {
+            let revocation = RevocationOptions {
+                crls: &[],
+                ocsp_responses: &[],
+                depth: crate::crl::RevocationCheckDepth::Chain,
+                status_policy: UnknownStatusPolicy::Deny,
+                expiration_policy: crate::crl::ExpirationPolicy::Ignore,
+            };
+            verify_server_auth(
+                &ee_cert,
+                &anchors,
+                &intermediates,
+                Some(revocation),
+                options,
+            )
+            .map(|_| ())
+        };
+
+        let options = ocsp_options().with_stapled_ocsp(&ee_response);
+        assert_eq!(verify(&options), Err(Error::UnknownRevocationStatus));
+
+        let responses = [&intermediate_response[..]];
+        let options = options.with_stapled_intermediate_ocsp(&responses);
+        assert_eq!(verify(&options), Ok(()));
+
+        // Responses about intermediates are not used for the end-entity certificate.
+        let responses = [&intermediate_response[..], &ee_response[..]];
+        let options = ocsp_options().with_stapled_intermediate_ocsp(&responses);
+        assert_eq!(verify(&options), Err(Error::UnknownRevocationStatus));
+    }
+
+    #[test]
+    fn must_staple() {
+        let issuer = make_issuer("Issuer");
+        let mut params = end_entity_params(vec!["example.com".into()]);
+        params.custom_extensions = vec![rcgen::CustomExtension::from_oid_content(
+            &[1, 3, 6, 1, 5, 5, 7, 1, 24],
+            vec![0x30, 0x03, 0x02, 0x01, 0x05],
+        )];
+        let key_pair = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let cert = params
+            .signed_by(&key_pair, &issuer.cert, &issuer.key_pair)
+            .unwrap();
+        let pki = Pki {
+            issuer,
+            ee: CertifiedKey { cert, key_pair },
+        };
+
+        let (issuer, key_pair) = (&pki.issuer.cert, &pki.issuer.key_pair);
+        let good = pki.response(issuer, key_pair, GOOD, THIS_UPDATE, None, &[]);
+        let unknown = pki.response(issuer, key_pair, UNKNOWN, THIS_UPDATE, None, &[]);
+        let verify = |options: VerificationOptions<'_>| {
+            pki.verify_with_options(&[], UnknownStatusPolicy::Allow, &options)
+        };
+
+        // Must-Staple is ignored by default.
+        assert_eq!(verify(ocsp_options()), Ok(()));
+
+        let options = ocsp_options().with_must_staple_policy(MustStaplePolicy::Enforce);
+        assert_eq!(verify(options), Err(Error::RequiredOcspStapleNotFound));
+        assert_eq!(
+            verify(options.with_stapled_ocsp(&unknown)),
+            Err(Error::RequiredOcspStapleNotFound)
+        );
+        assert_eq!(
+            verify(options.with_stapled_ocsp(&[0x30, 0x00])),
+            Err(Error::RequiredOcspStapleNotFound)
+        );
+        assert_eq!(verify(options.with_stapled_ocsp(&good)), Ok(()));
+
+        // Certificates without Must-Staple don't require a stapled response.
+        let pki = Pki::new();
+        assert_eq!(
+            pki.verify_with_options(&[], UnknownStatusPolicy::Allow, &options),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn ocsp_revocation_opts_builder() {
+        assert!(matches!(
+            RevocationOptionsBuilder::from_ocsp_responses(&[]),
+            Err(crate::crl::OcspResponsesRequired(_))
+        ));
+
+        let pki = Pki::new();
+        let der = pki.response(
+            &pki.issuer.cert,
+            &pki.issuer.key_pair,
+            GOOD,
+            THIS_UPDATE,
+            None,
+            &[],
+        );
+        let response = BorrowedOcspResponse::from_der(&der).unwrap().into();
+        let responses = [&response];
+        let opts = RevocationOptionsBuilder::from_ocsp_responses(&responses)
+            .unwrap()
+            .build();
+        assert!(opts.crls.is_empty());
+        assert_eq!(opts.ocsp_responses.len(), 1);
+    }
+
+    struct Pki {
+        issuer: CertifiedKey,
+        ee: CertifiedKey,
+    }
+
+    impl Pki {
+        fn new() -> Self {
+            let issuer = make_issuer("Issuer");
+            let ee = make_end_entity(&issuer.cert, &issuer.key_pair);
+            Self { issuer, ee }
+        }
+
+        /// A responder certificate issued by the issuer, with the given EKUs.
+        fn responder(&self, eku: Vec<rcgen::ExtendedKeyUsagePurpose>) -> CertifiedKey {
+            let key_pair = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+            let cert = responder_params(eku)
+                .signed_by(&key_pair, &self.issuer.cert, &self.issuer.key_pair)
+                .unwrap();
+            CertifiedKey { cert, key_pair }
+        }
+
+        /// An OCSP response about the end-entity certificate, from `responder` and signed
+        /// with `key_pair`.
+        fn response(
+            &self,
+            responder: &rcgen::Certificate,
+            key_pair: &KeyPair,
+            status: &[u8],
+            this_update: &str,
+            next_update: Option<&str>,
+            certs: &[&[u8]],
+        ) -> Vec<u8> {
+            let ee = Cert::from_der(untrusted::Input::from(self.ee.cert.der())).unwrap();
+            let issuer = Cert::from_der(untrusted::Input::from(self.issuer.cert.der())).unwrap();
+            let issuer_key = der::read_all::<SubjectPublicKeyInfo<'_>>(issuer.spki).unwrap();
+            let responder_cert = Cert::from_der(untrusted::Input::from(responder.der())).unwrap();
+
+            let issuer_name = tlv(0x30, &[issuer.subject.as_slice_less_safe()]);
+            let cert_id = tlv(
+                0x30,
+                &[
+                    &tlv(0x30, &[&tlv(0x06, &[provider::OCSP_SHA256.oid()])]),
+                    &tlv(0x04, &[&provider::SHA256.hash(&[&issuer_name])]),
+                    &tlv(
+                        0x04,
+                        &[&provider::SHA256.hash(&[issuer_key.key_value.as_slice_less_safe()])],
+                    ),
+                    &tlv(0x02, &[ee.serial()]),
+                ],
+            );
+            let next_update = next_update
+                .map(|next_update| tlv(0xa0, &[&time(next_update)]))
+                .unwrap_or_default();
+            let single = tlv(0x30, &[&cert_id, status, &time(this_update), &next_update]);
+
+            let responder_id = tlv(
+                0xa1,
+                &[&tlv(0x30, &[responder_cert.subject.as_slice_less_safe()])],
+            );
+            let tbs = tlv(
+                0x30,
+                &[&responder_id, &time(THIS_UPDATE), &tlv(0x30, &[&single])],
+            );
+
+            let signature = sign(key_pair, &tbs);
+            let certs = match certs.is_empty() {
+                true => Vec::new(),
+                false => tlv(0xa0, &[&tlv(0x30, certs)]),
+            };
+            let basic = tlv(
+                0x30,
+                &[
+                    &tbs,
+                    &tlv(0x30, &[ECDSA_SHA256_ALG_ID]),
+                    &tlv(0x03, &[&[0], &signature]),
+                    &certs,
+                ],
+            );
+
+            let response_bytes = tlv(
+                0x30,
+                &[&tlv(0x06, &[ID_PKIX_OCSP_BASIC]), &tlv(0x04, &[&basic])],
+            );
+            tlv(0x30, &[&[0x0a, 0x01, 0x00], &tlv(0xa0, &[&response_bytes])])
+        }
+
+        fn verify(
+            &self,
+            responses: &[&OcspResponse<'_>],
+            status_policy: UnknownStatusPolicy,
+        ) -> Result<(), Error> {
+            self.verify_with_options(responses, status_policy, &ocsp_options())
+        }
+
+        fn verify_with_options(
+            &self,
+            responses: &[&OcspResponse<'_>],
+            status_policy: UnknownStatusPolicy,
+            options: &VerificationOptions<'_>,
+        ) -> Result<(), Error> {
+            let anchors = [anchor_from_trusted_cert(self.issuer.cert.der()).unwrap()];
+            let ee = EndEntityCert::try_from(self.ee.cert.der()).unwrap();
+            let revocation = RevocationOptions {
+                crls: &[],
+                ocsp_responses: responses,
+                depth: crate::crl::RevocationCheckDepth::Chain,
+                status_policy,
+                expiration_policy: crate::crl::ExpirationPolicy::Ignore,
+            };
+            verify_server_auth(&ee, &anchors, &[], Some(revocation), options).map(|_| ())
+        }
+    }
+
+    fn responder_params(eku: Vec<rcgen::ExtendedKeyUsagePurpose>) -> rcgen::CertificateParams {
+        let mut params = rcgen::CertificateParams::new(Vec::new()).unwrap();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::OrganizationName, "Responder");
+        params.extended_key_usages = eku;
+        params
+    }
+
+    /// Verification options matching OCSP responses using SHA-1 and SHA-256.
+    fn ocsp_options() -> VerificationOptions<'static> {
+        VerificationOptions::new().with_ocsp_hashers(HASHERS)
+    }
+
+    static HASHERS: &[&dyn OcspHasher] = &[provider::OCSP_SHA1, provider::OCSP_SHA256];
+
+    fn time(time: &str) -> Vec<u8> {
+        tlv(0x18, &[time.as_bytes()])
+    }
+
+    const GOOD: &[u8] = &[0x80, 0x00];
+    const UNKNOWN: &[u8] = &[0x82, 0x00];
+
+    // Around the verification time of 1986-12-23.
+    const THIS_UPDATE: &str = "19861201000000Z";
+    const NEXT_UPDATE: &str = "19870101000000Z";
+
+    const ECDSA_SHA256_ALG_ID: &[u8] =
+        &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+}
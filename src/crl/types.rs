@@ -2,10 +2,12 @@
 use alloc::collections::BTreeMap;
 #[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+use core::cmp::Ordering;
 use core::fmt::Debug;
 
 use pki_types::{SignatureVerificationAlgorithm, UnixTime};
 
+use crate::algorithm_policy::AlgorithmPolicy;
 use crate::cert::lenient_certificate_serial_number;
 use crate::crl::crl_signature_err;
 use crate::der::{self, DerIterator, FromDer, Tag, CONSTRUCTED, CONTEXT_SPECIFIC};
@@ -64,8 +66,33 @@ impl CertRevocationList<'_> {
         }
     }
 
+    /// Return the CRL number of the CRL, if any, as a big-endian unsigned integer.
+    pub fn crl_number(&self) -> Option<&[u8]> {
+        match self {
+            #[cfg(feature = "alloc")]
+            CertRevocationList::Owned(crl) => crl.crl_number.as_deref(),
+            CertRevocationList::Borrowed(crl) => {
+                crl.crl_number.map(|number| number.as_slice_less_safe())
+            }
+        }
+    }
+
+    /// Return the CRL number of the complete CRL this delta CRL updates (BaseCRLNumber), as a
+    /// big-endian unsigned integer, or `None` if this is not a delta CRL.
+    pub fn base_crl_number(&self) -> Option<&[u8]> {
+        match self {
+            #[cfg(feature = "alloc")]
+            CertRevocationList::Owned(crl) => crl.base_crl_number.as_deref(),
+            CertRevocationList::Borrowed(crl) => crl
+                .base_crl_number
+                .map(|number| number.as_slice_less_safe()),
+        }
+    }
+
     /// Try to find a revoked certificate in the CRL by DER encoded serial number. This
     /// may yield an error if the CRL has malformed revoked certificates.
+    ///
+    /// In an indirect CRL, entries for certificates from any certificate issuer are considered.
     pub fn find_serial(&self, serial: &[u8]) -> Result<Option<BorrowedRevokedCert<'_>>, Error> {
         match self {
             #[cfg(feature = "alloc")]
@@ -74,10 +101,36 @@ impl CertRevocationList<'_> {
         }
     }
 
+    /// Find the entry for the certificate with the given DER encoded serial number, issued by
+    /// `cert_issuer`. Unlike [`Self::find_serial`], this only considers the entries for
+    /// certificates from `cert_issuer` in an indirect CRL.
+    pub(crate) fn find_revoked_cert(
+        &self,
+        serial: &[u8],
+        cert_issuer: &[u8],
+    ) -> Result<Option<BorrowedRevokedCert<'_>>, Error> {
+        match self {
+            #[cfg(feature = "alloc")]
+            CertRevocationList::Owned(crl) => Ok(crl.find_revoked_cert(serial, cert_issuer)),
+            CertRevocationList::Borrowed(crl) => {
+                for revoked_cert in crl.revoked_certs_with_issuer() {
+                    let (issuer, revoked_cert) = revoked_cert?;
+                    if issuer == cert_issuer && revoked_cert.serial_number == serial {
+                        return Ok(Some(revoked_cert));
+                    }
+                }
+
+                Ok(None)
+            }
+        }
+    }
+
     /// Returns true if the CRL can be considered authoritative for the given certificate.
     ///
     /// A CRL is considered authoritative for a certificate when:
-    ///   * The certificate issuer matches the CRL issuer and,
+    ///   * The certificate issuer matches the CRL issuer, or the CRL is an indirect CRL
+    ///     issued by the CRL issuer named in one of the certificate's CRL distribution points,
+    ///     and,
     ///     * The certificate has no CRL distribution points, and the CRL has no issuing distribution
     ///       point extension.
     ///     * Or, the certificate has no CRL distribution points, but the the CRL has an issuing
@@ -91,12 +144,6 @@ impl CertRevocationList<'_> {
     ///
     /// In all other circumstances the CRL is not considered authoritative.
     pub(crate) fn authoritative(&self, path: &PathNode<'_>) -> bool {
-        // In all cases we require that the authoritative CRL have the same issuer
-        // as the certificate. Recall we do not support indirect CRLs.
-        if self.issuer() != path.cert.issuer() {
-            return false;
-        }
-
         let crl_idp = match self.issuing_distribution_point() {
             // If the CRL has an issuing distribution point, parse it so we can consider its scope
             // and compare against the cert CRL distribution points, if present.
@@ -109,11 +156,33 @@ impl CertRevocationList<'_> {
             // If the CRL has no issuing distribution point we assume the CRL scope
             // to be "everything" and consider the CRL authoritative for the cert based on the
             // issuer matching. We do not need to consider the certificate's CRL distribution point
-            // extension (see also https://github.com/rustls/webpki/issues/228).
-            None => return true,
+            // extension (see also https://github.com/rustls/webpki/issues/228). Without an issuing
+            // distribution point the CRL can't be indirect, so the issuers must match.
+            None => return self.issuer() == path.cert.issuer(),
         };
 
-        crl_idp.authoritative_for(path)
+        crl_idp.authoritative_for(path, untrusted::Input::from(self.issuer()))
+    }
+
+    /// Returns true if this is a delta CRL that can be combined with the complete CRL `base`.
+    ///
+    /// RFC 5280 §5.2.4 requires that the delta CRL has the same issuer and scope as `base`, and
+    /// that `base` is at least as recent as the delta's BaseCRLNumber. We additionally require
+    /// that the delta is more recent than `base`, since otherwise it adds nothing.
+    pub(crate) fn is_delta_for(&self, base: &CertRevocationList<'_>) -> bool {
+        let (base_crl_number, delta_crl_number, crl_number) =
+            match (self.base_crl_number(), self.crl_number(), base.crl_number()) {
+                (Some(base_crl_number), Some(delta_crl_number), Some(crl_number)) => {
+                    (base_crl_number, delta_crl_number, crl_number)
+                }
+                _ => return false,
+            };
+
+        base.base_crl_number().is_none()
+            && self.issuer() == base.issuer()
+            && self.issuing_distribution_point() == base.issuing_distribution_point()
+            && compare_crl_numbers(base_crl_number, crl_number) != Ordering::Greater
+            && compare_crl_numbers(delta_crl_number, crl_number) == Ordering::Greater
     }
 
     /// Verify the CRL signature using the issuer certificate and a list of supported signature
@@ -121,24 +190,24 @@ impl CertRevocationList<'_> {
     pub(crate) fn verify_signature(
         &self,
         supported_sig_algs: &[&dyn SignatureVerificationAlgorithm],
+        algorithms: &AlgorithmPolicy<'_>,
         issuer_spki: untrusted::Input<'_>,
         budget: &mut Budget,
     ) -> Result<(), Error> {
-        signed_data::verify_signed_data(
-            supported_sig_algs,
-            issuer_spki,
-            &match self {
-                #[cfg(feature = "alloc")]
-                CertRevocationList::Owned(crl) => crl.signed_data.borrow(),
-                CertRevocationList::Borrowed(crl) => SignedData {
-                    data: crl.signed_data.data,
-                    algorithm: crl.signed_data.algorithm,
-                    signature: crl.signed_data.signature,
-                },
+        let signed_data = match self {
+            #[cfg(feature = "alloc")]
+            CertRevocationList::Owned(crl) => crl.signed_data.borrow(),
+            CertRevocationList::Borrowed(crl) => SignedData {
+                data: crl.signed_data.data,
+                algorithm: crl.signed_data.algorithm,
+                signature: crl.signed_data.signature,
             },
-            budget,
-        )
-        .map_err(crl_signature_err)
+        };
+
+        algorithms.check_signature_algorithm(signed_data.algorithm)?;
+        algorithms.check_key(issuer_spki)?;
+        signed_data::verify_signed_data(supported_sig_algs, issuer_spki, &signed_data, budget)
+            .map_err(crl_signature_err)
     }
 
     /// Checks the verification time is before the time in the CRL nextUpdate field.
@@ -167,10 +236,18 @@ pub struct OwnedCertRevocationList {
     /// of the revoked cert's serial number.
     revoked_certs: BTreeMap<Vec<u8>, OwnedRevokedCert>,
 
+    /// For indirect CRLs, the revoked certificates issued by other issuers than the CRL issuer,
+    /// keyed by the DER encoding of their issuer and then of their serial number.
+    indirect_revoked_certs: BTreeMap<Vec<u8>, BTreeMap<Vec<u8>, OwnedRevokedCert>>,
+
     issuer: Vec<u8>,
 
     issuing_distribution_point: Option<Vec<u8>>,
 
+    crl_number: Option<Vec<u8>>,
+
+    base_crl_number: Option<Vec<u8>>,
+
     signed_data: signed_data::OwnedSignedData,
 
     next_update: UnixTime,
@@ -184,7 +261,7 @@ impl OwnedCertRevocationList {
     ///   * CRL versions other than version 2.
     ///   * CRLs missing the next update field.
     ///   * CRLs missing certificate revocation list extensions.
-    ///   * Delta CRLs.
+    ///   * Delta CRLs missing the CRL number extension.
     ///   * CRLs larger than (2^32)-1 bytes in size.
     ///
     /// See [BorrowedCertRevocationList::from_der] for more details.
@@ -201,8 +278,28 @@ impl OwnedCertRevocationList {
         Ok(self
             .revoked_certs
             .get(serial)
+            .or_else(|| {
+                self.indirect_revoked_certs
+                    .values()
+                    .find_map(|revoked_certs| revoked_certs.get(serial))
+            })
             .map(|owned_revoked_cert| owned_revoked_cert.borrow()))
     }
+
+    fn find_revoked_cert(
+        &self,
+        serial: &[u8],
+        cert_issuer: &[u8],
+    ) -> Option<BorrowedRevokedCert<'_>> {
+        let revoked_certs = match cert_issuer == self.issuer.as_slice() {
+            true => &self.revoked_certs,
+            false => self.indirect_revoked_certs.get(cert_issuer)?,
+        };
+
+        revoked_certs
+            .get(serial)
+            .map(|owned_revoked_cert| owned_revoked_cert.borrow())
+    }
 }
 
 /// Borrowed representation of a RFC 5280[^1] profile Certificate Revocation List (CRL).
@@ -220,9 +317,20 @@ pub struct BorrowedCertRevocationList<'a> {
     /// An optional CRL extension that identifies the CRL distribution point and scope for the CRL.
     issuing_distribution_point: Option<untrusted::Input<'a>>,
 
+    /// The sequence number of the CRL, from the optional CRL number extension.
+    crl_number: Option<untrusted::Input<'a>>,
+
+    /// For delta CRLs, the CRL number of the complete CRL being updated, from the delta CRL
+    /// indicator extension.
+    base_crl_number: Option<untrusted::Input<'a>>,
+
     /// List of certificates revoked by the issuer in this CRL.
     revoked_certs: untrusted::Input<'a>,
 
+    /// Whether the CRL is an indirect CRL, which may list certificates from other issuers
+    /// than the CRL issuer, according to its issuing distribution point.
+    indirect_crl: bool,
+
     next_update: UnixTime,
 }
 
@@ -233,7 +341,7 @@ impl<'a> BorrowedCertRevocationList<'a> {
     ///   * CRL versions other than version 2.
     ///   * CRLs missing the next update field.
     ///   * CRLs missing certificate revocation list extensions.
-    ///   * Delta CRLs.
+    ///   * Delta CRLs missing the CRL number extension.
     ///   * CRLs larger than (2^32)-1 bytes in size.
     ///
     /// [^1]: <https://www.rfc-editor.org/rfc/rfc5280#section-5>
@@ -246,13 +354,18 @@ impl<'a> BorrowedCertRevocationList<'a> {
     #[cfg(feature = "alloc")]
     pub fn to_owned(&self) -> Result<OwnedCertRevocationList, Error> {
         // Parse and collect the CRL's revoked cert entries, ensuring there are no errors. With
-        // the full set in-hand, create a lookup map by serial number for fast revocation checking.
-        let revoked_certs = self
-            .into_iter()
-            .collect::<Result<Vec<_>, _>>()?
-            .iter()
-            .map(|revoked_cert| (revoked_cert.serial_number.to_vec(), revoked_cert.to_owned()))
-            .collect::<BTreeMap<_, _>>();
+        // the full set in-hand, create lookup maps by issuer and serial number for fast
+        // revocation checking.
+        let mut revoked_certs = BTreeMap::new();
+        let mut indirect_revoked_certs = BTreeMap::<_, BTreeMap<_, _>>::new();
+        for revoked_cert in self.revoked_certs_with_issuer() {
+            let (issuer, revoked_cert) = revoked_cert?;
+            let revoked_certs = match issuer == self.issuer.as_slice_less_safe() {
+                true => &mut revoked_certs,
+                false => indirect_revoked_certs.entry(issuer.to_vec()).or_default(),
+            };
+            revoked_certs.insert(revoked_cert.serial_number.to_vec(), revoked_cert.to_owned());
+        }
 
         Ok(OwnedCertRevocationList {
             signed_data: self.signed_data.to_owned(),
@@ -260,7 +373,14 @@ impl<'a> BorrowedCertRevocationList<'a> {
             issuing_distribution_point: self
                 .issuing_distribution_point
                 .map(|idp| idp.as_slice_less_safe().to_vec()),
+            crl_number: self
+                .crl_number
+                .map(|number| number.as_slice_less_safe().to_vec()),
+            base_crl_number: self
+                .base_crl_number
+                .map(|number| number.as_slice_less_safe().to_vec()),
             revoked_certs,
+            indirect_revoked_certs,
             next_update: self.next_update,
         })
     }
@@ -269,29 +389,14 @@ impl<'a> BorrowedCertRevocationList<'a> {
         remember_extension(extension, |id| {
             match id {
                 // id-ce-cRLNumber 2.5.29.20 - RFC 5280 §5.2.3
-                20 => {
-                    // RFC 5280 §5.2.3:
-                    //   CRL verifiers MUST be able to handle CRLNumber values
-                    //   up to 20 octets.  Conforming CRL issuers MUST NOT use CRLNumber
-                    //   values longer than 20 octets.
-                    //
-                    extension.value.read_all(Error::InvalidCrlNumber, |der| {
-                        let crl_number = der::nonnegative_integer(der)
-                            .map_err(|_| Error::InvalidCrlNumber)?
-                            .as_slice_less_safe();
-                        if crl_number.len() <= 20 {
-                            Ok(crl_number)
-                        } else {
-                            Err(Error::InvalidCrlNumber)
-                        }
-                    })?;
-                    // We enforce the cRLNumber is sensible, but don't retain the value for use.
-                    Ok(())
-                }
+                // We enforce the cRLNumber is sensible, and retain its value to relate delta
+                // CRLs to complete CRLs.
+                20 => set_extension_once(&mut self.crl_number, || crl_number(extension.value)),
 
                 // id-ce-deltaCRLIndicator 2.5.29.27 - RFC 5280 §5.2.4
-                // We explicitly do not support delta CRLs.
-                27 => Err(Error::UnsupportedDeltaCrl),
+                //   BaseCRLNumber ::= CRLNumber
+                // We retain the value to combine the delta CRL with complete CRLs.
+                27 => set_extension_once(&mut self.base_crl_number, || crl_number(extension.value)),
 
                 // id-ce-issuingDistributionPoint 2.5.29.28 - RFC 5280 §5.2.4
                 // We recognize the extension and retain its value for use.
@@ -309,11 +414,36 @@ impl<'a> BorrowedCertRevocationList<'a> {
         })
     }
 
+    /// Iterate over the revoked certificates in the CRL, along with the DER encoded issuer of
+    /// each.
+    ///
+    /// RFC 5280 §5.3.3:
+    ///   If this extension is not present on the first entry in an indirect CRL,
+    ///   the certificate issuer defaults to the CRL issuer.  On subsequent entries
+    ///   in an indirect CRL, if this extension is not present, the certificate
+    ///   issuer for the entry is the same as that for the preceding entry.
+    fn revoked_certs_with_issuer(
+        &self,
+    ) -> impl Iterator<Item = Result<(&[u8], BorrowedRevokedCert<'_>), Error>> {
+        let mut issuer = self.issuer.as_slice_less_safe();
+        let indirect_crl = self.indirect_crl;
+        self.into_iter().map(move |revoked_cert| {
+            let revoked_cert = revoked_cert?;
+            match (revoked_cert.certificate_issuer, indirect_crl) {
+                (Some(certificate_issuer), true) => issuer = certificate_issuer,
+                // The certificate issuer extension may only be used in indirect CRLs.
+                (Some(_), false) => return Err(Error::MalformedExtensions),
+                (None, _) => {}
+            }
+            Ok((issuer, revoked_cert))
+        })
+    }
+
     fn find_serial(&self, serial: &[u8]) -> Result<Option<BorrowedRevokedCert<'_>>, Error> {
-        for revoked_cert_result in self {
+        for revoked_cert_result in self.revoked_certs_with_issuer() {
             match revoked_cert_result {
                 Err(e) => return Err(e),
-                Ok(revoked_cert) => {
+                Ok((_, revoked_cert)) => {
                     if revoked_cert.serial_number.eq(serial) {
                         return Ok(Some(revoked_cert));
                     }
@@ -332,7 +462,7 @@ impl<'a> FromDer<'a> for BorrowedCertRevocationList<'a> {
     ///   * CRL versions other than version 2.
     ///   * CRLs missing the next update field.
     ///   * CRLs missing certificate revocation list extensions.
-    ///   * Delta CRLs.
+    ///   * Delta CRLs missing the CRL number extension.
     ///   * CRLs larger than (2^32)-1 bytes in size.
     ///
     /// [^1]: <https://www.rfc-editor.org/rfc/rfc5280#section-5>
@@ -345,7 +475,7 @@ impl<'a> FromDer<'a> for BorrowedCertRevocationList<'a> {
             der::MAX_DER_SIZE,
         )?;
 
-        let crl = tbs_cert_list.read_all(Error::BadDer, |tbs_cert_list| {
+        let mut crl = tbs_cert_list.read_all(Error::BadDer, |tbs_cert_list| {
             // RFC 5280 §5.1.2.1:
             //   This optional field describes the version of the encoded CRL.  When
             //   extensions are used, as required by this profile, this field MUST be
@@ -388,7 +518,6 @@ impl<'a> FromDer<'a> for BorrowedCertRevocationList<'a> {
             // RFC 5280 §5.1.2.6:
             //   When there are no revoked certificates, the revoked certificates list
             //   MUST be absent
-            // TODO(@cpu): Do we care to support empty CRLs if we don't support delta CRLs?
             let revoked_certs = if tbs_cert_list.peek(Tag::Sequence.into()) {
                 der::expect_tag_and_get_value_limited(
                     tbs_cert_list,
@@ -404,6 +533,9 @@ impl<'a> FromDer<'a> for BorrowedCertRevocationList<'a> {
                 issuer,
                 revoked_certs,
                 issuing_distribution_point: None,
+                crl_number: None,
+                base_crl_number: None,
+                indirect_crl: false,
                 next_update,
             };
 
@@ -440,10 +572,20 @@ impl<'a> FromDer<'a> for BorrowedCertRevocationList<'a> {
             Ok(crl)
         })?;
 
+        // RFC 5280 §5.2:
+        //   Conforming CRL issuers are REQUIRED to include the authority key
+        //   identifier (Section 5.2.1) and the CRL number (Section 5.2.3)
+        //   extensions in all CRLs issued.
+        // We tolerate complete CRLs without a CRL number, but a delta CRL without one can't be
+        // ordered against the complete CRL it updates, so we can't use it.
+        if crl.base_crl_number.is_some() && crl.crl_number.is_none() {
+            return Err(Error::UnsupportedDeltaCrl);
+        }
+
         // If an issuing distribution point extension is present, parse it up-front to validate
         // that it only uses well-formed and supported features.
         if let Some(der) = crl.issuing_distribution_point {
-            IssuingDistributionPoint::from_der(der)?;
+            crl.indirect_crl = IssuingDistributionPoint::from_der(der)?.indirect_crl;
         }
 
         Ok(crl)
@@ -452,6 +594,29 @@ impl<'a> FromDer<'a> for BorrowedCertRevocationList<'a> {
     const TYPE_ID: DerTypeId = DerTypeId::CertRevocationList;
 }
 
+/// Read the value of a CRL number or delta CRL indicator extension.
+fn crl_number(value: untrusted::Input<'_>) -> Result<untrusted::Input<'_>, Error> {
+    // RFC 5280 §5.2.3:
+    //   CRL verifiers MUST be able to handle CRLNumber values
+    //   up to 20 octets.  Conforming CRL issuers MUST NOT use CRLNumber
+    //   values longer than 20 octets.
+    //
+    value.read_all(Error::InvalidCrlNumber, |der| {
+        let crl_number = der::nonnegative_integer(der).map_err(|_| Error::InvalidCrlNumber)?;
+        if crl_number.len() <= 20 {
+            Ok(crl_number)
+        } else {
+            Err(Error::InvalidCrlNumber)
+        }
+    })
+}
+
+/// Compare two CRL numbers, as read by [`crl_number`].
+pub(crate) fn compare_crl_numbers(a: &[u8], b: &[u8]) -> Ordering {
+    // Both are minimally encoded, so the longer is larger.
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
 impl<'a> IntoIterator for &'a BorrowedCertRevocationList<'a> {
     type Item = Result<BorrowedRevokedCert<'a>, Error>;
     type IntoIter = DerIterator<'a, BorrowedRevokedCert<'a>>;
@@ -545,11 +710,6 @@ impl<'a> IssuingDistributionPoint<'a> {
             return Err(Error::MalformedExtensions);
         }
 
-        // We don't support indirect CRLs.
-        if result.indirect_crl {
-            return Err(Error::UnsupportedIndirectCrl);
-        }
-
         // We don't support CRLs partitioned by revocation reason.
         if result.only_some_reasons.is_some() {
             return Err(Error::UnsupportedRevocationReasonsPartitioning);
@@ -583,13 +743,26 @@ impl<'a> IssuingDistributionPoint<'a> {
     /// is a Uniform Resource Identifier (URI) general name that can also be found in the CRL
     /// issuing distribution point.
     ///
+    /// If the CRL issuer is not the certificate issuer, the CRL must be an indirect CRL, and
+    /// only CRL DPs with a cRLIssuer directory name matching `crl_issuer` are considered. Such
+    /// a CRL DP may omit its distribution point name.
+    ///
     /// We do not consider:
     /// * Distribution point names relative to an issuer.
     /// * General names of a type other than URI.
     /// * Malformed names or invalid IDP or CRL DP extensions.
-    pub(crate) fn authoritative_for(&self, node: &PathNode<'a>) -> bool {
+    pub(crate) fn authoritative_for(
+        &self,
+        node: &PathNode<'a>,
+        crl_issuer: untrusted::Input<'_>,
+    ) -> bool {
         assert!(!self.only_contains_attribute_certs); // We check this at time of parse.
 
+        let direct = public_values_eq(crl_issuer, node.cert.issuer);
+        if !direct && !self.indirect_crl {
+            return false;
+        }
+
         // Check that the scope of the CRL issuing distribution point could include the cert.
         if self.only_contains_ca_certs && node.role() != Role::Issuer
             || self.only_contains_user_certs && node.role() != Role::EndEntity
@@ -600,7 +773,7 @@ impl<'a> IssuingDistributionPoint<'a> {
         let cert_dps = match node.cert.crl_distribution_points() {
             // If the certificate has no distribution points, then the CRL can be authoritative
             // based on the issuer matching and the scope including the cert.
-            None => return true,
+            None => return direct,
             Some(cert_dps) => cert_dps,
         };
 
@@ -616,14 +789,33 @@ impl<'a> IssuingDistributionPoint<'a> {
                 Err(_) => return false,
             };
 
-            // If the certificate CRL DP was for an indirect CRL, or a CRL
-            // sharded by revocation reason, it can't match.
-            if cert_dp.crl_issuer.is_some() || cert_dp.reasons.is_some() {
+            // If the certificate CRL DP was for a CRL sharded by revocation reason, it can't
+            // match.
+            if cert_dp.reasons.is_some() {
                 return false;
             }
 
+            // RFC 5280 §6.3.3 (b)(2):
+            //   If the DP includes cRLIssuer, then verify that the issuer field in the
+            //   complete CRL matches cRLIssuer in the DP and that the complete CRL
+            //   contains an issuing distribution point extension with the indirectCRL
+            //   boolean asserted.  Otherwise, verify that the CRL issuer matches the
+            //   certificate issuer.
+            let indirect = match cert_dp.crl_issuer {
+                Some(dp_crl_issuer) => {
+                    if !self.indirect_crl || !has_directory_name(dp_crl_issuer, crl_issuer) {
+                        continue;
+                    }
+                    true
+                }
+                None if direct => false,
+                None => continue,
+            };
+
             let mut dp_general_names = match cert_dp.names() {
                 Ok(Some(DistributionPointName::FullName(general_names))) => general_names,
+                // A CRL DP for an indirect CRL may be identified by its cRLIssuer alone.
+                Ok(None) if indirect => return true,
                 _ => return false, // Either no full names, or malformed.
             };
 
@@ -663,6 +855,59 @@ impl<'a> IssuingDistributionPoint<'a> {
     }
 }
 
+/// Returns true if `general_names`, the DER encoded contents of a GeneralNames, contains a
+/// directory name that is the same as `name`, the DER encoded contents of a Name.
+fn has_directory_name(general_names: untrusted::Input<'_>, name: untrusted::Input<'_>) -> bool {
+    let mut reader = untrusted::Reader::new(general_names);
+    while !reader.at_end() {
+        match directory_name(&mut reader) {
+            Ok(Some(directory_name)) if public_values_eq(directory_name, name) => return true,
+            Ok(_) => {}
+            Err(_) => return false,
+        }
+    }
+
+    false
+}
+
+/// Read the value of a certificate issuer CRL entry extension, returning the DER encoded
+/// contents of the first directory name in it.
+fn certificate_issuer(value: untrusted::Input<'_>) -> Result<untrusted::Input<'_>, Error> {
+    // RFC 5280 §5.3.3:
+    //   When present, the certificate issuer CRL entry extension
+    //   includes one or more names from the issuer field and/or issuer
+    //   alternative name extension of the certificate that corresponds to the
+    //   CRL entry.
+    // Certificates are matched to entries using their issuer field, which is a directory name.
+    value.read_all(Error::BadDer, |der| {
+        der::nested(der, Tag::Sequence, Error::BadDer, |general_names| {
+            let mut result = None;
+            while !general_names.at_end() {
+                let name = directory_name(general_names)?;
+                if result.is_none() {
+                    result = name;
+                }
+            }
+            result.ok_or(Error::UnsupportedIndirectCrl)
+        })
+    })
+}
+
+/// Read a GeneralName, returning the DER encoded contents of the Name if it is a directory name.
+fn directory_name<'a>(
+    reader: &mut untrusted::Reader<'a>,
+) -> Result<Option<untrusted::Input<'a>>, Error> {
+    const DIRECTORY_NAME_TAG: u8 = CONTEXT_SPECIFIC | CONSTRUCTED | 4;
+
+    // Name is a CHOICE, so the explicit tagging of directoryName is kept.
+    match der::read_tag_and_get_value(reader)? {
+        (DIRECTORY_NAME_TAG, value) => value
+            .read_all(Error::BadDer, |name| der::expect_tag(name, Tag::Sequence))
+            .map(Some),
+        _ => Ok(None),
+    }
+}
+
 /// Owned representation of a RFC 5280[^1] profile Certificate Revocation List (CRL) revoked
 /// certificate entry.
 ///
@@ -688,6 +933,11 @@ pub struct OwnedRevokedCert {
     /// that the certificate otherwise became invalid. This date may be earlier than the revocation
     /// date which is the date at which the CA processed the revocation.
     pub invalidity_date: Option<UnixTime>,
+
+    /// In indirect CRLs, the DER encoded issuer of the revoked certificate and of the following
+    /// entries, up to the next entry with a certificate issuer. When absent, the issuer is the
+    /// same as for the preceding entry, or the CRL issuer for the first entry.
+    pub certificate_issuer: Option<Vec<u8>>,
 }
 
 #[cfg(feature = "alloc")]
@@ -699,6 +949,7 @@ impl OwnedRevokedCert {
             revocation_date: self.revocation_date,
             reason_code: self.reason_code,
             invalidity_date: self.invalidity_date,
+            certificate_issuer: self.certificate_issuer.as_deref(),
         }
     }
 }
@@ -725,6 +976,11 @@ pub struct BorrowedRevokedCert<'a> {
     /// that the certificate otherwise became invalid. This date may be earlier than the revocation
     /// date which is the date at which the CA processed the revocation.
     pub invalidity_date: Option<UnixTime>,
+
+    /// In indirect CRLs, the DER encoded issuer of the revoked certificate and of the following
+    /// entries, up to the next entry with a certificate issuer. When absent, the issuer is the
+    /// same as for the preceding entry, or the CRL issuer for the first entry.
+    pub certificate_issuer: Option<&'a [u8]>,
 }
 
 impl<'a> BorrowedRevokedCert<'a> {
@@ -736,6 +992,7 @@ impl<'a> BorrowedRevokedCert<'a> {
             revocation_date: self.revocation_date,
             reason_code: self.reason_code,
             invalidity_date: self.invalidity_date,
+            certificate_issuer: self.certificate_issuer.map(|issuer| issuer.to_vec()),
         }
     }
 
@@ -755,9 +1012,10 @@ impl<'a> BorrowedRevokedCert<'a> {
                 //   with an entry in an indirect CRL, that is, a CRL that has the
                 //   indirectCRL indicator set in its issuing distribution point
                 //   extension.
-                // We choose not to support indirect CRLs and so turn this into a more specific
-                // error rather than simply letting it fail as an unsupported critical extension.
-                29 => Err(Error::UnsupportedIndirectCrl),
+                // We retain the issuer's directory name to match entries to certificates.
+                29 => set_extension_once(&mut self.certificate_issuer, || {
+                    certificate_issuer(extension.value).map(|issuer| issuer.as_slice_less_safe())
+                }),
 
                 // Unsupported extension
                 _ => extension.unsupported(),
@@ -793,6 +1051,7 @@ impl<'a> FromDer<'a> for BorrowedRevokedCert<'a> {
                     revocation_date,
                     reason_code: None,
                     invalidity_date: None,
+                    certificate_issuer: None,
                 };
 
                 // RFC 5280 §5.3:
@@ -857,7 +1116,7 @@ pub enum RevocationReason {
     CessationOfOperation = 5,
     CertificateHold = 6,
     // 7 is not used.
-    /// RemoveFromCrl only appears in delta CRLs that are unsupported.
+    /// RemoveFromCrl only appears in delta CRLs, for certificates that are no longer revoked.
     RemoveFromCrl = 8,
     PrivilegeWithdrawn = 9,
     AaCompromise = 10,
@@ -929,7 +1188,7 @@ mod tests {
     use super::*;
     use crate::cert::Cert;
     use crate::end_entity::EndEntityCert;
-    use crate::verify_cert::PartialPath;
+    use crate::verify_cert::{PartialPath, MAX_SUB_CA_COUNT};
 
     #[test]
     fn parse_issuing_distribution_point_ext() {
@@ -1007,10 +1266,10 @@ mod tests {
         let ca = include_bytes!("../../tests/client_auth_revocation/no_crl_ku_chain.int.a.ca.der");
         let ca = Cert::from_der(untrusted::Input::from(&ca[..])).unwrap();
 
-        let mut path = PartialPath::new(&ee);
+        let mut path = PartialPath::new(&ee, MAX_SUB_CA_COUNT);
         path.push(ca).unwrap();
 
-        assert!(!crl_issuing_dp.authoritative_for(&path.node()));
+        assert!(!crl_issuing_dp.authoritative_for(&path.node(), crl.issuer));
     }
 
     #[test]
@@ -1033,18 +1292,29 @@ mod tests {
             &include_bytes!("../../tests/client_auth_revocation/no_crl_ku_chain.ee.der")[..],
         );
         let ee = EndEntityCert::try_from(&ee).unwrap();
-        let path = PartialPath::new(&ee);
+        let path = PartialPath::new(&ee, MAX_SUB_CA_COUNT);
 
-        assert!(!crl_issuing_dp.authoritative_for(&path.node()));
+        assert!(!crl_issuing_dp.authoritative_for(&path.node(), crl.issuer));
     }
 
     #[test]
     fn test_issuing_distribution_point_indirect() {
         let crl = include_bytes!("../../tests/crls/crl.idp.indirect_crl.der");
-        // We should encounter an error parsing a CRL with an IDP extension that indicates it's an
-        // indirect CRL.
-        let result = BorrowedCertRevocationList::from_der(&crl[..]);
-        assert!(matches!(result, Err(Error::UnsupportedIndirectCrl)));
+        let crl = BorrowedCertRevocationList::from_der(&crl[..]).unwrap();
+        assert!(crl.indirect_crl);
+
+        // We should find the expected bool state.
+        let crl_issuing_dp = crl
+            .issuing_distribution_point
+            .expect("missing crl distribution point DER");
+        let crl_issuing_dp = IssuingDistributionPoint::from_der(crl_issuing_dp)
+            .expect("failed to parse issuing distribution point DER");
+        assert!(crl_issuing_dp.indirect_crl);
+
+        // The entries are for certificates from the CRL issuer, since none have a certificate
+        // issuer extension.
+        let owned_crl = crl.to_owned().unwrap();
+        assert!(owned_crl.indirect_revoked_certs.is_empty());
     }
 
     #[test]
@@ -1225,7 +1495,7 @@ mod tests {
             &include_bytes!("../../tests/client_auth_revocation/no_ku_chain.ee.der")[..],
         );
         let ee = EndEntityCert::try_from(&ee).unwrap();
-        let path = PartialPath::new(&ee);
+        let path = PartialPath::new(&ee, MAX_SUB_CA_COUNT);
 
         // The CRL should not be authoritative for an EE issued by a different issuer.
         assert!(!crl.authoritative(&path.node()));
@@ -1241,7 +1511,7 @@ mod tests {
             &include_bytes!("../../tests/client_auth_revocation/ku_chain.ee.der")[..],
         );
         let ee = EndEntityCert::try_from(&ee).unwrap();
-        let path = PartialPath::new(&ee);
+        let path = PartialPath::new(&ee, MAX_SUB_CA_COUNT);
 
         // The CRL should be considered authoritative, the issuers match, the CRL has no IDP and the
         // cert has no CRL DPs.
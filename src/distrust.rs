@@ -0,0 +1,143 @@
+use pki_types::{Der, UnixTime};
+
+use crate::cert::Cert;
+use crate::error::Error;
+use crate::public_values_eq;
+
+/// A CA that is no longer (fully) trusted.
+///
+/// The CA is identified by its subject public key info or subject name, and may be either a
+/// trust anchor or an intermediate certificate. It can be distrusted outright, or only for
+/// end-entity certificates issued after a cutoff date, as root programs do when gradually
+/// distrusting a CA. Use with [`VerificationOptions::with_distrust()`]. Paths through a
+/// distrusted CA are rejected with [`Error::CaDistrusted`].
+///
+/// [`VerificationOptions::with_distrust()`]: crate::VerificationOptions::with_distrust
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Distrust<'a> {
+    key: DistrustKey<'a>,
+    after: Option<UnixTime>,
+}
+
+impl<'a> Distrust<'a> {
+    /// Distrust every CA with the given subject public key info.
+    ///
+    /// Like [`TrustAnchor::subject_public_key_info`], this is the value of the
+    /// `subjectPublicKeyInfo` SEQUENCE, without its tag and length.
+    ///
+    /// [`TrustAnchor::subject_public_key_info`]: pki_types::TrustAnchor::subject_public_key_info
+    pub fn spki(spki: Der<'a>) -> Self {
+        Self {
+            key: DistrustKey::Spki(spki),
+            after: None,
+        }
+    }
+
+    /// Distrust every CA with the given subject name.
+    ///
+    /// Like [`TrustAnchor::subject`], this is the value of the `subject` Name SEQUENCE,
+    /// without its tag and length.
+    ///
+    /// [`TrustAnchor::subject`]: pki_types::TrustAnchor::subject
+    pub fn subject(subject: Der<'a>) -> Self {
+        Self {
+            key: DistrustKey::Subject(subject),
+            after: None,
+        }
+    }
+
+    /// Only distrust the CA for end-entity certificates whose notBefore is after `cutoff`.
+    pub fn with_not_before_after(mut self, cutoff: UnixTime) -> Self {
+        self.after = Some(cutoff);
+        self
+    }
+
+    /// Check whether the CA with the given `subject` and `spki` may be used in a path for
+    /// `end_entity`.
+    pub(crate) fn check(
+        &self,
+        subject: untrusted::Input<'_>,
+        spki: untrusted::Input<'_>,
+        end_entity: &Cert<'_>,
+    ) -> Result<(), Error> {
+        let matches = match &self.key {
+            DistrustKey::Spki(value) => public_values_eq(spki, value.as_ref().into()),
+            DistrustKey::Subject(value) => public_values_eq(subject, value.as_ref().into()),
+        };
+
+        match (matches, self.after) {
+            (false, _) => Ok(()),
+            (true, None) => Err(Error::CaDistrusted),
+            (true, Some(cutoff)) => match end_entity.not_before()? > cutoff {
+                true => Err(Error::CaDistrusted),
+                false => Ok(()),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum DistrustKey<'a> {
+    Spki(Der<'a>),
+    Subject(Der<'a>),
+}
+
+#[cfg(all(test, feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+mod tests {
+    use super::*;
+    use crate::test_utils::{end_entity_params, issuer_params, Pki};
+    use crate::verify_cert::VerificationOptions;
+    use std::prelude::v1::*;
+
+    #[test]
+    fn distrust() {
+        let pki = Pki::new();
+        assert_eq!(verify(&pki, &[]), Ok(()));
+
+        let anchor = &pki.anchors[0];
+        let anchor_spki = Distrust::spki(anchor.subject_public_key_info.as_ref().into());
+        assert_eq!(verify(&pki, &[anchor_spki]), Err(Error::CaDistrusted));
+
+        let intermediate = Cert::from_der(untrusted::Input::from(&pki.intermediates[0])).unwrap();
+        let intermediate_subject = Distrust::subject(intermediate.subject().into());
+        assert_eq!(
+            verify(&pki, &[intermediate_subject]),
+            Err(Error::CaDistrusted)
+        );
+
+        let anchor_subject = Distrust::subject(anchor.subject.as_ref().into());
+        assert_eq!(verify(&pki, &[anchor_subject]), Err(Error::CaDistrusted));
+
+        // An SPKI doesn't match a subject, and vice versa.
+        let mismatched = [
+            Distrust::subject(intermediate.spki.as_slice_less_safe().into()),
+            Distrust::spki(intermediate.subject().into()),
+        ];
+        assert_eq!(verify(&pki, &mismatched), Ok(()));
+    }
+
+    #[test]
+    fn distrust_after() {
+        let mut params = end_entity_params(vec!["example.com".into()]);
+        params.not_before = rcgen::date_time_ymd(1980, 1, 1);
+        let pki = Pki::with_params(issuer_params("Intermediate"), params);
+        let anchor = &pki.anchors[0];
+        let distrust = Distrust::spki(anchor.subject_public_key_info.as_ref().into());
+
+        let before = UnixTime::since_unix_epoch(core::time::Duration::from_secs(315_532_799));
+        assert_eq!(
+            verify(&pki, &[distrust.clone().with_not_before_after(before)]),
+            Err(Error::CaDistrusted)
+        );
+
+        let after = UnixTime::since_unix_epoch(core::time::Duration::from_secs(315_532_800));
+        assert_eq!(
+            verify(&pki, &[distrust.with_not_before_after(after)]),
+            Ok(())
+        );
+    }
+
+    fn verify(pki: &Pki, distrust: &[Distrust<'_>]) -> Result<(), Error> {
+        pki.verify(&VerificationOptions::new().with_distrust(distrust), |_| ())
+    }
+}
@@ -12,7 +12,7 @@
 // ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-use core::ops::Deref;
+use core::ops::{ControlFlow, Deref};
 
 use pki_types::{
     CertificateDer, ServerName, SignatureVerificationAlgorithm, TrustAnchor, UnixTime,
@@ -21,7 +21,7 @@ use pki_types::{
 use crate::crl::RevocationOptions;
 use crate::error::Error;
 use crate::subject_name::{verify_dns_names, verify_ip_address_names};
-use crate::verify_cert::{self, KeyUsage, VerifiedPath};
+use crate::verify_cert::{self, KeyUsage, VerificationOptions, VerifiedPath};
 use crate::{cert, signed_data};
 
 /// An end-entity certificate.
@@ -105,20 +105,95 @@ impl EndEntityCert<'_> {
         trust_anchors: &'p [TrustAnchor<'_>],
         intermediate_certs: &'p [CertificateDer<'p>],
         time: UnixTime,
-        usage: KeyUsage,
+        usage: KeyUsage<'p>,
         revocation: Option<RevocationOptions<'_>>,
         verify_path: Option<&dyn Fn(&VerifiedPath<'_>) -> Result<(), Error>>,
     ) -> Result<VerifiedPath<'p>, Error> {
-        verify_cert::ChainOptions {
-            eku: usage,
+        self.verify_for_usage_with_options(
             supported_sig_algs,
             trust_anchors,
             intermediate_certs,
+            time,
+            usage,
             revocation,
-        }
+            verify_path,
+            &VerificationOptions::default(),
+        )
+    }
+
+    /// Verifies that the end-entity certificate is valid for use against the
+    /// specified Extended Key Usage (EKU), using the given [`VerificationOptions`].
+    ///
+    /// This behaves like [`EndEntityCert::verify_for_usage()`], except that `options` can be
+    /// used to customize how the path is built; e.g. the [`Budget`] limiting how much work is
+    /// spent searching for a valid path.
+    ///
+    /// [`Budget`]: crate::Budget
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_for_usage_with_options<'p>(
+        &'p self,
+        supported_sig_algs: &[&dyn SignatureVerificationAlgorithm],
+        trust_anchors: &'p [TrustAnchor<'_>],
+        intermediate_certs: &'p [CertificateDer<'p>],
+        time: UnixTime,
+        usage: KeyUsage<'p>,
+        revocation: Option<RevocationOptions<'_>>,
+        verify_path: Option<&dyn Fn(&VerifiedPath<'_>) -> Result<(), Error>>,
+        options: &VerificationOptions<'p>,
+    ) -> Result<VerifiedPath<'p>, Error> {
+        verify_cert::ChainOptions::new(
+            supported_sig_algs,
+            trust_anchors,
+            intermediate_certs,
+            usage,
+            revocation,
+            options,
+        )
         .build_chain(self, time, verify_path)
     }
 
+    /// Finds every path from the end-entity certificate to one of the `trust_anchors` that is
+    /// valid for use against the specified Extended Key Usage (EKU).
+    ///
+    /// The arguments have the same meaning as for [`EndEntityCert::verify_for_usage_with_options()`].
+    /// Rather than stopping at the first valid path, `visit` is called for each distinct valid
+    /// path in turn, and path building continues until all options are exhausted or `visit`
+    /// returns [`ControlFlow::Break`].
+    ///
+    /// Returns the number of paths visited. If there is no valid path, the most specific error
+    /// encountered is returned, as it would be by [`EndEntityCert::verify_for_usage()`].
+    ///
+    /// Path building remains subject to the [`Budget`] limits in `options`. If a limit is
+    /// exceeded the corresponding error is returned, even if some paths were already visited,
+    /// since there may be other valid paths that were not.
+    ///
+    /// [`Budget`]: crate::Budget
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_all_paths_for_usage<'p>(
+        &'p self,
+        supported_sig_algs: &[&dyn SignatureVerificationAlgorithm],
+        trust_anchors: &'p [TrustAnchor<'_>],
+        intermediate_certs: &'p [CertificateDer<'p>],
+        time: UnixTime,
+        usage: KeyUsage<'p>,
+        revocation: Option<RevocationOptions<'_>>,
+        options: &VerificationOptions<'p>,
+        visit: &mut dyn FnMut(&VerifiedPath<'_>) -> ControlFlow<()>,
+    ) -> Result<usize, Error> {
+        verify_cert::ChainOptions {
+            ranking: None,
+            ..verify_cert::ChainOptions::new(
+                supported_sig_algs,
+                trust_anchors,
+                intermediate_certs,
+                usage,
+                revocation,
+                options,
+            )
+        }
+        .for_each_chain(self, time, visit)
+    }
+
     /// Verifies that the certificate is valid for the given Subject Name.
     pub fn verify_is_valid_for_subject_name(
         &self,
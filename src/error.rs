@@ -18,6 +18,7 @@ use alloc::string::String;
 use alloc::vec::Vec;
 use core::fmt;
 use core::ops::ControlFlow;
+use core::time::Duration;
 
 #[cfg(feature = "alloc")]
 use pki_types::ServerName;
@@ -33,6 +34,11 @@ pub enum Error {
     /// The encoding of an ASN.1 DER-encoded time is invalid.
     BadDerTime,
 
+    /// A trust anchor or intermediate in the path is distrusted for the end-entity certificate.
+    ///
+    /// See [`crate::Distrust`].
+    CaDistrusted,
+
     /// A CA certificate is being used as an end-entity certificate.
     CaUsedAsEndEntity,
 
@@ -45,9 +51,34 @@ pub enum Error {
         not_after: UnixTime,
     },
 
+    /// The end-entity certificate's validity period is longer than the maximum allowed.
+    ///
+    /// See [`crate::ValidityPolicy::with_max_lifetime()`].
+    CertLifetimeTooLong {
+        /// The notBefore time of the certificate.
+        not_before: UnixTime,
+        /// The notAfter time of the certificate.
+        not_after: UnixTime,
+        /// The maximum validity period allowed.
+        max_lifetime: Duration,
+    },
+
     /// The certificate is not valid for the name it is being validated for.
     CertNotValidForName(InvalidNameContext),
 
+    /// The certificate is not valid at the time it is being validated for, even allowing for
+    /// the configured clock skew.
+    ///
+    /// See [`crate::ValidityPolicy::with_clock_skew()`].
+    CertNotValidWithinSkew {
+        /// The validation time.
+        time: UnixTime,
+        /// The notBefore time of the certificate.
+        not_before: UnixTime,
+        /// The notAfter time of the certificate.
+        not_after: UnixTime,
+    },
+
     /// The certificate is not valid yet; i.e. the time it is being validated
     /// for is earlier than the certificate's notBefore time.
     CertNotValidYet {
@@ -69,12 +100,24 @@ pub enum Error {
         next_update: UnixTime,
     },
 
+    /// A public key in the path, or the key that signed a CRL, uses an elliptic curve that
+    /// the algorithm policy does not allow.
+    ///
+    /// See [`crate::AlgorithmPolicy::with_ec_curves()`].
+    EcCurveNotAllowed,
+
     /// An end-entity certificate is being used as a CA certificate.
     EndEntityUsedAsCa,
 
     /// An X.509 extension is invalid.
     ExtensionValueInvalid,
 
+    /// A certificate or CRL is signed using a hash function that the algorithm policy does
+    /// not allow.
+    ///
+    /// See [`crate::AlgorithmPolicy::with_hash_algorithms()`].
+    HashAlgorithmNotAllowed,
+
     /// The certificate validity period (notBefore, notAfter) is invalid; e.g.
     /// the notAfter time is earlier than the notBefore time.
     InvalidCertValidity,
@@ -99,9 +142,17 @@ pub enum Error {
     /// The CRL signature is invalid for the issuer's public key.
     InvalidCrlSignatureForPublicKey,
 
+    /// The OCSP response signature is invalid for the responder's public key.
+    InvalidOcspSignatureForPublicKey,
+
     /// The signature is invalid for the given public key.
     InvalidSignatureForPublicKey,
 
+    /// An issuer has a KeyUsage bitstring that does not include the "keyCertSign" key usage.
+    ///
+    /// See [`crate::VerificationOptions::with_strict_key_usage()`].
+    IssuerNotCertSigner,
+
     /// A CRL was signed by an issuer that has a KeyUsage bitstring that does not include
     /// the cRLSign key usage bit.
     IssuerNotCrlSigner,
@@ -121,6 +172,11 @@ pub enum Error {
     /// invalid labels.
     MalformedNameConstraint,
 
+    /// An OBJECT IDENTIFIER was malformed.
+    ///
+    /// See [`crate::ObjectIdentifier`].
+    MalformedObjectIdentifier,
+
     /// The maximum number of name constraint comparisons has been reached.
     MaximumNameConstraintComparisonsExceeded,
 
@@ -128,6 +184,8 @@ pub enum Error {
     MaximumPathBuildCallsExceeded,
 
     /// The path search was terminated because it became too deep.
+    ///
+    /// See [`crate::VerificationOptions::with_max_path_depth()`].
     MaximumPathDepthExceeded,
 
     /// The maximum number of signature checks has been reached. Path complexity is too great.
@@ -136,6 +194,31 @@ pub enum Error {
     /// The certificate violates one or more name constraints.
     NameConstraintViolation,
 
+    /// The OCSP response is not signed by the issuer of the certificate, nor by a responder
+    /// it authorized using the id-kp-OCSPSigning extended key usage.
+    OcspResponderNotAuthorized,
+
+    /// The OCSP response is expired; i.e. the verification time is not before the time
+    /// in the nextUpdate field of the response for the certificate.
+    OcspResponseExpired {
+        /// The validation time.
+        time: UnixTime,
+        /// The nextUpdate time of the response.
+        next_update: UnixTime,
+    },
+
+    /// The OCSP response status is not "successful".
+    OcspResponseNotSuccessful,
+
+    /// The OCSP response is not valid yet; i.e. the verification time is earlier than
+    /// the time in the thisUpdate field of the response for the certificate.
+    OcspResponseNotYetValid {
+        /// The validation time.
+        time: UnixTime,
+        /// The thisUpdate time of the response.
+        this_update: UnixTime,
+    },
+
     /// The certificate violates one or more path length constraints.
     PathLenConstraintViolated,
 
@@ -143,13 +226,51 @@ pub enum Error {
     /// being validated.
     RequiredEkuNotFound,
 
+    /// The end-entity certificate has a KeyUsage bitstring that does not include the key usage
+    /// for which it is being validated.
+    ///
+    /// See [`crate::VerificationOptions::with_strict_key_usage()`].
+    RequiredKeyUsageNotFound,
+
+    /// The end-entity certificate requires a stapled OCSP response ("OCSP Must-Staple"), but
+    /// none determined that it is not revoked.
+    ///
+    /// See [`crate::MustStaplePolicy`].
+    RequiredOcspStapleNotFound,
+
+    /// The path is not valid for any of the acceptable certificate policies, and an explicit
+    /// policy is required.
+    ///
+    /// See [`crate::PolicyOptions`].
+    RequiredPolicyNotFound,
+
+    /// An RSA public key in the path, or the key that signed a CRL, has a modulus smaller
+    /// than the algorithm policy allows.
+    ///
+    /// See [`crate::AlgorithmPolicy::with_min_rsa_modulus_bits()`].
+    RsaModulusTooSmall {
+        /// The size of the modulus, in bits.
+        bits: usize,
+        /// The minimum size allowed, in bits.
+        min_bits: usize,
+    },
+
     /// The algorithm in the TBSCertificate "signature" field of a certificate
     /// does not match the algorithm in the signature of the certificate.
     SignatureAlgorithmMismatch,
 
+    /// None of the certificates in the path has a pinned subject public key.
+    ///
+    /// See [`crate::SpkiPins`].
+    SpkiPinMismatch,
+
     /// Trailing data was found while parsing DER-encoded input for the named type.
     TrailingData(DerTypeId),
 
+    /// The issuer of an indirect CRL could not be validated: no certificate for it was found
+    /// that has a valid path to a trust anchor.
+    UnknownCrlIssuer,
+
     /// A valid issuer for the certificate could not be found.
     UnknownIssuer,
 
@@ -177,15 +298,27 @@ pub enum Error {
     /// This error may also be reported if the CRL version field is malformed.
     UnsupportedCrlVersion,
 
-    /// The CRL is an unsupported "delta" CRL.
+    /// The CRL is a "delta" CRL without a CRL number, so it can't be combined with the
+    /// complete CRL it updates.
     UnsupportedDeltaCrl,
 
-    /// The CRL contains unsupported "indirect" entries.
+    /// The CRL contains "indirect" entries that don't identify their certificate issuer with
+    /// a directory name.
     UnsupportedIndirectCrl,
 
     /// The `ServerName` contained an unsupported type of value.
     UnsupportedNameType,
 
+    /// The OCSP response covers the certificate's serial number, but identifies its issuer
+    /// using a hash algorithm none of the hash functions given supports.
+    UnsupportedOcspCertIdHashAlgorithm,
+
+    /// The OCSP response is not an `id-pkix-ocsp-basic` response.
+    UnsupportedOcspResponseType,
+
+    /// The OCSP response is not a v1 basic OCSP response.
+    UnsupportedOcspVersion,
+
     /// The revocation reason is not in the set of supported revocation reasons.
     UnsupportedRevocationReason,
 
@@ -196,6 +329,10 @@ pub enum Error {
     /// signature algorithms given.
     UnsupportedCrlSignatureAlgorithm,
 
+    /// The signature algorithm for a signature over an OCSP response is not in the set of
+    /// supported signature algorithms given.
+    UnsupportedOcspSignatureAlgorithm,
+
     /// The signature algorithm for a signature is not in the set of supported
     /// signature algorithms given.
     UnsupportedSignatureAlgorithm,
@@ -209,6 +346,10 @@ pub enum Error {
     /// verifying an RSA signature with an ECC public key).
     UnsupportedCrlSignatureAlgorithmForPublicKey,
 
+    /// The OCSP response signature's algorithm does not match the algorithm of the
+    /// responder public key it is being validated for.
+    UnsupportedOcspSignatureAlgorithmForPublicKey,
+
     /// The signature's algorithm does not match the algorithm of the public
     /// key it is being validated for. This may be because the public key
     /// algorithm's OID isn't recognized (e.g. DSA), or the public key
@@ -238,16 +379,36 @@ impl Error {
     pub(crate) fn rank(&self) -> u32 {
         match &self {
             // Errors related to certificate validity
-            Self::CertNotValidYet { .. } | Self::CertExpired { .. } => 290,
+            Self::CertNotValidYet { .. }
+            | Self::CertExpired { .. }
+            | Self::CertNotValidWithinSkew { .. } => 290,
+            Self::CertLifetimeTooLong { .. } => 285,
             Self::CertNotValidForName(_) => 280,
-            Self::CertRevoked | Self::UnknownRevocationStatus | Self::CrlExpired { .. } => 270,
-            Self::InvalidCrlSignatureForPublicKey | Self::InvalidSignatureForPublicKey => 260,
+            Self::CaDistrusted => 275,
+            Self::CertRevoked
+            | Self::UnknownRevocationStatus
+            | Self::CrlExpired { .. }
+            | Self::OcspResponseExpired { .. }
+            | Self::OcspResponseNotYetValid { .. } => 270,
+            Self::SpkiPinMismatch => 265,
+            Self::InvalidCrlSignatureForPublicKey
+            | Self::InvalidOcspSignatureForPublicKey
+            | Self::InvalidSignatureForPublicKey => 260,
             Self::SignatureAlgorithmMismatch => 250,
-            Self::RequiredEkuNotFound => 240,
+            Self::RsaModulusTooSmall { .. }
+            | Self::EcCurveNotAllowed
+            | Self::HashAlgorithmNotAllowed => 245,
+            Self::RequiredEkuNotFound
+            | Self::RequiredKeyUsageNotFound
+            | Self::RequiredOcspStapleNotFound => 240,
+            Self::RequiredPolicyNotFound => 235,
             Self::NameConstraintViolation => 230,
             Self::PathLenConstraintViolated => 220,
             Self::CaUsedAsEndEntity | Self::EndEntityUsedAsCa => 210,
-            Self::IssuerNotCrlSigner => 200,
+            Self::IssuerNotCertSigner
+            | Self::IssuerNotCrlSigner
+            | Self::OcspResponderNotAuthorized
+            | Self::UnknownCrlIssuer => 200,
 
             // Errors related to supported features used in an invalid way.
             Self::InvalidCertValidity => 190,
@@ -257,11 +418,16 @@ impl Error {
 
             // Errors related to unsupported features.
             Self::UnsupportedCrlSignatureAlgorithmForPublicKey
+            | Self::UnsupportedOcspSignatureAlgorithmForPublicKey
             | Self::UnsupportedSignatureAlgorithmForPublicKey => 150,
-            Self::UnsupportedCrlSignatureAlgorithm | Self::UnsupportedSignatureAlgorithm => 140,
+            Self::UnsupportedCrlSignatureAlgorithm
+            | Self::UnsupportedOcspCertIdHashAlgorithm
+            | Self::UnsupportedOcspSignatureAlgorithm
+            | Self::UnsupportedSignatureAlgorithm => 140,
             Self::UnsupportedCriticalExtension => 130,
             Self::UnsupportedCertVersion => 130,
-            Self::UnsupportedCrlVersion => 120,
+            Self::UnsupportedCrlVersion | Self::UnsupportedOcspVersion => 120,
+            Self::OcspResponseNotSuccessful | Self::UnsupportedOcspResponseType => 115,
             Self::UnsupportedDeltaCrl => 110,
             Self::UnsupportedIndirectCrl => 100,
             Self::UnsupportedNameType => 95,
@@ -272,6 +438,7 @@ impl Error {
 
             // Errors related to malformed data.
             Self::MalformedDnsIdentifier => 60,
+            Self::MalformedObjectIdentifier => 55,
             Self::MalformedNameConstraint => 50,
             Self::MalformedExtensions | Self::TrailingData(_) => 40,
             Self::ExtensionValueInvalid => 30,
@@ -370,4 +537,9 @@ pub enum DerTypeId {
     RevokedCertificateExtension,
     RevokedCertEntry,
     IssuingDistributionPoint,
+    OcspResponse,
+    BasicOcspResponse,
+    OcspResponseData,
+    OcspSingleResponse,
+    OcspCertId,
 }
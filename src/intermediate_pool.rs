@@ -0,0 +1,206 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+
+use pki_types::CertificateDer;
+
+use crate::cert::Cert;
+use crate::error::Error;
+
+/// An owned set of intermediate certificates, indexed by subject name.
+///
+/// Path building parses the intermediate certificates given as a slice for every verification,
+/// and scans all of them whenever it looks for an issuer. An `IntermediatePool` instead looks up
+/// only the certificates whose subject is the issuer being looked for, and parses only those.
+/// This suits a long-lived set of known intermediates, like the preloaded intermediates of
+/// Firefox, that is used to complete paths when peers do not send every intermediate needed.
+/// Use it with [`VerificationOptions::with_intermediate_pool()`] or
+/// [`VerifierBuilder::with_intermediate_pool()`]; the pool's certificates are then considered
+/// alongside those supplied with each end-entity certificate.
+///
+/// [`VerificationOptions::with_intermediate_pool()`]: crate::VerificationOptions::with_intermediate_pool
+/// [`VerifierBuilder::with_intermediate_pool()`]: crate::VerifierBuilder::with_intermediate_pool
+#[derive(Clone, Default)]
+pub struct IntermediatePool {
+    certs: Vec<CertificateDer<'static>>,
+    by_subject: BTreeMap<Vec<u8>, Vec<usize>>,
+}
+
+impl IntermediatePool {
+    /// Create an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `cert` to the pool.
+    ///
+    /// Returns an error, leaving the pool unchanged, if `cert` fails to parse, and `Ok(false)`
+    /// if `cert` is already in the pool.
+    pub fn add(&mut self, cert: &CertificateDer<'_>) -> Result<bool, Error> {
+        let subject = Cert::from_der(untrusted::Input::from(cert.as_ref()))?.subject;
+        let indices = self
+            .by_subject
+            .entry(subject.as_slice_less_safe().to_vec())
+            .or_default();
+        if indices.iter().any(|&i| self.certs[i] == *cert) {
+            return Ok(false);
+        }
+
+        indices.push(self.certs.len());
+        self.certs.push(cert.clone().into_owned());
+        Ok(true)
+    }
+
+    /// The number of certificates in the pool.
+    pub fn len(&self) -> usize {
+        self.certs.len()
+    }
+
+    /// Returns `true` if there are no certificates in the pool.
+    pub fn is_empty(&self) -> bool {
+        self.certs.is_empty()
+    }
+
+    /// Returns `true` if `cert` is in the pool.
+    pub(crate) fn contains(&self, cert: &Cert<'_>) -> bool {
+        self.issuers(cert.subject)
+            .any(|pooled| pooled.der() == cert.der())
+    }
+
+    /// The certificates in the pool with the subject `subject`, in the order they were added.
+    pub(crate) fn issuers(&self, subject: untrusted::Input<'_>) -> impl Iterator<Item = Cert<'_>> {
+        self.by_subject
+            .get(subject.as_slice_less_safe())
+            .into_iter()
+            .flatten()
+            // The certificates were parsed successfully when added, so this can't fail.
+            .filter_map(|&i| Cert::from_der(untrusted::Input::from(self.certs[i].as_ref())).ok())
+    }
+}
+
+impl fmt::Debug for IntermediatePool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntermediatePool")
+            .field("len", &self.certs.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(test, any(feature = "ring", feature = "aws-lc-rs")))]
+mod tests {
+    use super::*;
+    use crate::end_entity::EndEntityCert;
+    use crate::test_utils::{issuer_params, make_end_entity, make_issuer, RCGEN_SIGNATURE_ALG};
+    use crate::trace::{Candidate, TraceLog};
+    use crate::trust_anchor::anchor_from_trusted_cert;
+    use crate::verifier::{PerCallOptions, Verifier};
+    use crate::verify_cert::{KeyUsage, VerificationOptions};
+    use pki_types::UnixTime;
+    use rcgen::KeyPair;
+    use std::prelude::v1::*;
+
+    #[test]
+    fn add_skips_duplicates() {
+        let first = make_issuer("Intermediate");
+        let rekeyed = make_issuer("Intermediate");
+
+        let mut pool = IntermediatePool::new();
+        assert!(pool.is_empty());
+        assert_eq!(pool.add(first.cert.der()), Ok(true));
+        assert_eq!(pool.add(first.cert.der()), Ok(false));
+        assert_eq!(pool.add(rekeyed.cert.der()), Ok(true));
+        assert_eq!(pool.len(), 2);
+
+        let garbage = CertificateDer::from(&b"garbage"[..]);
+        assert!(pool.add(&garbage).is_err());
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn verify_with_intermediate_pool() {
+        let trust_anchor = make_issuer("Trust Anchor");
+        let anchors = [anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap()];
+        let intermediate_key = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let intermediate = issuer_params("Intermediate")
+            .signed_by(
+                &intermediate_key,
+                &trust_anchor.cert,
+                &trust_anchor.key_pair,
+            )
+            .unwrap();
+        let ee = make_end_entity(&intermediate, &intermediate_key);
+        let ee_cert = EndEntityCert::try_from(ee.cert.der()).unwrap();
+        let time = UnixTime::since_unix_epoch(core::time::Duration::from_secs(0x1fed_f00d));
+
+        // The peer does not supply the intermediate.
+        let verifier = Verifier::builder(
+            crate::ALL_VERIFICATION_ALGS,
+            &anchors,
+            KeyUsage::server_auth(),
+        )
+        .build();
+        assert_eq!(
+            verifier.verify(&ee_cert, &[], time).map(|_| ()),
+            Err(Error::UnknownIssuer)
+        );
+
+        // An intermediate with the same subject name, but the wrong key, is both supplied by the
+        // peer and in the pool.
+        let wrong = make_issuer("Intermediate");
+        let other = make_issuer("Other");
+        let mut pool = IntermediatePool::new();
+        pool.add(wrong.cert.der()).unwrap();
+        pool.add(intermediate.der()).unwrap();
+        pool.add(other.cert.der()).unwrap();
+
+        let verifier = Verifier::builder(
+            crate::ALL_VERIFICATION_ALGS,
+            &anchors,
+            KeyUsage::server_auth(),
+        )
+        .with_intermediate_pool(&pool)
+        .build();
+        let supplied = [wrong.cert.der().clone()];
+        let trace = TraceLog::new();
+        let path = verifier
+            .verify_with(
+                &ee_cert,
+                &supplied,
+                time,
+                PerCallOptions::new().with_trace(&trace),
+            )
+            .unwrap();
+        assert_eq!(
+            path.intermediate_certificates()
+                .map(|cert| cert.der())
+                .collect::<Vec<_>>(),
+            vec![intermediate.der().clone()]
+        );
+
+        // The wrong intermediate was only tried once as the issuer of the end-entity certificate.
+        let wrong_candidate = Candidate::Intermediate(wrong.cert.der().clone());
+        assert_eq!(
+            trace
+                .into_entries()
+                .iter()
+                .filter(|entry| entry.depth == 0 && entry.issuer == wrong_candidate)
+                .count(),
+            1
+        );
+
+        // The pool can also be given in the options of a single verification.
+        let path = ee_cert
+            .verify_for_usage_with_options(
+                crate::ALL_VERIFICATION_ALGS,
+                &anchors,
+                &supplied,
+                time,
+                KeyUsage::server_auth(),
+                None,
+                None,
+                &VerificationOptions::new().with_intermediate_pool(&pool),
+            )
+            .unwrap();
+        assert_eq!(path.intermediate_certificates().count(), 1);
+    }
+}
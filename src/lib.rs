@@ -51,20 +51,35 @@ extern crate alloc;
 #[macro_use]
 mod der;
 
+mod algorithm_policy;
 #[cfg(feature = "aws-lc-rs")]
 mod aws_lc_rs_algs;
 mod cert;
+mod distrust;
 mod end_entity;
 mod error;
+#[cfg(feature = "alloc")]
+mod intermediate_pool;
+mod oid;
+mod pinning;
+#[cfg(feature = "alloc")]
+mod policy;
+mod ranking;
 #[cfg(feature = "ring")]
 mod ring_algs;
 mod rpk_entity;
+#[cfg(feature = "std")]
+mod signature_cache;
 mod signed_data;
 mod subject_name;
 mod time;
+mod trace;
 mod trust_anchor;
+#[cfg(feature = "alloc")]
+mod trust_store;
 
 mod crl;
+mod verifier;
 mod verify_cert;
 mod x509;
 
@@ -72,28 +87,50 @@ mod x509;
 pub(crate) mod test_utils;
 
 pub use {
+    algorithm_policy::{AlgorithmPolicy, EcCurve, HashAlgorithm},
     cert::Cert,
     crl::{
-        BorrowedCertRevocationList, BorrowedRevokedCert, CertRevocationList, ExpirationPolicy,
-        RevocationCheckDepth, RevocationOptions, RevocationOptionsBuilder, RevocationReason,
-        UnknownStatusPolicy,
+        BorrowedCertRevocationList, BorrowedOcspResponse, BorrowedRevokedCert, CertRevocationList,
+        ExpirationPolicy, MustStaplePolicy, OcspHasher, OcspResponse, RevocationCheckDepth,
+        RevocationOptions, RevocationOptionsBuilder, RevocationReason, UnknownStatusPolicy,
     },
+    distrust::Distrust,
     end_entity::EndEntityCert,
     error::{DerTypeId, Error, InvalidNameContext},
+    oid::ObjectIdentifier,
+    pinning::{Sha256Hasher, SpkiPins},
+    ranking::{FewestIntermediates, LatestExpiry, PathRanking, PreferredSignatureAlgorithms},
     rpk_entity::RawPublicKeyEntity,
-    trust_anchor::anchor_from_trusted_cert,
-    verify_cert::KeyUsage,
-    verify_cert::VerifiedPath,
+    trace::{Candidate, PathCheck, PathTrace, TraceStep},
+    trust_anchor::{
+        anchor_from_trusted_cert, constrained_anchor_from_trusted_cert, ConstrainedTrustAnchor,
+        TrustAnchorConstraints,
+    },
+    verifier::{PerCallOptions, Verifier, VerifierBuilder},
+    verify_cert::{
+        Budget, BudgetUsage, EndEntityKeyUsage, IntermediateEkuMode, KeyUsage, ValidityPolicy,
+        VerificationOptions, VerifiedPath,
+    },
 };
 
 #[cfg(feature = "alloc")]
-pub use crl::{OwnedCertRevocationList, OwnedRevokedCert};
+pub use {
+    crl::{OwnedCertRevocationList, OwnedOcspResponse, OwnedRevokedCert},
+    intermediate_pool::IntermediatePool,
+    policy::PolicyOptions,
+    trace::{TraceEntry, TraceLog},
+    trust_store::TrustStore,
+};
+
+#[cfg(feature = "std")]
+pub use signature_cache::SignatureCache;
 
 #[cfg(feature = "ring")]
 /// Signature verification algorithm implementations using the *ring* crypto library.
 pub mod ring {
     pub use super::ring_algs::{
         ECDSA_P256_SHA256, ECDSA_P256_SHA384, ECDSA_P384_SHA256, ECDSA_P384_SHA384, ED25519,
+        OCSP_SHA1, OCSP_SHA256, SHA256,
     };
 
     #[cfg(feature = "alloc")]
@@ -109,10 +146,10 @@ pub mod ring {
 pub mod aws_lc_rs {
     pub use super::aws_lc_rs_algs::{
         ECDSA_P256_SHA256, ECDSA_P256_SHA384, ECDSA_P384_SHA256, ECDSA_P384_SHA384,
-        ECDSA_P521_SHA256, ECDSA_P521_SHA384, ECDSA_P521_SHA512, ED25519,
+        ECDSA_P521_SHA256, ECDSA_P521_SHA384, ECDSA_P521_SHA512, ED25519, OCSP_SHA1, OCSP_SHA256,
         RSA_PKCS1_2048_8192_SHA256, RSA_PKCS1_2048_8192_SHA384, RSA_PKCS1_2048_8192_SHA512,
         RSA_PKCS1_3072_8192_SHA384, RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
-        RSA_PSS_2048_8192_SHA384_LEGACY_KEY, RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+        RSA_PSS_2048_8192_SHA384_LEGACY_KEY, RSA_PSS_2048_8192_SHA512_LEGACY_KEY, SHA256,
     };
 }
 
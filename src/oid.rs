@@ -0,0 +1,233 @@
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::str::FromStr;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use pki_types::Der;
+
+use crate::error::Error;
+
+/// An OBJECT IDENTIFIER, such as an extended key usage purpose.
+///
+/// This holds the DER encoding of the OID's value, without its tag and length, which is how
+/// OIDs are given elsewhere in this crate; e.g. to [`KeyUsage::required()`]. With the `alloc`
+/// feature, an OID can also be parsed from its dotted-decimal text form, such as
+/// `"1.3.6.1.4.1.311.20.2.2"`:
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use webpki::{KeyUsage, ObjectIdentifier};
+///
+/// let oid = "1.3.6.1.4.1.311.20.2.2".parse::<ObjectIdentifier<'_>>().unwrap();
+/// assert_eq!(oid.as_ref(), &[43, 6, 1, 4, 1, 130, 55, 20, 2, 2]);
+/// let usage = KeyUsage::required(oid.as_ref());
+/// # }
+/// ```
+///
+/// [`KeyUsage::required()`]: crate::KeyUsage::required
+#[derive(Clone, PartialEq, Eq)]
+pub struct ObjectIdentifier<'a>(Der<'a>);
+
+impl<'a> ObjectIdentifier<'a> {
+    /// Wrap the DER encoding of an OID's value, without its tag and length.
+    ///
+    /// Returns [`Error::MalformedObjectIdentifier`] if `value` is not a valid encoding.
+    pub fn from_der(value: &'a [u8]) -> Result<Self, Error> {
+        if value.last().map_or(true, |byte| byte & 0x80 != 0) {
+            return Err(Error::MalformedObjectIdentifier);
+        }
+
+        // Every subidentifier must be minimally encoded, and fit in a u64.
+        for bytes in value.split_inclusive(|byte| byte & 0x80 == 0) {
+            if bytes[0] == 0x80 {
+                return Err(Error::MalformedObjectIdentifier);
+            }
+            bytes
+                .iter()
+                .try_fold(0u64, |value, byte| {
+                    Some(value.checked_mul(0x80)? | u64::from(byte & 0x7f))
+                })
+                .ok_or(Error::MalformedObjectIdentifier)?;
+        }
+
+        Ok(Self(Der::from_slice(value)))
+    }
+
+    /// Copy the OID so that it no longer borrows.
+    #[cfg(feature = "alloc")]
+    pub fn to_owned(&self) -> ObjectIdentifier<'static> {
+        ObjectIdentifier(Der::from(self.0.as_ref().to_vec()))
+    }
+
+    /// The arcs of the OID, e.g. 1, 3, 6 and 1 for `1.3.6.1`.
+    fn arcs(&self) -> impl Iterator<Item = u64> + '_ {
+        let mut subidentifiers = self
+            .0
+            .as_ref()
+            .split_inclusive(|byte| byte & 0x80 == 0)
+            .map(|bytes| {
+                bytes
+                    .iter()
+                    .fold(0u64, |value, byte| (value << 7) | u64::from(byte & 0x7f))
+            });
+
+        // The first subidentifier encodes the first two arcs.
+        let (first, second) = match subidentifiers.next().unwrap_or_default() {
+            value @ 0..=39 => (0, value),
+            value @ 40..=79 => (1, value - 40),
+            value => (2, value - 80),
+        };
+
+        [first, second].into_iter().chain(subidentifiers)
+    }
+}
+
+impl AsRef<[u8]> for ObjectIdentifier<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Display for ObjectIdentifier<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, arc) in self.arcs().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{arc}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ObjectIdentifier<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ObjectIdentifier({self})")
+    }
+}
+
+/// Parse an OID from its dotted-decimal text form, e.g. `"2.5.29.37.0"`.
+///
+/// There must be at least two arcs, the first of which is 0, 1 or 2, and the second of which
+/// is less than 40 unless the first is 2. Returns [`Error::MalformedObjectIdentifier`]
+/// otherwise.
+#[cfg(feature = "alloc")]
+impl FromStr for ObjectIdentifier<'static> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut arcs = s.split('.').map(|arc| {
+            // Only plain decimal digits, without redundant leading zeros, are accepted.
+            match arc.as_bytes() {
+                [] | [b'0', _, ..] => Err(Error::MalformedObjectIdentifier),
+                digits if !digits.iter().all(u8::is_ascii_digit) => {
+                    Err(Error::MalformedObjectIdentifier)
+                }
+                _ => arc
+                    .parse::<u64>()
+                    .map_err(|_| Error::MalformedObjectIdentifier),
+            }
+        });
+
+        let first = match (arcs.next(), arcs.next()) {
+            (Some(Ok(first @ 0..=1)), Some(Ok(second @ 0..=39))) => first * 40 + second,
+            (Some(Ok(2)), Some(Ok(second))) => second
+                .checked_add(80)
+                .ok_or(Error::MalformedObjectIdentifier)?,
+            _ => return Err(Error::MalformedObjectIdentifier),
+        };
+
+        let mut der = Vec::new();
+        for subidentifier in [Ok(first)].into_iter().chain(arcs) {
+            encode_subidentifier(subidentifier?, &mut der);
+        }
+
+        Ok(Self(Der::from(der)))
+    }
+}
+
+/// Append the base-128 encoding of `value` to `der`, most significant group first.
+#[cfg(feature = "alloc")]
+fn encode_subidentifier(value: u64, der: &mut Vec<u8>) {
+    let groups = (1..10).take_while(|i| value >> (7 * i) != 0).count();
+    for i in (1..=groups).rev() {
+        der.push(0x80 | ((value >> (7 * i)).to_le_bytes()[0] & 0x7f));
+    }
+    der.push(value.to_le_bytes()[0] & 0x7f);
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use std::prelude::v1::*;
+
+    #[test]
+    fn from_str() {
+        for (text, der) in [
+            ("1.3.6.1.5.5.7.3.1", &oid!(1, 3, 6, 1, 5, 5, 7, 3, 1)[..]),
+            ("2.5.29.37.0", &oid!(2, 5, 29, 37, 0)),
+            ("1.0.18013.5.1.2", &[40, 129, 140, 93, 5, 1, 2]),
+            (
+                "1.3.6.1.4.1.311.20.2.2",
+                &[43, 6, 1, 4, 1, 130, 55, 20, 2, 2],
+            ),
+            ("0.0", &[0]),
+            ("2.999.3", &[136, 55, 3]),
+            (
+                "1.2.18446744073709551615",
+                &[42, 129, 255, 255, 255, 255, 255, 255, 255, 255, 127],
+            ),
+        ] {
+            let oid = text.parse::<ObjectIdentifier<'_>>().unwrap();
+            assert_eq!(oid.as_ref(), der, "{text}");
+            assert_eq!(oid, ObjectIdentifier::from_der(der).unwrap());
+            assert_eq!(oid.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn from_str_malformed() {
+        for text in [
+            "",
+            "1",
+            "1.",
+            ".1.2",
+            "1..2",
+            "3.1",
+            "1.40",
+            "0.40.1",
+            "1.02",
+            "1.+2",
+            "1.2.a",
+            "1.2 ",
+            "1.2.18446744073709551616",
+            "2.18446744073709551615",
+        ] {
+            assert_eq!(
+                text.parse::<ObjectIdentifier<'_>>(),
+                Err(Error::MalformedObjectIdentifier),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn from_der_malformed() {
+        for der in [
+            &[][..],
+            &[0x2b, 0x86],
+            &[0x2b, 0x80, 0x01],
+            &[0x80, 0x01],
+            &[
+                0x82, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00,
+            ],
+        ] {
+            assert_eq!(
+                ObjectIdentifier::from_der(der),
+                Err(Error::MalformedObjectIdentifier),
+                "{der:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use core::fmt::Debug;
+use core::mem::size_of;
+
+/// A SHA-256 implementation, used to compute the digests of subject public key infos.
+///
+/// Implementations are provided by the crypto providers this crate supports, e.g.
+/// `webpki::ring::SHA256` and `webpki::aws_lc_rs::SHA256`.
+pub trait Sha256Hasher: Debug + Send + Sync {
+    /// Compute the SHA-256 digest of the concatenation of `data`.
+    fn hash(&self, data: &[&[u8]]) -> [u8; 32];
+}
+
+/// A set of pinned subject public keys, identified by the SHA-256 digests of their SPKIs.
+///
+/// As for HTTP Public Key Pinning (RFC 7469), each pin is the SHA-256 digest of the DER
+/// encoding of a `SubjectPublicKeyInfo`, including its tag and length. Use with
+/// [`VerificationOptions::with_spki_pins()`]. A path is only valid if the end-entity
+/// certificate, one of the intermediate certificates or the trust anchor has a pinned key;
+/// otherwise it is rejected with [`Error::SpkiPinMismatch`].
+///
+/// [`VerificationOptions::with_spki_pins()`]: crate::VerificationOptions::with_spki_pins
+/// [`Error::SpkiPinMismatch`]: crate::Error::SpkiPinMismatch
+#[derive(Clone, Copy, Debug)]
+pub struct SpkiPins<'a> {
+    hasher: &'a dyn Sha256Hasher,
+    pins: &'a [[u8; 32]],
+}
+
+impl<'a> SpkiPins<'a> {
+    /// Create a new set of `pins`, whose digests are computed using `hasher`.
+    pub fn new(hasher: &'a dyn Sha256Hasher, pins: &'a [[u8; 32]]) -> Self {
+        Self { hasher, pins }
+    }
+
+    /// Compute the pin for `spki`, using `hasher`.
+    ///
+    /// Like [`TrustAnchor::subject_public_key_info`], `spki` is the value of the
+    /// `subjectPublicKeyInfo` SEQUENCE, without its tag and length.
+    ///
+    /// [`TrustAnchor::subject_public_key_info`]: pki_types::TrustAnchor::subject_public_key_info
+    pub fn pin(hasher: &dyn Sha256Hasher, spki: &[u8]) -> [u8; 32] {
+        // The SEQUENCE tag, followed by the length in short or long form.
+        let len = spki.len().to_be_bytes();
+        let len = &len[len.iter().take_while(|&&byte| byte == 0).count()..];
+        let mut header = [0u8; 2 + size_of::<usize>()];
+        header[0] = 0x30;
+        let header_len = match len {
+            [] => 2,
+            [byte] if *byte < 0x80 => {
+                header[1] = *byte;
+                2
+            }
+            _ => {
+                header[1] = 0x80 | u8::try_from(len.len()).unwrap_or_default();
+                header[2..2 + len.len()].copy_from_slice(len);
+                2 + len.len()
+            }
+        };
+
+        hasher.hash(&[&header[..header_len], spki])
+    }
+
+    /// Returns `true` if `spki` is pinned.
+    pub(crate) fn contains(&self, spki: untrusted::Input<'_>) -> bool {
+        self.pins
+            .contains(&Self::pin(self.hasher, spki.as_slice_less_safe()))
+    }
+}
+
+#[cfg(all(test, feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+mod tests {
+    use super::*;
+    use crate::end_entity::EndEntityCert;
+    use crate::error::Error;
+    use crate::test_utils::{
+        issuer_params, make_end_entity, make_issuer, verify_server_auth, Pki, RCGEN_SIGNATURE_ALG,
+    };
+    use crate::trust_anchor::anchor_from_trusted_cert;
+    use crate::verify_cert::VerificationOptions;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use pki_types::CertificateDer;
+    use rcgen::KeyPair;
+    use std::prelude::v1::*;
+
+    #[cfg(feature = "aws-lc-rs")]
+    const HASHER: &dyn Sha256Hasher = crate::aws_lc_rs::SHA256;
+    #[cfg(all(feature = "ring", not(feature = "aws-lc-rs")))]
+    const HASHER: &dyn Sha256Hasher = crate::ring::SHA256;
+
+    #[test]
+    fn pin_encodes_spki_header() {
+        // The digest covers the SEQUENCE tag and length, whatever the length of the value.
+        for (len, header) in [
+            (0, &[0x30, 0x00][..]),
+            (0x7f, &[0x30, 0x7f]),
+            (0x80, &[0x30, 0x81, 0x80]),
+            (0x100, &[0x30, 0x82, 0x01, 0x00]),
+            (0x1_0000, &[0x30, 0x83, 0x01, 0x00, 0x00]),
+        ] {
+            let spki = vec![0xaa; len];
+            let der = [header, &spki].concat();
+            assert_eq!(SpkiPins::pin(HASHER, &spki), HASHER.hash(&[&der]));
+        }
+    }
+
+    #[test]
+    fn spki_pins() {
+        let pki = Pki::new();
+        let verify = |pins: &[[u8; 32]]| {
+            pki.verify(
+                &VerificationOptions::new().with_spki_pins(SpkiPins::new(HASHER, pins)),
+                |_| (),
+            )
+        };
+
+        let spki_pin = |der: &CertificateDer<'_>| {
+            let cert = crate::cert::Cert::from_der(untrusted::Input::from(der)).unwrap();
+            SpkiPins::pin(HASHER, cert.spki.as_slice_less_safe())
+        };
+        let anchor_pin = SpkiPins::pin(HASHER, &pki.anchors[0].subject_public_key_info);
+        let intermediate_pin = spki_pin(&pki.intermediates[0]);
+        let ee_pin = spki_pin(pki.ee.cert.der());
+
+        // Any certificate in the path may be pinned.
+        assert_eq!(verify(&[anchor_pin]), Ok(()));
+        assert_eq!(verify(&[intermediate_pin]), Ok(()));
+        assert_eq!(verify(&[ee_pin]), Ok(()));
+        assert_eq!(verify(&[[0; 32], intermediate_pin]), Ok(()));
+
+        assert_eq!(verify(&[[0; 32]]), Err(Error::SpkiPinMismatch));
+        assert_eq!(verify(&[]), Err(Error::SpkiPinMismatch));
+    }
+
+    #[test]
+    fn spkis_hashed_once() {
+        // Several trust anchors share a subject name; only the last one issued the intermediate.
+        let anchors = (0..3)
+            .map(|_| make_issuer("Trust Anchor"))
+            .collect::<Vec<_>>();
+        let trust_anchor = anchors.last().unwrap();
+        let key_pair = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let intermediate = issuer_params("Intermediate")
+            .signed_by(&key_pair, &trust_anchor.cert, &trust_anchor.key_pair)
+            .unwrap();
+        let ee = make_end_entity(&intermediate, &key_pair);
+        let ee_cert = EndEntityCert::try_from(ee.cert.der()).unwrap();
+        let intermediates = [intermediate.der().clone()];
+        let anchors = anchors
+            .iter()
+            .map(|anchor| anchor_from_trusted_cert(anchor.cert.der()).unwrap())
+            .collect::<Vec<_>>();
+
+        let cert = crate::cert::Cert::from_der(untrusted::Input::from(intermediate.der())).unwrap();
+        let pins = [SpkiPins::pin(HASHER, cert.spki.as_slice_less_safe())];
+        let hasher = CountingHasher::default();
+        verify_server_auth(
+            &ee_cert,
+            &anchors,
+            &intermediates,
+            None,
+            &VerificationOptions::new().with_spki_pins(SpkiPins::new(&hasher, &pins)),
+        )
+        .unwrap();
+
+        // The end-entity and intermediate certificates are hashed once, rather than once for
+        // each trust anchor tried; the trust anchors needn't be, as the intermediate is pinned.
+        assert_eq!(hasher.0.load(Ordering::Relaxed), 2);
+    }
+
+    #[derive(Debug, Default)]
+    struct CountingHasher(AtomicUsize);
+
+    impl Sha256Hasher for CountingHasher {
+        fn hash(&self, data: &[&[u8]]) -> [u8; 32] {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            HASHER.hash(data)
+        }
+    }
+}
@@ -0,0 +1,687 @@
+use alloc::vec::Vec;
+
+use crate::cert::Cert;
+use crate::der::{self, Tag, CONTEXT_SPECIFIC};
+use crate::error::Error;
+use crate::public_values_eq;
+
+/// Options for certificate policy processing, as described in RFC 5280 section 6.1.
+///
+/// Use with [`VerificationOptions::with_policies()`]. Without these options, the certificate
+/// policy extensions are not processed, and any path containing a certificate with one of them
+/// marked critical is rejected with [`Error::UnsupportedCriticalExtension`].
+///
+/// The policies in force for a valid path are available from [`VerifiedPath::policies()`].
+///
+/// [`VerificationOptions::with_policies()`]: crate::VerificationOptions::with_policies
+/// [`VerifiedPath::policies()`]: crate::VerifiedPath::policies
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PolicyOptions<'a> {
+    acceptable: Option<&'a [&'a [u8]]>,
+    require_explicit_policy: bool,
+    inhibit_policy_mapping: bool,
+    inhibit_any_policy: bool,
+}
+
+impl<'a> PolicyOptions<'a> {
+    /// The value of the anyPolicy OBJECT IDENTIFIER (2.5.29.32.0).
+    pub const ANY_POLICY: &'static [u8] = &oid!(2, 5, 29, 32, 0);
+
+    /// Create a new [`PolicyOptions`] accepting any policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only accept paths valid for one of `policies` (the `user-initial-policy-set`).
+    ///
+    /// Each policy is the value of an OBJECT IDENTIFIER in bytes. Unless an explicit policy is
+    /// required (see [`PolicyOptions::with_require_explicit_policy()`]), paths that are not valid
+    /// for any policy are still accepted, and yield no policies in force.
+    pub fn with_acceptable_policies(mut self, policies: &'a [&'a [u8]]) -> Self {
+        self.acceptable = Some(policies);
+        self
+    }
+
+    /// Require the path to be valid for at least one acceptable policy (the
+    /// `initial-explicit-policy` input).
+    pub fn with_require_explicit_policy(mut self, require: bool) -> Self {
+        self.require_explicit_policy = require;
+        self
+    }
+
+    /// Disallow policy mapping (the `initial-policy-mapping-inhibit` input).
+    pub fn with_inhibit_policy_mapping(mut self, inhibit: bool) -> Self {
+        self.inhibit_policy_mapping = inhibit;
+        self
+    }
+
+    /// Don't treat anyPolicy in a certificate as matching every policy (the
+    /// `initial-any-policy-inhibit` input).
+    pub fn with_inhibit_any_policy(mut self, inhibit: bool) -> Self {
+        self.inhibit_any_policy = inhibit;
+        self
+    }
+
+    /// Process the certificate policies of `certs`, which are given in order from the one issued
+    /// by the trust anchor to the end-entity certificate.
+    ///
+    /// Returns the authority-constrained policy set intersected with the acceptable policies,
+    /// which contains [`PolicyOptions::ANY_POLICY`] if the path is valid for any policy.
+    ///
+    /// This keeps the `valid_policy_tree` of RFC 5280 as a graph instead, as suggested by
+    /// RFC 9618, so that its size is linear in the size of the certificates.
+    pub(crate) fn process<'p>(&self, certs: &[&Cert<'p>]) -> Result<Vec<&'p [u8]>, Error> {
+        let n = certs.len();
+        let initial = |set: bool| match set {
+            true => 0,
+            false => n + 1,
+        };
+        let mut explicit_policy = initial(self.require_explicit_policy);
+        let mut inhibit_any_policy = initial(self.inhibit_any_policy);
+        let mut policy_mapping = initial(self.inhibit_policy_mapping);
+
+        let mut levels = Vec::with_capacity(n + 1);
+        levels.push(Vec::from([Node {
+            policy: Self::ANY_POLICY,
+            expected: Vec::from([Self::ANY_POLICY]),
+            parents: Vec::new(),
+        }]));
+
+        for (i, cert) in certs.iter().enumerate().map(|(i, cert)| (i + 1, cert)) {
+            if let Some(err) = &cert.invalid_policy_extension {
+                return Err(err.clone());
+            }
+
+            let self_issued = public_values_eq(cert.subject, cert.issuer);
+            let previous = &levels[i - 1];
+
+            // RFC 5280 section 6.1.3 (d) and (e).
+            let mut level = Vec::new();
+            if let (Some(policies), false) = (cert.certificate_policies, previous.is_empty()) {
+                let policies = policy_identifiers(policies)?;
+                for &policy in policies.iter().filter(|&&p| p != Self::ANY_POLICY) {
+                    let mut parents = matching(previous, |node| node.expected.contains(&policy));
+                    if parents.is_empty() {
+                        parents = matching(previous, |node| node.policy == Self::ANY_POLICY);
+                    }
+
+                    if !parents.is_empty() {
+                        level.push(Node::new(policy, parents));
+                    }
+                }
+
+                if policies.contains(&Self::ANY_POLICY)
+                    && (inhibit_any_policy > 0 || (i < n && self_issued))
+                {
+                    for (parent, node) in previous.iter().enumerate() {
+                        for &policy in &node.expected {
+                            match level.iter_mut().find(|child| child.policy == policy) {
+                                Some(child) if child.parents.contains(&parent) => {}
+                                Some(child) => child.parents.push(parent),
+                                None => level.push(Node::new(policy, Vec::from([parent]))),
+                            }
+                        }
+                    }
+                }
+            }
+
+            // RFC 5280 section 6.1.3 (f).
+            if explicit_policy == 0 && level.is_empty() {
+                return Err(Error::RequiredPolicyNotFound);
+            }
+
+            let constraints = untrusted::read_all_optional(
+                cert.policy_constraints,
+                Error::BadDer,
+                policy_constraints,
+            )?;
+
+            if i < n {
+                // RFC 5280 section 6.1.4 (a) and (b).
+                if let Some(mappings) = cert.policy_mappings {
+                    let mappings = policy_mappings(mappings)?;
+                    for (j, &(issuer_policy, _)) in mappings.iter().enumerate() {
+                        if mappings[..j].iter().any(|&(p, _)| p == issuer_policy) {
+                            continue;
+                        }
+
+                        let subject_policies = mappings
+                            .iter()
+                            .filter(|&&(p, _)| p == issuer_policy)
+                            .map(|&(_, subject_policy)| subject_policy)
+                            .collect::<Vec<_>>();
+
+                        if policy_mapping == 0 {
+                            level.retain(|node| node.policy != issuer_policy);
+                        } else if let Some(node) =
+                            level.iter_mut().find(|node| node.policy == issuer_policy)
+                        {
+                            node.expected = subject_policies;
+                        } else if level.iter().any(|node| node.policy == Self::ANY_POLICY) {
+                            level.push(Node {
+                                policy: issuer_policy,
+                                expected: subject_policies,
+                                parents: matching(previous, |node| node.policy == Self::ANY_POLICY),
+                            });
+                        }
+                    }
+                }
+
+                // RFC 5280 section 6.1.4 (h), (i) and (j).
+                if !self_issued {
+                    for counter in [
+                        &mut explicit_policy,
+                        &mut policy_mapping,
+                        &mut inhibit_any_policy,
+                    ] {
+                        *counter = counter.saturating_sub(1);
+                    }
+                }
+
+                let (require, inhibit) = constraints.unwrap_or_default();
+                for (counter, skip_certs) in [
+                    (&mut explicit_policy, require),
+                    (&mut policy_mapping, inhibit),
+                ] {
+                    if let Some(skip_certs) = skip_certs {
+                        *counter = (*counter).min(skip_certs);
+                    }
+                }
+
+                if let Some(value) = cert.inhibit_any_policy {
+                    inhibit_any_policy = inhibit_any_policy.min(skip_certs(value)?);
+                }
+            } else {
+                // RFC 5280 section 6.1.5 (a) and (b).
+                explicit_policy = explicit_policy.saturating_sub(1);
+                if let Some((Some(0), _)) = constraints {
+                    explicit_policy = 0;
+                }
+            }
+
+            levels.push(level);
+        }
+
+        // RFC 5280 section 6.1.5 (g). Rather than modifying the graph, nodes outside the
+        // acceptable policies are excluded when following the paths from the root.
+        let acceptable = |node: &Node<'_>, parent: &Node<'_>| {
+            let acceptable = match self.acceptable {
+                Some(acceptable) => acceptable,
+                None => return true,
+            };
+
+            // Only nodes in the valid_policy_node_set are subject to the acceptable policies;
+            // their descendants are implied by them.
+            match node.policy == Self::ANY_POLICY {
+                true => !acceptable.is_empty(),
+                false => parent.policy != Self::ANY_POLICY || acceptable.contains(&node.policy),
+            }
+        };
+
+        // Whether each node is reachable from the root through acceptable nodes.
+        let mut rooted = Vec::with_capacity(levels.len());
+        rooted.push(Vec::from([true]));
+        for (depth, level) in levels.iter().enumerate().skip(1) {
+            let reachable = level
+                .iter()
+                .map(|node| {
+                    node.parents.iter().any(|&parent| {
+                        rooted[depth - 1][parent] && acceptable(node, &levels[depth - 1][parent])
+                    })
+                })
+                .collect::<Vec<_>>();
+            rooted.push(reachable);
+        }
+
+        // Whether each rooted node also reaches a leaf of the graph.
+        let mut alive = rooted.clone();
+        for depth in (1..levels.len()).rev() {
+            let mut parents_alive = alloc::vec![false; levels[depth - 1].len()];
+            for (node, &node_alive) in levels[depth].iter().zip(&alive[depth]) {
+                if !node_alive {
+                    continue;
+                }
+
+                for &parent in &node.parents {
+                    if rooted[depth - 1][parent] && acceptable(node, &levels[depth - 1][parent]) {
+                        parents_alive[parent] = true;
+                    }
+                }
+            }
+
+            for (alive, parent_alive) in alive[depth - 1].iter_mut().zip(parents_alive) {
+                *alive &= parent_alive;
+            }
+        }
+
+        // The valid_policy_node_set: nodes other than anyPolicy whose parent is anyPolicy.
+        let mut policies = Vec::new();
+        for (depth, level) in levels.iter().enumerate().skip(1) {
+            for (node, &node_alive) in level.iter().zip(&alive[depth]) {
+                let in_set = node.policy != Self::ANY_POLICY
+                    && node
+                        .parents
+                        .iter()
+                        .any(|&parent| levels[depth - 1][parent].policy == Self::ANY_POLICY);
+                if node_alive && in_set && !policies.contains(&node.policy) {
+                    policies.push(node.policy);
+                }
+            }
+        }
+
+        let leaves = &levels[n];
+        if leaves
+            .iter()
+            .zip(&alive[n])
+            .any(|(node, &alive)| alive && node.policy == Self::ANY_POLICY)
+        {
+            policies.push(Self::ANY_POLICY);
+        }
+
+        // RFC 5280 section 6.1.5 (g) (iii), and the check at the end of section 6.1.5.
+        let valid = alive[n].iter().any(|&alive| alive);
+        if explicit_policy == 0 && !valid {
+            return Err(Error::RequiredPolicyNotFound);
+        }
+
+        Ok(policies)
+    }
+}
+
+/// A node in the `valid_policy_tree` (here, a graph).
+struct Node<'p> {
+    /// The `valid_policy`.
+    policy: &'p [u8],
+    /// The `expected_policy_set`.
+    expected: Vec<&'p [u8]>,
+    /// The indexes of the parent nodes, in the previous level of the graph.
+    parents: Vec<usize>,
+}
+
+impl<'p> Node<'p> {
+    fn new(policy: &'p [u8], parents: Vec<usize>) -> Self {
+        Self {
+            policy,
+            expected: Vec::from([policy]),
+            parents,
+        }
+    }
+}
+
+/// The indexes of the nodes in `level` matching `predicate`.
+fn matching(level: &[Node<'_>], predicate: impl Fn(&Node<'_>) -> bool) -> Vec<usize> {
+    level
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| predicate(node))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+// https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.4
+fn policy_identifiers(value: untrusted::Input<'_>) -> Result<Vec<&[u8]>, Error> {
+    //   certificatePolicies ::= SEQUENCE SIZE (1..MAX) OF PolicyInformation
+    //
+    //   PolicyInformation ::= SEQUENCE {
+    //        policyIdentifier   CertPolicyId,
+    //        policyQualifiers   SEQUENCE SIZE (1..MAX) OF
+    //                                PolicyQualifierInfo OPTIONAL }
+    value.read_all(Error::BadDer, |reader| {
+        let mut policies = Vec::new();
+        loop {
+            der::nested(reader, Tag::Sequence, Error::BadDer, |info| {
+                let policy = der::expect_tag(info, Tag::OID)?.as_slice_less_safe();
+                // Policy qualifiers are informational only.
+                info.skip_to_end();
+
+                // A policy must not appear more than once, but there's no harm in tolerating it.
+                if !policies.contains(&policy) {
+                    policies.push(policy);
+                }
+                Ok(())
+            })?;
+
+            if reader.at_end() {
+                break Ok(policies);
+            }
+        }
+    })
+}
+
+// https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.5
+fn policy_mappings(value: untrusted::Input<'_>) -> Result<Vec<(&[u8], &[u8])>, Error> {
+    //   PolicyMappings ::= SEQUENCE SIZE (1..MAX) OF SEQUENCE {
+    //        issuerDomainPolicy      CertPolicyId,
+    //        subjectDomainPolicy     CertPolicyId }
+    value.read_all(Error::BadDer, |reader| {
+        let mut mappings = Vec::new();
+        loop {
+            let mapping = der::nested(reader, Tag::Sequence, Error::BadDer, |mapping| {
+                let issuer_policy = der::expect_tag(mapping, Tag::OID)?.as_slice_less_safe();
+                let subject_policy = der::expect_tag(mapping, Tag::OID)?.as_slice_less_safe();
+                Ok((issuer_policy, subject_policy))
+            })?;
+
+            // RFC 5280 section 6.1.4 (a): policies may not be mapped to or from anyPolicy.
+            if mapping.0 == PolicyOptions::ANY_POLICY || mapping.1 == PolicyOptions::ANY_POLICY {
+                return Err(Error::ExtensionValueInvalid);
+            }
+            mappings.push(mapping);
+
+            if reader.at_end() {
+                break Ok(mappings);
+            }
+        }
+    })
+}
+
+// https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.11
+fn policy_constraints(
+    input: Option<&mut untrusted::Reader<'_>>,
+) -> Result<Option<(Option<usize>, Option<usize>)>, Error> {
+    //   PolicyConstraints ::= SEQUENCE {
+    //        requireExplicitPolicy           [0] SkipCerts OPTIONAL,
+    //        inhibitPolicyMapping            [1] SkipCerts OPTIONAL }
+    const REQUIRE_EXPLICIT_POLICY_TAG: u8 = CONTEXT_SPECIFIC;
+    const INHIBIT_POLICY_MAPPING_TAG: u8 = CONTEXT_SPECIFIC | 1;
+
+    let input = match input {
+        Some(input) => input,
+        None => return Ok(None),
+    };
+
+    let mut require = None;
+    let mut inhibit = None;
+    while !input.at_end() {
+        match der::read_tag_and_get_value(input)? {
+            (REQUIRE_EXPLICIT_POLICY_TAG, value) if require.is_none() && inhibit.is_none() => {
+                require = Some(skip_certs(value)?);
+            }
+            (INHIBIT_POLICY_MAPPING_TAG, value) if inhibit.is_none() => {
+                inhibit = Some(skip_certs(value)?);
+            }
+            _ => return Err(Error::BadDer),
+        }
+    }
+
+    Ok(Some((require, inhibit)))
+}
+
+/// Parse the contents of a `SkipCerts ::= INTEGER (0..MAX)`, saturating at `usize::MAX`.
+fn skip_certs(value: untrusted::Input<'_>) -> Result<usize, Error> {
+    match value.as_slice_less_safe() {
+        // Empty or negative.
+        [] => Err(Error::BadDer),
+        [first, ..] if first & 0x80 == 0x80 => Err(Error::BadDer),
+        // Unnecessary leading zero.
+        [0, second, ..] if second & 0x80 == 0 => Err(Error::BadDer),
+        bytes => Ok(bytes.iter().fold(0usize, |value, &byte| {
+            value.saturating_mul(256).saturating_add(usize::from(byte))
+        })),
+    }
+}
+
+#[cfg(all(test, any(feature = "ring", feature = "aws-lc-rs")))]
+mod tests {
+    use super::*;
+    use crate::der::asn1_wrap;
+    use crate::test_utils::{end_entity_params, issuer_params, Pki};
+    use crate::verify_cert::VerificationOptions;
+    use rcgen::CustomExtension;
+    use std::prelude::v1::*;
+
+    const P1: &[u8] = &oid!(1, 2, 3, 1);
+    const P2: &[u8] = &oid!(1, 2, 3, 2);
+    const ANY: &[u8] = PolicyOptions::ANY_POLICY;
+
+    #[test]
+    fn policies_in_force() {
+        let intermediate = [certificate_policies(&[P1, P2])];
+        let ee = [certificate_policies(&[P1])];
+        assert_eq!(
+            verify(&intermediate, &ee, PolicyOptions::new()),
+            Ok(vec![P1])
+        );
+
+        // P2 isn't valid for the end-entity, so there's no acceptable policy in force.
+        let acceptable = &[P2];
+        let options = PolicyOptions::new().with_acceptable_policies(acceptable);
+        assert_eq!(verify(&intermediate, &ee, options), Ok(vec![]));
+        assert_eq!(
+            verify(
+                &intermediate,
+                &ee,
+                options.with_require_explicit_policy(true)
+            ),
+            Err(Error::RequiredPolicyNotFound)
+        );
+
+        // A certificate without policies leaves none in force.
+        let options = PolicyOptions::new().with_require_explicit_policy(true);
+        assert_eq!(
+            verify(&intermediate, &[], options),
+            Err(Error::RequiredPolicyNotFound)
+        );
+    }
+
+    #[test]
+    fn any_policy() {
+        let intermediate = [certificate_policies(&[ANY])];
+        let options = PolicyOptions::new().with_require_explicit_policy(true);
+        assert_eq!(
+            verify(&intermediate, &[certificate_policies(&[P1])], options),
+            Ok(vec![P1])
+        );
+        assert_eq!(
+            verify(&intermediate, &[certificate_policies(&[ANY])], options),
+            Ok(vec![ANY])
+        );
+
+        // The acceptable policies don't restrict a path valid for any policy.
+        let acceptable = &[P2];
+        assert_eq!(
+            verify(
+                &intermediate,
+                &[certificate_policies(&[ANY])],
+                options.with_acceptable_policies(acceptable)
+            ),
+            Ok(vec![ANY])
+        );
+
+        // ... unless anyPolicy is inhibited, by the caller or by an issuer.
+        assert_eq!(
+            verify(
+                &intermediate,
+                &[certificate_policies(&[P1])],
+                options.with_inhibit_any_policy(true)
+            ),
+            Err(Error::RequiredPolicyNotFound)
+        );
+        let intermediate = [certificate_policies(&[ANY]), inhibit_any_policy(0)];
+        assert_eq!(
+            verify(&intermediate, &[certificate_policies(&[ANY])], options),
+            Err(Error::RequiredPolicyNotFound)
+        );
+    }
+
+    #[test]
+    fn policy_mapping() {
+        let intermediate = [certificate_policies(&[P1]), policy_mappings(&[(P1, P2)])];
+        let ee = [certificate_policies(&[P2])];
+        let options = PolicyOptions::new().with_require_explicit_policy(true);
+        assert_eq!(verify(&intermediate, &ee, options), Ok(vec![P1]));
+        assert_eq!(
+            verify(
+                &intermediate,
+                &ee,
+                options.with_inhibit_policy_mapping(true)
+            ),
+            Err(Error::RequiredPolicyNotFound)
+        );
+
+        let intermediate = [certificate_policies(&[P1]), policy_mappings(&[(P1, ANY)])];
+        assert_eq!(
+            verify(&intermediate, &ee, options),
+            Err(Error::ExtensionValueInvalid)
+        );
+    }
+
+    #[test]
+    fn policy_constraints() {
+        // Explicit policy is required for the path, from the certificate after the intermediate.
+        let intermediate = [certificate_policies(&[P1]), require_explicit_policy(0)];
+        assert_eq!(
+            verify(&intermediate, &[], PolicyOptions::new()),
+            Err(Error::RequiredPolicyNotFound)
+        );
+        assert_eq!(
+            verify(
+                &intermediate,
+                &[certificate_policies(&[P1])],
+                PolicyOptions::new()
+            ),
+            Ok(vec![P1])
+        );
+
+        // ... unless more certificates may be skipped than remain in the path.
+        let intermediate = [certificate_policies(&[P1]), require_explicit_policy(2)];
+        assert_eq!(verify(&intermediate, &[], PolicyOptions::new()), Ok(vec![]));
+    }
+
+    #[test]
+    fn critical_policies_require_processing() {
+        let mut policies = certificate_policies(&[P1]);
+        policies.set_criticality(true);
+        let intermediate = [policies];
+        let ee = [certificate_policies(&[P1])];
+
+        let pki = make_pki(&intermediate, &ee);
+        assert_eq!(
+            verified_policies(&pki, &VerificationOptions::new()).map(|_| ()),
+            Err(Error::UnsupportedCriticalExtension)
+        );
+        assert_eq!(
+            verify(&intermediate, &ee, PolicyOptions::new()),
+            Ok(vec![P1])
+        );
+    }
+
+    #[test]
+    fn malformed_policies_require_processing() {
+        for intermediate in [
+            [extension(32, asn1_wrap(Tag::OID, P1))],
+            [extension(54, asn1_wrap(Tag::Sequence, &[]))],
+        ] {
+            let ee = [certificate_policies(&[P1])];
+
+            // Without policy processing, a malformed non-critical extension is ignored ...
+            let pki = make_pki(&intermediate, &ee);
+            assert_eq!(
+                verified_policies(&pki, &VerificationOptions::new()),
+                Ok(vec![])
+            );
+
+            // ... but with it, the path is rejected.
+            assert_eq!(
+                verify(&intermediate, &ee, PolicyOptions::new()),
+                Err(Error::BadDer)
+            );
+        }
+
+        // Likewise for a repeated extension.
+        let intermediate = [certificate_policies(&[P1]), certificate_policies(&[P2])];
+        let ee = [certificate_policies(&[P1])];
+        let pki = make_pki(&intermediate, &ee);
+        assert_eq!(
+            verified_policies(&pki, &VerificationOptions::new()),
+            Ok(vec![])
+        );
+        assert_eq!(
+            verify(&intermediate, &ee, PolicyOptions::new()),
+            Err(Error::ExtensionValueInvalid)
+        );
+
+        // A critical one is always rejected.
+        let mut policies = extension(32, asn1_wrap(Tag::OID, P1));
+        policies.set_criticality(true);
+        let pki = make_pki(&[policies], &ee);
+        assert_eq!(
+            verified_policies(&pki, &VerificationOptions::new()).map(|_| ()),
+            Err(Error::BadDer)
+        );
+    }
+
+    /// Verify a path of an end-entity issued by an intermediate with the given extensions,
+    /// returning the policies in force.
+    fn verify(
+        intermediate: &[CustomExtension],
+        ee: &[CustomExtension],
+        options: PolicyOptions<'_>,
+    ) -> Result<Vec<&'static [u8]>, Error> {
+        let pki = make_pki(intermediate, ee);
+        let policies = verified_policies(&pki, &VerificationOptions::new().with_policies(options))?;
+
+        // Map the policies back to the test constants, so that they can outlive `pki`.
+        Ok(policies
+            .into_iter()
+            .map(|policy| *[P1, P2, ANY].iter().find(|p| **p == policy).unwrap())
+            .collect())
+    }
+
+    /// A PKI whose intermediate and end-entity certificates have the given extensions.
+    fn make_pki(intermediate: &[CustomExtension], ee: &[CustomExtension]) -> Pki {
+        let mut intermediate_params = issuer_params("Intermediate");
+        intermediate_params.custom_extensions = intermediate.to_vec();
+        let mut ee_params = end_entity_params(vec!["example.com".into()]);
+        ee_params.custom_extensions = ee.to_vec();
+        Pki::with_params(intermediate_params, ee_params)
+    }
+
+    fn verified_policies(
+        pki: &Pki,
+        options: &VerificationOptions<'_>,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        pki.verify(options, |path| {
+            path.policies()
+                .unwrap_or_default()
+                .iter()
+                .map(|policy| policy.to_vec())
+                .collect()
+        })
+    }
+
+    fn certificate_policies(policies: &[&[u8]]) -> CustomExtension {
+        let infos = policies
+            .iter()
+            .flat_map(|policy| asn1_wrap(Tag::Sequence, &asn1_wrap(Tag::OID, policy)))
+            .collect::<Vec<_>>();
+        extension(32, asn1_wrap(Tag::Sequence, &infos))
+    }
+
+    fn policy_mappings(mappings: &[(&[u8], &[u8])]) -> CustomExtension {
+        let mappings = mappings
+            .iter()
+            .flat_map(|(issuer_policy, subject_policy)| {
+                let mut mapping = asn1_wrap(Tag::OID, issuer_policy);
+                mapping.extend(asn1_wrap(Tag::OID, subject_policy));
+                asn1_wrap(Tag::Sequence, &mapping)
+            })
+            .collect::<Vec<_>>();
+        extension(33, asn1_wrap(Tag::Sequence, &mappings))
+    }
+
+    fn require_explicit_policy(skip_certs: u8) -> CustomExtension {
+        extension(
+            36,
+            asn1_wrap(Tag::Sequence, &[CONTEXT_SPECIFIC, 1, skip_certs]),
+        )
+    }
+
+    fn inhibit_any_policy(skip_certs: u8) -> CustomExtension {
+        extension(54, asn1_wrap(Tag::Integer, &[skip_certs]))
+    }
+
+    fn extension(id: u64, content: Vec<u8>) -> CustomExtension {
+        CustomExtension::from_oid_content(&[2, 5, 29, id], content)
+    }
+}
@@ -0,0 +1,282 @@
+use core::cmp::Ordering;
+use core::fmt::Debug;
+
+use pki_types::{SignatureVerificationAlgorithm, UnixTime};
+
+use crate::cert::Cert;
+use crate::signed_data;
+use crate::verify_cert::VerifiedPath;
+
+/// A policy expressing a preference between valid paths.
+///
+/// By default, path building yields the first valid path it finds, which depends on the order
+/// of the trust anchors and intermediate certificates provided. When a ranking is configured
+/// using [`VerificationOptions::with_ranking()`], path building instead considers every valid
+/// path and yields the best one according to [`PathRanking::compare()`]. Of paths that compare
+/// as equal, the one found first is kept.
+///
+/// [`VerificationOptions::with_ranking()`]: crate::VerificationOptions::with_ranking
+pub trait PathRanking: Debug + Send + Sync {
+    /// Compare two valid paths, yielding [`Ordering::Greater`] if `a` is preferred over `b`.
+    fn compare(&self, a: &VerifiedPath<'_>, b: &VerifiedPath<'_>) -> Ordering;
+}
+
+/// Prefers paths with fewer intermediate certificates.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FewestIntermediates;
+
+impl PathRanking for FewestIntermediates {
+    fn compare(&self, a: &VerifiedPath<'_>, b: &VerifiedPath<'_>) -> Ordering {
+        let len = |path: &VerifiedPath<'_>| path.intermediate_certificates().count();
+        len(b).cmp(&len(a))
+    }
+}
+
+/// Prefers paths that remain valid for longer.
+///
+/// A path is only valid until the earliest `notAfter` of the end-entity and intermediate
+/// certificates it contains, so the path for which this is latest is preferred. For example,
+/// this prefers a path through a newer root over a path through an expiring cross-signed
+/// certificate.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LatestExpiry;
+
+impl PathRanking for LatestExpiry {
+    fn compare(&self, a: &VerifiedPath<'_>, b: &VerifiedPath<'_>) -> Ordering {
+        fn expiry(path: &VerifiedPath<'_>) -> Option<UnixTime> {
+            // The validity of every certificate has already been checked, so parsing can't fail.
+            certs(path)
+                .map(|cert| cert.not_after().ok())
+                .min()
+                .flatten()
+        }
+
+        expiry(a).cmp(&expiry(b))
+    }
+}
+
+/// Prefers paths using more preferred signature algorithms.
+///
+/// `algorithms` lists signature algorithms from most to least preferred. Each path is ranked by
+/// the least preferred algorithm used for any of the signatures in it, and algorithms that are
+/// not listed rank below all of those that are.
+#[derive(Clone, Copy, Debug)]
+pub struct PreferredSignatureAlgorithms<'a> {
+    algorithms: &'a [&'a dyn SignatureVerificationAlgorithm],
+}
+
+impl<'a> PreferredSignatureAlgorithms<'a> {
+    /// Create a new ranking preferring `algorithms`, given from most to least preferred.
+    pub fn new(algorithms: &'a [&'a dyn SignatureVerificationAlgorithm]) -> Self {
+        Self { algorithms }
+    }
+
+    /// The rank of the least preferred algorithm in `path`, where lower is better.
+    fn rank(&self, path: &VerifiedPath<'_>) -> usize {
+        let issuer_spkis = path
+            .intermediate_certificates()
+            .map(|cert| cert.spki)
+            .chain([untrusted::Input::from(
+                path.anchor().subject_public_key_info.as_ref(),
+            )]);
+
+        certs(path)
+            .zip(issuer_spkis)
+            .map(|(cert, issuer_spki)| {
+                self.algorithms
+                    .iter()
+                    .position(|alg| {
+                        signed_data::uses_algorithm(*alg, issuer_spki, &cert.signed_data)
+                    })
+                    .unwrap_or(self.algorithms.len())
+            })
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl PathRanking for PreferredSignatureAlgorithms<'_> {
+    fn compare(&self, a: &VerifiedPath<'_>, b: &VerifiedPath<'_>) -> Ordering {
+        self.rank(b).cmp(&self.rank(a))
+    }
+}
+
+/// The end-entity and intermediate certificates in `path`, in order.
+fn certs<'a>(path: &'a VerifiedPath<'_>) -> impl Iterator<Item = &'a Cert<'a>> {
+    [&**path.end_entity()]
+        .into_iter()
+        .chain(path.intermediate_certificates())
+}
+
+#[cfg(all(test, feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+mod tests {
+    use super::*;
+    use crate::end_entity::EndEntityCert;
+    use crate::test_utils::{
+        issuer_params, make_end_entity, make_issuer, verify_server_auth, RCGEN_SIGNATURE_ALG,
+    };
+    use crate::trust_anchor::anchor_from_trusted_cert;
+    use crate::verify_cert::VerificationOptions;
+    use pki_types::{alg_id, CertificateDer, TrustAnchor};
+    use rcgen::{CertifiedKey, KeyPair};
+    use std::prelude::v1::*;
+
+    /// Builds a PKI where the end-entity's issuer B has three valid paths to the trust anchor:
+    ///
+    /// * EE -> B (issued by A) -> A (expires 2030) -> TA
+    /// * EE -> B (issued by C) -> C (expires 2040, P-384 key) -> TA
+    /// * EE -> B (issued by TA, expires 2035) -> TA
+    ///
+    /// The intermediates are given in that order.
+    struct Pki {
+        anchors: Vec<TrustAnchor<'static>>,
+        intermediates: Vec<CertificateDer<'static>>,
+        ee: CertificateDer<'static>,
+    }
+
+    impl Pki {
+        fn new() -> Self {
+            let trust_anchor = make_issuer("Trust Anchor");
+            let anchors = vec![anchor_from_trusted_cert(trust_anchor.cert.der())
+                .unwrap()
+                .to_owned()];
+
+            let mut cross_signers = Vec::new();
+            for (name, not_after, alg) in [
+                ("Intermediate A", 2030, RCGEN_SIGNATURE_ALG),
+                ("Intermediate C", 2040, &rcgen::PKCS_ECDSA_P384_SHA384),
+            ] {
+                let key_pair = KeyPair::generate_for(alg).unwrap();
+                let mut params = issuer_params(name);
+                params.not_after = rcgen::date_time_ymd(not_after, 1, 1);
+                let cert = params
+                    .signed_by(&key_pair, &trust_anchor.cert, &trust_anchor.key_pair)
+                    .unwrap();
+                cross_signers.push(CertifiedKey { cert, key_pair });
+            }
+
+            let b_key = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+            let mut intermediates = Vec::new();
+            for issuer in &cross_signers {
+                let b = issuer_params("Intermediate B")
+                    .signed_by(&b_key, &issuer.cert, &issuer.key_pair)
+                    .unwrap();
+                intermediates.push(b.der().clone());
+                intermediates.push(issuer.cert.der().clone());
+            }
+
+            let mut params = issuer_params("Intermediate B");
+            params.not_after = rcgen::date_time_ymd(2035, 1, 1);
+            let b = params
+                .signed_by(&b_key, &trust_anchor.cert, &trust_anchor.key_pair)
+                .unwrap();
+            intermediates.push(b.der().clone());
+
+            let ee = make_end_entity(&b, &b_key).cert.der().clone();
+            Self {
+                anchors,
+                intermediates,
+                ee,
+            }
+        }
+
+        /// Verify the end-entity certificate, returning the serial numbers of the intermediates
+        /// in the chosen path.
+        fn verify(
+            &self,
+            intermediates: &[CertificateDer<'_>],
+            ranking: Option<&dyn PathRanking>,
+        ) -> Vec<Vec<u8>> {
+            let ee = EndEntityCert::try_from(&self.ee).unwrap();
+            let options = match ranking {
+                Some(ranking) => VerificationOptions::new().with_ranking(ranking),
+                None => VerificationOptions::new(),
+            };
+            let path =
+                verify_server_auth(&ee, &self.anchors, intermediates, None, &options).unwrap();
+
+            path.intermediate_certificates()
+                .map(|cert| cert.serial().to_vec())
+                .collect()
+        }
+
+        /// The serial numbers of `self.intermediates` at the given indexes.
+        fn serials(&self, indexes: &[usize]) -> Vec<Vec<u8>> {
+            indexes
+                .iter()
+                .map(|&i| {
+                    Cert::from_der(untrusted::Input::from(&self.intermediates[i]))
+                        .unwrap()
+                        .serial()
+                        .to_vec()
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn first_path_without_ranking() {
+        let pki = Pki::new();
+        assert_eq!(pki.verify(&pki.intermediates, None), pki.serials(&[0, 1]));
+    }
+
+    #[test]
+    fn fewest_intermediates() {
+        let pki = Pki::new();
+        assert_eq!(
+            pki.verify(&pki.intermediates, Some(&FewestIntermediates)),
+            pki.serials(&[4])
+        );
+    }
+
+    #[test]
+    fn latest_expiry() {
+        let pki = Pki::new();
+        assert_eq!(
+            pki.verify(&pki.intermediates, Some(&LatestExpiry)),
+            pki.serials(&[2, 3])
+        );
+    }
+
+    #[test]
+    fn preferred_signature_algorithms() {
+        let find = |signature_alg_id, public_key_alg_id| {
+            *crate::ALL_VERIFICATION_ALGS
+                .iter()
+                .find(|alg| {
+                    alg.signature_alg_id() == signature_alg_id
+                        && alg.public_key_alg_id() == public_key_alg_id
+                })
+                .unwrap()
+        };
+        let p256 = find(alg_id::ECDSA_SHA256, alg_id::ECDSA_P256);
+        let p384 = find(alg_id::ECDSA_SHA384, alg_id::ECDSA_P384);
+
+        // Only consider the paths through A and C, starting with C.
+        let pki = Pki::new();
+        let intermediates = [
+            pki.intermediates[2].clone(),
+            pki.intermediates[3].clone(),
+            pki.intermediates[0].clone(),
+            pki.intermediates[1].clone(),
+        ];
+        assert_eq!(pki.verify(&intermediates, None), pki.serials(&[2, 3]));
+
+        // B is signed by C using P-384, so the path through A is preferred when P-256 is.
+        let algorithms = &[p256, p384];
+        let ranking = PreferredSignatureAlgorithms::new(algorithms);
+        assert_eq!(
+            pki.verify(&intermediates, Some(&ranking)),
+            pki.serials(&[0, 1])
+        );
+
+        // ... while the path through C remains when P-384 is preferred, since all paths
+        // also use P-256 signatures.
+        let algorithms = &[p384, p256];
+        let ranking = PreferredSignatureAlgorithms::new(algorithms);
+        assert_eq!(
+            pki.verify(&intermediates, Some(&ranking)),
+            pki.serials(&[2, 3])
+        );
+    }
+}
@@ -13,7 +13,10 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 use pki_types::{alg_id, AlgorithmIdentifier, InvalidSignature, SignatureVerificationAlgorithm};
-use ring::signature;
+use ring::{digest, signature};
+
+use crate::crl::OcspHasher;
+use crate::pinning::Sha256Hasher;
 
 /// A `SignatureVerificationAlgorithm` implemented using *ring*.
 #[derive(Debug)]
@@ -147,6 +150,60 @@ pub static ED25519: &dyn SignatureVerificationAlgorithm = &RingAlgorithm {
     verification_alg: &signature::ED25519,
 };
 
+/// A `Sha256Hasher` implemented using *ring*.
+#[derive(Debug)]
+struct RingSha256;
+
+impl Sha256Hasher for RingSha256 {
+    fn hash(&self, data: &[&[u8]]) -> [u8; 32] {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        for part in data {
+            ctx.update(part);
+        }
+
+        let mut out = [0u8; 32];
+        out.copy_from_slice(ctx.finish().as_ref());
+        out
+    }
+}
+
+/// SHA-256, for computing SPKI pins; see [`SpkiPins`](crate::SpkiPins).
+pub static SHA256: &dyn Sha256Hasher = &RingSha256;
+
+/// An `OcspHasher` implemented using *ring*.
+#[derive(Debug)]
+struct RingOcspHasher {
+    oid: &'static [u8],
+    algorithm: &'static digest::Algorithm,
+}
+
+impl OcspHasher for RingOcspHasher {
+    fn oid(&self) -> &[u8] {
+        self.oid
+    }
+
+    fn digest_matches(&self, data: &[&[u8]], digest: &[u8]) -> bool {
+        let mut ctx = digest::Context::new(self.algorithm);
+        for part in data {
+            ctx.update(part);
+        }
+
+        ctx.finish().as_ref() == digest
+    }
+}
+
+/// SHA-1, for matching OCSP responses to certificates; see [`OcspHasher`](crate::OcspHasher).
+pub static OCSP_SHA1: &dyn OcspHasher = &RingOcspHasher {
+    oid: &[0x2b, 0x0e, 0x03, 0x02, 0x1a],
+    algorithm: &digest::SHA1_FOR_LEGACY_USE_ONLY,
+};
+
+/// SHA-256, for matching OCSP responses to certificates; see [`OcspHasher`](crate::OcspHasher).
+pub static OCSP_SHA256: &dyn OcspHasher = &RingOcspHasher {
+    oid: &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01],
+    algorithm: &digest::SHA256,
+};
+
 #[cfg(test)]
 #[path = "."]
 mod tests {
@@ -0,0 +1,379 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::sync::Mutex;
+
+use core::fmt;
+
+use pki_types::SignatureVerificationAlgorithm;
+
+use crate::error::Error;
+use crate::pinning::Sha256Hasher;
+use crate::signed_data::{self, SignedData};
+use crate::verify_cert::Budget;
+
+/// A bounded cache of successful signature verifications, which can be shared between
+/// verifications and threads.
+///
+/// Servers and clients often see the same intermediate certificates over and over again. With
+/// a cache set using [`VerificationOptions::with_signature_cache()`], the signature of each
+/// certificate in a candidate path is only verified if the same signature, over the same data,
+/// was not already successfully verified using the same issuer public key and the same
+/// [`SignatureVerificationAlgorithm`]. Entries are identified by the SHA-256 digest of all of
+/// these, computed using the `hasher` given to [`SignatureCache::new()`], where the algorithm is
+/// identified by its address. Failed verifications are not cached.
+///
+/// Signatures found in the cache do not consume the signature [`Budget`]; they are counted in
+/// [`BudgetUsage::cached_signatures`] instead. Once the cache holds `capacity` entries, the
+/// oldest entry is evicted for each new one.
+///
+/// [`VerificationOptions::with_signature_cache()`]: crate::VerificationOptions::with_signature_cache
+/// [`BudgetUsage::cached_signatures`]: crate::BudgetUsage::cached_signatures
+pub struct SignatureCache<'a> {
+    hasher: &'a dyn Sha256Hasher,
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl<'a> SignatureCache<'a> {
+    /// Create an empty cache holding up to `capacity` entries, identified by digests computed
+    /// using `hasher`.
+    pub fn new(hasher: &'a dyn Sha256Hasher, capacity: usize) -> Self {
+        Self {
+            hasher,
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// The maximum number of entries in the cache.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.entries().order.len()
+    }
+
+    /// Returns `true` if there are no entries in the cache.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all entries from the cache.
+    pub fn clear(&self) {
+        *self.entries() = Entries::default();
+    }
+
+    /// Like [`signed_data::verify_signed_data()`], but skip verifying a signature that was
+    /// already successfully verified.
+    pub(crate) fn verify_signed_data(
+        &self,
+        supported_algorithms: &[&dyn SignatureVerificationAlgorithm],
+        spki_value: untrusted::Input<'_>,
+        signed_data: &SignedData<'_>,
+        budget: &mut Budget,
+    ) -> Result<(), Error> {
+        // The signature may have been verified using an algorithm that is not supported now, or
+        // that shares its identifiers with a supported one but has different key constraints.
+        let cached = supported_algorithms
+            .iter()
+            .filter(|alg| signed_data::uses_algorithm(**alg, spki_value, signed_data))
+            .any(|alg| {
+                let key = self.key(*alg, spki_value, signed_data);
+                self.entries().set.contains(&key)
+            });
+        if cached {
+            budget.record_cached_signature();
+            return Ok(());
+        }
+
+        let alg = signed_data::verify_signed_data_returning_alg(
+            supported_algorithms,
+            spki_value,
+            signed_data,
+            budget,
+        )?;
+        self.insert(self.key(alg, spki_value, signed_data));
+        Ok(())
+    }
+
+    fn insert(&self, key: [u8; 32]) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries();
+        if !entries.set.insert(key) {
+            return;
+        }
+
+        entries.order.push_back(key);
+        while entries.order.len() > self.capacity {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.set.remove(&oldest);
+            }
+        }
+    }
+
+    fn key(
+        &self,
+        alg: &dyn SignatureVerificationAlgorithm,
+        spki_value: untrusted::Input<'_>,
+        signed_data: &SignedData<'_>,
+    ) -> [u8; 32] {
+        // Algorithms with the same identifiers may differ in the keys they accept, so the
+        // algorithm itself is identified by its address.
+        #[allow(clippy::as_conversions)] // the address is only used as an identity.
+        let alg = (alg as *const dyn SignatureVerificationAlgorithm).cast::<()>() as usize;
+        let alg = alg.to_be_bytes();
+        let parts = [
+            untrusted::Input::from(&alg),
+            spki_value,
+            signed_data.algorithm,
+            signed_data.data,
+            signed_data.signature,
+        ];
+
+        // Each part is preceded by its length, so that the boundaries between them are fixed.
+        let lengths = parts.map(|part| part.len().to_be_bytes());
+        let mut data = [&[][..]; 10];
+        for (i, (part, len)) in parts.iter().zip(&lengths).enumerate() {
+            data[2 * i] = len;
+            data[2 * i + 1] = part.as_slice_less_safe();
+        }
+
+        self.hasher.hash(&data)
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, Entries> {
+        // The entries are consistent even if another thread panicked while holding the lock.
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl fmt::Debug for SignatureCache<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignatureCache")
+            .field("hasher", &self.hasher)
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[derive(Default)]
+struct Entries {
+    set: BTreeSet<[u8; 32]>,
+    /// The keys in `set`, oldest first.
+    order: VecDeque<[u8; 32]>,
+}
+
+#[cfg(all(test, any(feature = "ring", feature = "aws-lc-rs")))]
+mod tests {
+    use super::*;
+    use crate::end_entity::EndEntityCert;
+    use crate::test_utils::{
+        issuer_params, make_end_entity, make_issuer, test_time, verify_server_auth,
+        RCGEN_SIGNATURE_ALG,
+    };
+    use crate::trust_anchor::anchor_from_trusted_cert;
+    use crate::verify_cert::{BudgetUsage, KeyUsage, VerificationOptions};
+    use pki_types::{CertificateDer, TrustAnchor};
+    use rcgen::KeyPair;
+    use std::prelude::v1::*;
+
+    #[cfg(feature = "aws-lc-rs")]
+    use crate::aws_lc_rs as provider;
+    #[cfg(all(feature = "ring", not(feature = "aws-lc-rs")))]
+    use crate::ring as provider;
+
+    const HASHER: &dyn Sha256Hasher = provider::SHA256;
+
+    #[test]
+    fn signature_cache() {
+        let trust_anchor = make_issuer("Trust Anchor");
+        let anchors = [anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap()];
+        let intermediate_key = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let intermediate = issuer_params("Intermediate")
+            .signed_by(
+                &intermediate_key,
+                &trust_anchor.cert,
+                &trust_anchor.key_pair,
+            )
+            .unwrap();
+        let intermediates = [intermediate.der().clone()];
+        let ee = make_end_entity(&intermediate, &intermediate_key);
+        let ee_cert = EndEntityCert::try_from(ee.cert.der()).unwrap();
+
+        let cache = SignatureCache::new(HASHER, 16);
+        assert!(cache.is_empty());
+        let options = VerificationOptions::new().with_signature_cache(&cache);
+
+        let usage = verify(&ee_cert, &anchors, &intermediates, &options).unwrap();
+        assert_eq!((usage.signatures, usage.cached_signatures), (2, 0));
+        assert_eq!(cache.len(), 2);
+
+        // Cached signatures do not consume the budget.
+        let options = options.with_budget(Budget::default().with_signatures(0));
+        let usage = verify(&ee_cert, &anchors, &intermediates, &options).unwrap();
+        assert_eq!((usage.signatures, usage.cached_signatures), (0, 2));
+
+        // The cache only records successful verifications.
+        let wrong_key = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let wrong = issuer_params("Intermediate")
+            .signed_by(&wrong_key, &trust_anchor.cert, &trust_anchor.key_pair)
+            .unwrap();
+        let wrong_intermediates = [wrong.der().clone()];
+        let options = VerificationOptions::new().with_signature_cache(&cache);
+        for _ in 0..2 {
+            assert_eq!(
+                verify(&ee_cert, &anchors, &wrong_intermediates, &options),
+                Err(Error::InvalidSignatureForPublicKey)
+            );
+        }
+        // Only the trust anchor's signature on the wrong intermediate was added.
+        assert_eq!(cache.len(), 3);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn signature_cache_is_bounded() {
+        let trust_anchor = make_issuer("Trust Anchor");
+        let anchors = [anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap()];
+        let cache = SignatureCache::new(HASHER, 1);
+        let options = VerificationOptions::new().with_signature_cache(&cache);
+
+        let first = make_end_entity(&trust_anchor.cert, &trust_anchor.key_pair);
+        let first = EndEntityCert::try_from(first.cert.der()).unwrap();
+        let second = make_end_entity(&trust_anchor.cert, &trust_anchor.key_pair);
+        let second = EndEntityCert::try_from(second.cert.der()).unwrap();
+
+        verify(&first, &anchors, &[], &options).unwrap();
+        verify(&second, &anchors, &[], &options).unwrap();
+        assert_eq!(cache.len(), 1);
+
+        // The first signature was evicted, and is verified again.
+        let usage = verify(&first, &anchors, &[], &options).unwrap();
+        assert_eq!((usage.signatures, usage.cached_signatures), (1, 0));
+        let usage = verify(&first, &anchors, &[], &options).unwrap();
+        assert_eq!((usage.signatures, usage.cached_signatures), (0, 1));
+
+        // Nothing is cached with a capacity of zero.
+        let cache = SignatureCache::new(HASHER, 0);
+        let options = VerificationOptions::new().with_signature_cache(&cache);
+        verify(&first, &anchors, &[], &options).unwrap();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn signature_cache_respects_supported_algorithms() {
+        let trust_anchor = make_issuer("Trust Anchor");
+        let anchors = [anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap()];
+        let ee = make_end_entity(&trust_anchor.cert, &trust_anchor.key_pair);
+        let ee_cert = EndEntityCert::try_from(ee.cert.der()).unwrap();
+        let time = test_time();
+
+        let cache = SignatureCache::new(HASHER, 16);
+        let options = VerificationOptions::new().with_signature_cache(&cache);
+        verify(&ee_cert, &anchors, &[], &options).unwrap();
+        assert_eq!(cache.len(), 1);
+
+        // Without support for ECDSA with SHA-256, the cached signature is not accepted.
+        let algs = crate::ALL_VERIFICATION_ALGS
+            .iter()
+            .copied()
+            .filter(|alg| alg.signature_alg_id() != pki_types::alg_id::ECDSA_SHA256)
+            .collect::<Vec<_>>();
+        let verify_with = |options: &VerificationOptions<'_>| {
+            ee_cert
+                .verify_for_usage_with_options(
+                    &algs,
+                    &anchors,
+                    &[],
+                    time,
+                    KeyUsage::server_auth(),
+                    None,
+                    None,
+                    options,
+                )
+                .map(|_| ())
+        };
+        assert_eq!(
+            verify_with(&options),
+            Err(Error::UnsupportedSignatureAlgorithm)
+        );
+        assert_eq!(
+            verify_with(&options),
+            verify_with(&VerificationOptions::new())
+        );
+    }
+
+    #[test]
+    fn signature_cache_respects_key_constraints() {
+        // Both algorithms have the same identifiers, but only the first accepts a 2048-bit key.
+        let key_pair =
+            KeyPair::generate_rsa_for(&rcgen::PKCS_RSA_SHA384, rcgen::RsaKeySize::_2048).unwrap();
+        let trust_anchor = issuer_params("Trust Anchor")
+            .self_signed(&key_pair)
+            .unwrap();
+        let anchors = [anchor_from_trusted_cert(trust_anchor.der()).unwrap()];
+        let ee = make_end_entity(&trust_anchor, &key_pair);
+        let ee_cert = EndEntityCert::try_from(ee.cert.der()).unwrap();
+        let time = test_time();
+
+        let cache = SignatureCache::new(HASHER, 16);
+        let verify_with = |alg: &dyn SignatureVerificationAlgorithm,
+                           options: &VerificationOptions<'_>| {
+            ee_cert
+                .verify_for_usage_with_options(
+                    &[alg],
+                    &anchors,
+                    &[],
+                    time,
+                    KeyUsage::server_auth(),
+                    None,
+                    None,
+                    options,
+                )
+                .map(|path| path.budget_usage().cached_signatures)
+        };
+
+        let options = VerificationOptions::new().with_signature_cache(&cache);
+        assert_eq!(
+            verify_with(provider::RSA_PKCS1_2048_8192_SHA384, &options),
+            Ok(0)
+        );
+        assert_eq!(cache.len(), 1);
+        assert_eq!(
+            verify_with(provider::RSA_PKCS1_2048_8192_SHA384, &options),
+            Ok(1)
+        );
+
+        // The cached signature is not accepted for an algorithm requiring a larger key.
+        assert_eq!(
+            verify_with(provider::RSA_PKCS1_3072_8192_SHA384, &options),
+            Err(Error::InvalidSignatureForPublicKey)
+        );
+        assert_eq!(
+            verify_with(provider::RSA_PKCS1_3072_8192_SHA384, &options),
+            verify_with(
+                provider::RSA_PKCS1_3072_8192_SHA384,
+                &VerificationOptions::new()
+            )
+        );
+    }
+
+    fn verify(
+        ee_cert: &EndEntityCert<'_>,
+        anchors: &[TrustAnchor<'_>],
+        intermediates: &[CertificateDer<'_>],
+        options: &VerificationOptions<'_>,
+    ) -> Result<BudgetUsage, Error> {
+        verify_server_auth(ee_cert, anchors, intermediates, None, options)
+            .map(|path| path.budget_usage())
+    }
+}
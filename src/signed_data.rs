@@ -60,7 +60,7 @@ impl OwnedSignedData {
 /// X.509 certificates and related items that are signed are almost always
 /// encoded in the format "tbs||signatureAlgorithm||signature". This structure
 /// captures this pattern.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct SignedData<'a> {
     /// The signed data. This would be `tbsCertificate` in the case of an X.509
     /// certificate, `tbsResponseData` in the case of an OCSP response, `tbsCertList`
@@ -159,6 +159,18 @@ pub(crate) fn verify_signed_data(
     signed_data: &SignedData<'_>,
     budget: &mut Budget,
 ) -> Result<(), Error> {
+    verify_signed_data_returning_alg(supported_algorithms, spki_value, signed_data, budget)
+        .map(|_| ())
+}
+
+/// Like [`verify_signed_data()`], but return the algorithm from `supported_algorithms` that
+/// verified the signature.
+pub(crate) fn verify_signed_data_returning_alg<'a>(
+    supported_algorithms: &[&'a dyn SignatureVerificationAlgorithm],
+    spki_value: untrusted::Input<'_>,
+    signed_data: &SignedData<'_>,
+    budget: &mut Budget,
+) -> Result<&'a dyn SignatureVerificationAlgorithm, Error> {
     budget.consume_signature()?;
 
     // We need to verify the signature in `signed_data` using the public key
@@ -206,7 +218,7 @@ pub(crate) fn verify_signed_data(
                 continue;
             }
             result => {
-                return result;
+                return result.map(|()| *supported_alg);
             }
         }
     }
@@ -218,6 +230,35 @@ pub(crate) fn verify_signed_data(
     }
 }
 
+/// Returns true if `signed_data` claims to be signed with `signature_alg`, using the public key
+/// in the DER-encoded SubjectPublicKeyInfo `spki_value`.
+///
+/// Only the algorithm identifiers are compared; the signature itself is not verified.
+pub(crate) fn uses_algorithm(
+    signature_alg: &dyn SignatureVerificationAlgorithm,
+    spki_value: untrusted::Input<'_>,
+    signed_data: &SignedData<'_>,
+) -> bool {
+    let supported_alg_id = signature_alg.signature_alg_id();
+    let supported_alg = untrusted::Input::from(supported_alg_id.as_ref())
+        .read_all(Error::BadDer, NormalizedAlgorithmIdentifier::from_der);
+    let signed_alg = signed_data
+        .algorithm
+        .read_all(Error::BadDer, NormalizedAlgorithmIdentifier::from_der);
+    match (signed_alg, supported_alg) {
+        (Ok(signed), Ok(supported)) if signed == supported => {}
+        _ => return false,
+    }
+
+    match der::read_all::<SubjectPublicKeyInfo<'_>>(spki_value) {
+        Ok(spki) => {
+            signature_alg.public_key_alg_id().as_ref()
+                == spki.algorithm_id_value.as_slice_less_safe()
+        }
+        Err(_) => false,
+    }
+}
+
 pub(crate) fn verify_signature(
     signature_alg: &dyn SignatureVerificationAlgorithm,
     spki_value: untrusted::Input<'_>,
@@ -239,8 +280,8 @@ pub(crate) fn verify_signature(
 }
 
 pub(crate) struct SubjectPublicKeyInfo<'a> {
-    algorithm_id_value: untrusted::Input<'a>,
-    key_value: untrusted::Input<'a>,
+    pub(crate) algorithm_id_value: untrusted::Input<'a>,
+    pub(crate) key_value: untrusted::Input<'a>,
 }
 
 impl<'a> FromDer<'a> for SubjectPublicKeyInfo<'a> {
@@ -46,5 +46,143 @@ pub(crate) fn end_entity_params(subject_alt_names: Vec<String>) -> rcgen::Certif
     ee_params
 }
 
+/// Sign `message` with `key_pair`, which must be a P-256 key as generated for
+/// [`RCGEN_SIGNATURE_ALG`], yielding an ASN.1 encoded ECDSA signature.
+///
+/// This allows building signed structures that rcgen can't produce, like OCSP responses.
+#[cfg(any(feature = "ring", feature = "aws-lc-rs"))]
+pub(crate) fn sign(key_pair: &rcgen::KeyPair, message: &[u8]) -> Vec<u8> {
+    #[cfg(feature = "aws-lc-rs")]
+    use aws_lc_rs::{rand, signature};
+    #[cfg(all(feature = "ring", not(feature = "aws-lc-rs")))]
+    use ring::{rand, signature};
+
+    let pkcs8 = key_pair.serialize_der();
+    let alg = &signature::ECDSA_P256_SHA256_ASN1_SIGNING;
+    #[cfg(feature = "aws-lc-rs")]
+    let signer = signature::EcdsaKeyPair::from_pkcs8(alg, &pkcs8).unwrap();
+    #[cfg(all(feature = "ring", not(feature = "aws-lc-rs")))]
+    let signer =
+        signature::EcdsaKeyPair::from_pkcs8(alg, &pkcs8, &rand::SystemRandom::new()).unwrap();
+
+    signer
+        .sign(&rand::SystemRandom::new(), message)
+        .unwrap()
+        .as_ref()
+        .to_vec()
+}
+
+/// Encode a DER TLV with the given tag, and value made of `parts`.
+#[cfg_attr(not(any(feature = "ring", feature = "aws-lc-rs")), allow(dead_code))]
+pub(crate) fn tlv(tag: u8, parts: &[&[u8]]) -> Vec<u8> {
+    let value = parts.concat();
+    let mut out = vec![tag];
+    let len = value.len();
+    match len {
+        0..=0x7f => out.push(u8::try_from(len).unwrap()),
+        0x80..=0xff => out.extend([0x81, u8::try_from(len).unwrap()]),
+        _ => {
+            out.push(0x82);
+            out.extend(u16::try_from(len).unwrap().to_be_bytes());
+        }
+    }
+    out.extend(value);
+    out
+}
+
 /// Signature algorithm used by certificates and parameters generated using the test utils helpers.
 pub(crate) static RCGEN_SIGNATURE_ALG: &rcgen::SignatureAlgorithm = &rcgen::PKCS_ECDSA_P256_SHA256;
+
+/// The time at which certificates generated using the test utils helpers are verified.
+#[cfg(any(feature = "ring", feature = "aws-lc-rs"))]
+pub(crate) fn test_time() -> pki_types::UnixTime {
+    pki_types::UnixTime::since_unix_epoch(core::time::Duration::from_secs(0x1fed_f00d))
+}
+
+/// Verify `ee` for server authentication at [`test_time()`], using all the verification
+/// algorithms exported by this crate.
+#[cfg(any(feature = "ring", feature = "aws-lc-rs"))]
+pub(crate) fn verify_server_auth<'p>(
+    ee: &'p crate::EndEntityCert<'p>,
+    anchors: &'p [pki_types::TrustAnchor<'_>],
+    intermediates: &'p [pki_types::CertificateDer<'p>],
+    revocation: Option<crate::RevocationOptions<'_>>,
+    options: &crate::VerificationOptions<'p>,
+) -> Result<crate::VerifiedPath<'p>, crate::Error> {
+    ee.verify_for_usage_with_options(
+        crate::ALL_VERIFICATION_ALGS,
+        anchors,
+        intermediates,
+        test_time(),
+        crate::KeyUsage::server_auth(),
+        revocation,
+        None,
+        options,
+    )
+}
+
+/// A trust anchor, an intermediate certificate it issued, and an end-entity certificate the
+/// intermediate issued.
+#[cfg(any(feature = "ring", feature = "aws-lc-rs"))]
+pub(crate) struct Pki {
+    pub(crate) anchors: [pki_types::TrustAnchor<'static>; 1],
+    pub(crate) intermediates: [pki_types::CertificateDer<'static>; 1],
+    pub(crate) ee: rcgen::CertifiedKey,
+}
+
+#[cfg(any(feature = "ring", feature = "aws-lc-rs"))]
+impl Pki {
+    /// Generate a PKI from the default issuer and end-entity parameters.
+    pub(crate) fn new() -> Self {
+        Self::with_params(
+            issuer_params("Intermediate"),
+            end_entity_params(vec!["example.com".into()]),
+        )
+    }
+
+    /// Generate a PKI whose intermediate and end-entity certificates are made from the given
+    /// parameters.
+    pub(crate) fn with_params(
+        intermediate: rcgen::CertificateParams,
+        ee: rcgen::CertificateParams,
+    ) -> Self {
+        let trust_anchor = make_issuer("Trust Anchor");
+        let anchor = crate::anchor_from_trusted_cert(trust_anchor.cert.der())
+            .unwrap()
+            .to_owned();
+
+        let intermediate_key = rcgen::KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let intermediate = intermediate
+            .signed_by(
+                &intermediate_key,
+                &trust_anchor.cert,
+                &trust_anchor.key_pair,
+            )
+            .unwrap();
+
+        let key_pair = rcgen::KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let ee = rcgen::CertifiedKey {
+            cert: ee
+                .signed_by(&key_pair, &intermediate, &intermediate_key)
+                .unwrap(),
+            key_pair,
+        };
+
+        Self {
+            anchors: [anchor],
+            intermediates: [intermediate.der().clone()],
+            ee,
+        }
+    }
+
+    /// Verify the end-entity certificate with `options`, passing the verified path to `f`.
+    pub(crate) fn verify<T>(
+        &self,
+        options: &crate::VerificationOptions<'_>,
+        f: impl FnOnce(&crate::VerifiedPath<'_>) -> T,
+    ) -> Result<T, crate::Error> {
+        let ee = crate::EndEntityCert::try_from(self.ee.cert.der()).unwrap();
+        verify_server_auth(&ee, &self.anchors, &self.intermediates, None, options)
+            .map(|path| f(&path))
+    }
+}
@@ -0,0 +1,291 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::cell::RefCell;
+use core::fmt::Debug;
+
+use pki_types::{CertificateDer, TrustAnchor};
+
+use crate::cert::Cert;
+use crate::error::Error;
+
+/// A collector of the steps taken while building a path.
+///
+/// When configured using [`VerificationOptions::with_trace()`], [`PathTrace::record()`] is
+/// called for every candidate issuer that was tried, explaining why it was (or wasn't)
+/// rejected. This is intended for diagnosing verification failures; see [`TraceLog`] for a
+/// ready-made collector.
+///
+/// Candidates whose subject doesn't match the issuer of the certificate being extended are not
+/// recorded. Every other candidate is recorded each time it is tried, so the same candidate may
+/// be recorded several times: for a certificate reached through more than one partial path, or
+/// for a trust anchor given more than once, e.g. both as a slice and in a trust store.
+///
+/// [`VerificationOptions::with_trace()`]: crate::VerificationOptions::with_trace
+pub trait PathTrace: Debug {
+    /// Record a single step of path building.
+    fn record(&self, step: &TraceStep<'_>);
+}
+
+/// An attempt to extend a path with a candidate issuer, as recorded by a [`PathTrace`].
+///
+/// Steps are recorded once the attempt is complete. Since path building proceeds depth-first,
+/// the steps for the issuers of a candidate intermediate (at `depth + 1`) are recorded before
+/// the step for the candidate itself.
+#[non_exhaustive]
+pub struct TraceStep<'a> {
+    /// The number of intermediate certificates in the path below the candidate, i.e. 0 when
+    /// looking for the issuer of the end-entity certificate.
+    pub depth: usize,
+    /// The certificate whose issuer was sought.
+    pub cert: &'a Cert<'a>,
+    /// The candidate issuer that was tried.
+    pub issuer: Candidate<'a>,
+    /// Whether the candidate led to a valid path, or else the check that failed and why.
+    pub result: Result<(), (PathCheck, Error)>,
+}
+
+/// A candidate issuer tried during path building.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Candidate<'a> {
+    /// One of the trust anchors.
+    TrustAnchor(TrustAnchor<'a>),
+    /// One of the intermediate certificates.
+    Intermediate(CertificateDer<'a>),
+}
+
+impl<'a> Candidate<'a> {
+    pub(crate) fn trust_anchor(anchor: &'a TrustAnchor<'_>) -> Self {
+        Self::TrustAnchor(TrustAnchor {
+            subject: anchor.subject.as_ref().into(),
+            subject_public_key_info: anchor.subject_public_key_info.as_ref().into(),
+            name_constraints: anchor
+                .name_constraints
+                .as_ref()
+                .map(|nc| nc.as_ref().into()),
+        })
+    }
+
+    pub(crate) fn intermediate(cert: &'a CertificateDer<'_>) -> Self {
+        Self::Intermediate(cert.as_ref().into())
+    }
+
+    /// Copy the candidate so that it no longer borrows from the path building inputs.
+    #[cfg(feature = "alloc")]
+    pub fn into_owned(self) -> Candidate<'static> {
+        match self {
+            Self::TrustAnchor(anchor) => Candidate::TrustAnchor(anchor.to_owned()),
+            Self::Intermediate(cert) => Candidate::Intermediate(cert.into_owned()),
+        }
+    }
+}
+
+/// The stage of path building at which a candidate issuer was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PathCheck {
+    /// The candidate intermediate certificate could not be parsed.
+    Parse,
+    /// The candidate is already part of the path.
+    Loop,
+    /// The candidate is distrusted for the end-entity certificate.
+    Distrust,
+    /// The path building [`Budget`](crate::Budget) was exhausted.
+    Budget,
+    /// The path would be longer than the maximum path depth.
+    PathDepth,
+    /// The candidate itself is unsuitable, e.g. because it is expired, not a CA, lacks the
+    /// required extended key usage, or has a key that the algorithm policy doesn't allow.
+    Properties,
+    /// None of the issuers of the candidate intermediate led to a valid path.
+    Issuers,
+    /// The path violates the constraints on the candidate trust anchor.
+    AnchorConstraints,
+    /// None of the certificates in the path, ending with the candidate trust anchor, has a
+    /// pinned subject public key.
+    Pins,
+    /// None of the acceptable extended key usage purposes is allowed by every certificate in
+    /// the path, ending with the candidate trust anchor.
+    KeyPurpose,
+    /// A signature in the path, ending with the candidate trust anchor, is invalid or
+    /// unsupported.
+    Signature,
+    /// A certificate in the path, ending with the candidate trust anchor, is revoked or its
+    /// revocation status could not be determined.
+    Revocation,
+    /// A certificate in the path, ending with the candidate trust anchor, violates the name
+    /// constraints of its issuers.
+    NameConstraints,
+    /// The path, ending with the candidate trust anchor, failed certificate policy processing.
+    Policy,
+    /// The path, ending with the candidate trust anchor, was rejected by the caller-provided
+    /// `verify_path` function.
+    VerifyPath,
+}
+
+/// A [`PathTrace`] which keeps every step in memory.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Default)]
+pub struct TraceLog {
+    entries: RefCell<Vec<TraceEntry>>,
+}
+
+#[cfg(feature = "alloc")]
+impl TraceLog {
+    /// Create a new, empty [`TraceLog`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Yield the recorded steps, in the order they were recorded.
+    pub fn into_entries(self) -> Vec<TraceEntry> {
+        self.entries.into_inner()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PathTrace for TraceLog {
+    fn record(&self, step: &TraceStep<'_>) {
+        self.entries.borrow_mut().push(TraceEntry {
+            depth: step.depth,
+            cert: step.cert.der().into_owned(),
+            issuer: step.issuer.clone().into_owned(),
+            result: step.result.clone(),
+        });
+    }
+}
+
+/// An owned copy of a [`TraceStep`], as kept by a [`TraceLog`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TraceEntry {
+    /// See [`TraceStep::depth`].
+    pub depth: usize,
+    /// The DER encoding of [`TraceStep::cert`].
+    pub cert: CertificateDer<'static>,
+    /// See [`TraceStep::issuer`].
+    pub issuer: Candidate<'static>,
+    /// See [`TraceStep::result`].
+    pub result: Result<(), (PathCheck, Error)>,
+}
+
+#[cfg(all(test, feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+mod tests {
+    use super::*;
+    use crate::end_entity::EndEntityCert;
+    use crate::error::DerTypeId;
+    use crate::test_utils::{issuer_params, make_end_entity, make_issuer, RCGEN_SIGNATURE_ALG};
+    use crate::trust_anchor::anchor_from_trusted_cert;
+    use crate::verify_cert::{KeyUsage, VerificationOptions};
+    use pki_types::UnixTime;
+    use rcgen::KeyPair;
+    use std::prelude::v1::*;
+
+    #[test]
+    fn trace_log() {
+        let trust_anchor = make_issuer("Trust Anchor");
+        let anchor = anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap();
+
+        let key_pair = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let valid = issuer_params("Intermediate")
+            .signed_by(&key_pair, &trust_anchor.cert, &trust_anchor.key_pair)
+            .unwrap();
+
+        let mut params = issuer_params("Intermediate");
+        params.not_after = rcgen::date_time_ymd(1980, 1, 1);
+        let expired = params
+            .signed_by(&key_pair, &trust_anchor.cert, &trust_anchor.key_pair)
+            .unwrap();
+
+        let other_key_pair = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let wrong_key = issuer_params("Intermediate")
+            .signed_by(&other_key_pair, &trust_anchor.cert, &trust_anchor.key_pair)
+            .unwrap();
+
+        let ee = make_end_entity(&valid, &key_pair);
+        let ee_cert = EndEntityCert::try_from(ee.cert.der()).unwrap();
+
+        let garbage = CertificateDer::from(&b"not a certificate"[..]);
+        let intermediates = [
+            garbage.clone(),
+            expired.der().clone(),
+            wrong_key.der().clone(),
+            valid.der().clone(),
+        ];
+
+        let log = TraceLog::new();
+        ee_cert
+            .verify_for_usage_with_options(
+                crate::ALL_VERIFICATION_ALGS,
+                &[anchor.clone()],
+                &intermediates,
+                UnixTime::since_unix_epoch(core::time::Duration::from_secs(0x1fed_f00d)),
+                KeyUsage::server_auth(),
+                None,
+                None,
+                &VerificationOptions::new().with_trace(&log),
+            )
+            .unwrap();
+
+        let anchor = Candidate::TrustAnchor(anchor.to_owned());
+        let entry = |depth, cert: &CertificateDer<'_>, issuer, result| TraceEntry {
+            depth,
+            cert: cert.clone().into_owned(),
+            issuer,
+            result,
+        };
+        assert_eq!(
+            log.into_entries(),
+            vec![
+                entry(
+                    0,
+                    ee.cert.der(),
+                    Candidate::Intermediate(garbage.clone().into_owned()),
+                    Err((PathCheck::Parse, Error::TrailingData(DerTypeId::SignedData)))
+                ),
+                entry(
+                    0,
+                    ee.cert.der(),
+                    Candidate::Intermediate(expired.der().clone()),
+                    Err((
+                        PathCheck::Properties,
+                        Error::CertExpired {
+                            time: UnixTime::since_unix_epoch(core::time::Duration::from_secs(
+                                0x1fed_f00d
+                            )),
+                            not_after: UnixTime::since_unix_epoch(core::time::Duration::from_secs(
+                                315_532_800
+                            )),
+                        }
+                    ))
+                ),
+                entry(
+                    1,
+                    wrong_key.der(),
+                    anchor.clone(),
+                    Err((PathCheck::Signature, Error::InvalidSignatureForPublicKey))
+                ),
+                entry(
+                    1,
+                    wrong_key.der(),
+                    Candidate::Intermediate(garbage.clone().into_owned()),
+                    Err((PathCheck::Parse, Error::TrailingData(DerTypeId::SignedData)))
+                ),
+                entry(
+                    0,
+                    ee.cert.der(),
+                    Candidate::Intermediate(wrong_key.der().clone()),
+                    Err((PathCheck::Issuers, Error::InvalidSignatureForPublicKey))
+                ),
+                entry(1, valid.der(), anchor, Ok(())),
+                entry(
+                    0,
+                    ee.cert.der(),
+                    Candidate::Intermediate(valid.der().clone()),
+                    Ok(())
+                ),
+            ]
+        );
+    }
+}
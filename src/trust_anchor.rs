@@ -1,7 +1,7 @@
-use pki_types::{CertificateDer, TrustAnchor};
+use pki_types::{CertificateDer, Der, TrustAnchor, UnixTime};
 
 use crate::cert::{lenient_certificate_serial_number, Cert};
-use crate::der;
+use crate::der::{self, FromDer};
 use crate::error::{DerTypeId, Error};
 
 /// Interprets the given pre-validated DER-encoded certificate as a `TrustAnchor`.
@@ -36,17 +36,170 @@ pub fn anchor_from_trusted_cert<'a>(
     // embedded name constraints in a v1 certificate.
     match Cert::from_der(cert_der) {
         Ok(cert) => Ok(TrustAnchor::from(cert)),
+        Err(Error::UnsupportedCertVersion) => extract_trust_anchor_from_v1_cert_der(cert_der)
+            .map(|(anchor, _)| anchor)
+            .or(Err(Error::BadDer)),
+        Err(err) => Err(err),
+    }
+}
+
+/// Interprets the given pre-validated DER-encoded certificate as a [`ConstrainedTrustAnchor`].
+///
+/// This is like [`anchor_from_trusted_cert()`], but additionally keeps the extended key usage,
+/// key usage and path length constraint extensions and the validity period of the certificate
+/// as [`TrustAnchorConstraints`]. These are then enforced for paths ending at the trust anchor
+/// when configured using [`VerificationOptions::with_constrained_anchors()`].
+///
+/// A v1 certificate has no extensions, so only its validity period is kept.
+///
+/// [`VerificationOptions::with_constrained_anchors()`]: crate::VerificationOptions::with_constrained_anchors
+pub fn constrained_anchor_from_trusted_cert<'a>(
+    cert: &'a CertificateDer<'a>,
+) -> Result<ConstrainedTrustAnchor<'a>, Error> {
+    let cert_der = untrusted::Input::from(cert.as_ref());
+
+    let (anchor, validity, constraints) = match Cert::from_der(cert_der) {
+        Ok(cert) => {
+            let path_len =
+                untrusted::read_all_optional(cert.basic_constraints, Error::BadDer, |value| {
+                    match value {
+                        Some(value) => {
+                            bool::from_der(value)?; // cA
+                            match value.at_end() {
+                                true => Ok(None),
+                                false => Ok(Some(usize::from(u8::from_der(value)?))),
+                            }
+                        }
+                        None => Ok(None),
+                    }
+                })?;
+
+            let constraints = TrustAnchorConstraints {
+                extended_key_usage: cert.eku.map(|eku| eku.as_slice_less_safe().into()),
+                key_usage: cert.key_usage.map(|ku| ku.as_slice_less_safe().into()),
+                path_len,
+                validity: None,
+            };
+            let validity = cert.validity;
+            (TrustAnchor::from(cert), validity, constraints)
+        }
         Err(Error::UnsupportedCertVersion) => {
-            extract_trust_anchor_from_v1_cert_der(cert_der).or(Err(Error::BadDer))
+            let (anchor, validity) =
+                extract_trust_anchor_from_v1_cert_der(cert_der).or(Err(Error::BadDer))?;
+            (anchor, validity, TrustAnchorConstraints::default())
+        }
+        Err(err) => return Err(err),
+    };
+
+    let (not_before, not_after) = validity.read_all(Error::BadDer, |value| {
+        Ok((UnixTime::from_der(value)?, UnixTime::from_der(value)?))
+    })?;
+
+    Ok(ConstrainedTrustAnchor {
+        anchor,
+        constraints: constraints.with_validity(not_before, not_after),
+    })
+}
+
+/// A trust anchor together with constraints on its use, as described in [RFC 5937].
+///
+/// Root programs commonly restrict trust anchors to certain purposes, such as server
+/// authentication only. Use with [`VerificationOptions::with_constrained_anchors()`] or
+/// [`TrustStore::add_constrained()`].
+///
+/// [RFC 5937]: <https://www.rfc-editor.org/rfc/rfc5937>
+/// [`VerificationOptions::with_constrained_anchors()`]: crate::VerificationOptions::with_constrained_anchors
+/// [`TrustStore::add_constrained()`]: crate::TrustStore::add_constrained
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstrainedTrustAnchor<'a> {
+    /// The trust anchor.
+    pub anchor: TrustAnchor<'a>,
+    /// The constraints on paths ending at `anchor`.
+    pub constraints: TrustAnchorConstraints<'a>,
+}
+
+impl ConstrainedTrustAnchor<'_> {
+    /// Yield a `'static` lifetime of the `ConstrainedTrustAnchor` by allocating owned `Der`
+    /// variants.
+    #[cfg(feature = "alloc")]
+    pub fn to_owned(&self) -> ConstrainedTrustAnchor<'static> {
+        ConstrainedTrustAnchor {
+            anchor: self.anchor.to_owned(),
+            constraints: self.constraints.to_owned(),
+        }
+    }
+}
+
+/// Constraints on the paths ending at a trust anchor.
+///
+/// The [`Default`] constraints don't constrain the trust anchor at all. Paths violating the
+/// constraints are rejected with the same errors as if the trust anchor were an intermediate
+/// certificate with the corresponding extensions and validity period.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrustAnchorConstraints<'a> {
+    pub(crate) extended_key_usage: Option<Der<'a>>,
+    pub(crate) key_usage: Option<Der<'a>>,
+    pub(crate) path_len: Option<usize>,
+    pub(crate) validity: Option<(UnixTime, UnixTime)>,
+}
+
+impl<'a> TrustAnchorConstraints<'a> {
+    /// Create new [`TrustAnchorConstraints`] that don't constrain the trust anchor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict the trust anchor to the key purposes in `extended_key_usage`.
+    ///
+    /// This is the DER-encoded content of an extended key usage extension's `SEQUENCE`, i.e.
+    /// the concatenated `KeyPurposeId` OBJECT IDENTIFIERs. A path is only valid for the usage
+    /// being verified if it is included.
+    pub fn with_extended_key_usage(mut self, extended_key_usage: Der<'a>) -> Self {
+        self.extended_key_usage = Some(extended_key_usage);
+        self
+    }
+
+    /// Restrict the trust anchor to the uses in `key_usage`, a DER-encoded key usage
+    /// `BIT STRING`.
+    ///
+    /// This is consulted when the trust anchor is the issuer of a CRL used for revocation
+    /// checking, which requires the `cRLSign` bit.
+    pub fn with_key_usage(mut self, key_usage: Der<'a>) -> Self {
+        self.key_usage = Some(key_usage);
+        self
+    }
+
+    /// Restrict the number of intermediate certificates in a path ending at the trust anchor to
+    /// `path_len`, like a path length constraint in a basic constraints extension.
+    pub fn with_path_len(mut self, path_len: usize) -> Self {
+        self.path_len = Some(path_len);
+        self
+    }
+
+    /// Restrict the use of the trust anchor to the period from `not_before` to `not_after`.
+    pub fn with_validity(mut self, not_before: UnixTime, not_after: UnixTime) -> Self {
+        self.validity = Some((not_before, not_after));
+        self
+    }
+
+    /// Yield a `'static` lifetime of the `TrustAnchorConstraints` by allocating owned `Der`
+    /// variants.
+    #[cfg(feature = "alloc")]
+    pub fn to_owned(&self) -> TrustAnchorConstraints<'static> {
+        let to_owned = |der: &Der<'_>| Der::from(der.as_ref().to_vec());
+        TrustAnchorConstraints {
+            extended_key_usage: self.extended_key_usage.as_ref().map(to_owned),
+            key_usage: self.key_usage.as_ref().map(to_owned),
+            path_len: self.path_len,
+            validity: self.validity,
         }
-        Err(err) => Err(err),
     }
 }
 
-/// Parses a v1 certificate directly into a TrustAnchor.
+/// Parses a v1 certificate directly into a TrustAnchor, also returning its validity.
 fn extract_trust_anchor_from_v1_cert_der(
     cert_der: untrusted::Input<'_>,
-) -> Result<TrustAnchor<'_>, Error> {
+) -> Result<(TrustAnchor<'_>, untrusted::Input<'_>), Error> {
     // X.509 Certificate: https://tools.ietf.org/html/rfc5280#section-4.1.
     cert_der.read_all(Error::BadDer, |cert_der| {
         der::nested(
@@ -64,15 +217,16 @@ fn extract_trust_anchor_from_v1_cert_der(
 
                         skip(tbs, der::Tag::Sequence)?; // signature.
                         skip(tbs, der::Tag::Sequence)?; // issuer.
-                        skip(tbs, der::Tag::Sequence)?; // validity.
+                        let validity = der::expect_tag(tbs, der::Tag::Sequence)?;
                         let subject = der::expect_tag(tbs, der::Tag::Sequence)?;
                         let spki = der::expect_tag(tbs, der::Tag::Sequence)?;
 
-                        Ok(TrustAnchor {
+                        let anchor = TrustAnchor {
                             subject: subject.as_slice_less_safe().into(),
                             subject_public_key_info: spki.as_slice_less_safe().into(),
                             name_constraints: None,
-                        })
+                        };
+                        Ok((anchor, validity))
                     },
                 );
 
@@ -101,3 +255,168 @@ impl<'a> From<Cert<'a>> for TrustAnchor<'a> {
 fn skip(input: &mut untrusted::Reader<'_>, tag: der::Tag) -> Result<(), Error> {
     der::expect_tag(input, tag).map(|_| ())
 }
+
+#[cfg(all(test, feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+mod tests {
+    use super::*;
+    use crate::end_entity::EndEntityCert;
+    use crate::test_utils::{issuer_params, make_end_entity, make_issuer, RCGEN_SIGNATURE_ALG};
+    use crate::trust_store::TrustStore;
+    use crate::verify_cert::{KeyUsage, VerificationOptions};
+    use rcgen::{BasicConstraints, ExtendedKeyUsagePurpose, IsCa, KeyPair};
+    use std::prelude::v1::*;
+
+    #[test]
+    fn constraints_from_trusted_cert() {
+        let key_pair = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let mut params = issuer_params("Trust Anchor");
+        params.is_ca = IsCa::Ca(BasicConstraints::Constrained(1));
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+        params.not_before = rcgen::date_time_ymd(1980, 1, 1);
+        params.not_after = rcgen::date_time_ymd(1990, 1, 1);
+        let cert = params.self_signed(&key_pair).unwrap();
+
+        let constrained = constrained_anchor_from_trusted_cert(cert.der()).unwrap();
+        assert_eq!(
+            constrained.anchor,
+            anchor_from_trusted_cert(cert.der()).unwrap()
+        );
+
+        let constraints = constrained.constraints;
+        assert_eq!(
+            constraints.extended_key_usage.as_deref(),
+            Some(asn1_oid(&oid!(1, 3, 6, 1, 5, 5, 7, 3, 2)).as_slice())
+        );
+        assert!(constraints.key_usage.is_some());
+        assert_eq!(constraints.path_len, Some(1));
+        assert_eq!(
+            constraints.validity,
+            Some((
+                UnixTime::since_unix_epoch(core::time::Duration::from_secs(315_532_800)),
+                UnixTime::since_unix_epoch(core::time::Duration::from_secs(631_152_000)),
+            ))
+        );
+    }
+
+    #[test]
+    fn constraints_enforced() {
+        let unconstrained = TrustAnchorConstraints::new();
+        assert_eq!(verify(unconstrained.clone()), Ok(()));
+
+        let client_auth = asn1_oid(&oid!(1, 3, 6, 1, 5, 5, 7, 3, 2));
+        assert_eq!(
+            verify(
+                unconstrained
+                    .clone()
+                    .with_extended_key_usage(client_auth.as_slice().into())
+            ),
+            Err(Error::RequiredEkuNotFound)
+        );
+
+        assert_eq!(verify(unconstrained.clone().with_path_len(1)), Ok(()));
+        assert_eq!(
+            verify(unconstrained.clone().with_path_len(0)),
+            Err(Error::PathLenConstraintViolated)
+        );
+
+        let not_after = UnixTime::since_unix_epoch(core::time::Duration::from_secs(0x1fed_0000));
+        assert_eq!(
+            verify(unconstrained.with_validity(
+                UnixTime::since_unix_epoch(core::time::Duration::ZERO),
+                not_after
+            )),
+            Err(Error::CertExpired {
+                time: time(),
+                not_after
+            })
+        );
+    }
+
+    #[test]
+    fn constraints_kept_in_trust_store() {
+        let path_len = TrustAnchorConstraints::new().with_path_len(0);
+        assert_eq!(
+            verify_with_store(path_len.clone(), false),
+            Err(Error::PathLenConstraintViolated)
+        );
+
+        // The constraints of the trust anchor added first are kept.
+        assert_eq!(verify_with_store(path_len, true), Ok(()));
+    }
+
+    /// Verify a path through one intermediate to a trust anchor with the given `constraints`.
+    fn verify(constraints: TrustAnchorConstraints<'_>) -> Result<(), Error> {
+        let trust_anchor = make_issuer("Trust Anchor");
+        let anchor = anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap();
+
+        let key_pair = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let intermediate = issuer_params("Intermediate")
+            .signed_by(&key_pair, &trust_anchor.cert, &trust_anchor.key_pair)
+            .unwrap();
+        let ee = make_end_entity(&intermediate, &key_pair);
+        let ee = EndEntityCert::try_from(ee.cert.der()).unwrap();
+
+        let constrained = [ConstrainedTrustAnchor {
+            anchor: anchor.clone(),
+            constraints,
+        }];
+        ee.verify_for_usage_with_options(
+            crate::ALL_VERIFICATION_ALGS,
+            &[],
+            &[intermediate.der().clone()],
+            time(),
+            KeyUsage::server_auth(),
+            None,
+            None,
+            &VerificationOptions::new().with_constrained_anchors(&constrained),
+        )
+        .map(|_| ())
+    }
+
+    /// Like [`verify()`], but with the constrained trust anchor in a [`TrustStore`], to which
+    /// the same trust anchor was first added unconstrained if `unconstrained_first`.
+    fn verify_with_store(
+        constraints: TrustAnchorConstraints<'_>,
+        unconstrained_first: bool,
+    ) -> Result<(), Error> {
+        let trust_anchor = make_issuer("Trust Anchor");
+        let anchor = anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap();
+
+        let key_pair = KeyPair::generate_for(RCGEN_SIGNATURE_ALG).unwrap();
+        let intermediate = issuer_params("Intermediate")
+            .signed_by(&key_pair, &trust_anchor.cert, &trust_anchor.key_pair)
+            .unwrap();
+        let ee = make_end_entity(&intermediate, &key_pair);
+        let ee = EndEntityCert::try_from(ee.cert.der()).unwrap();
+
+        let mut store = TrustStore::new();
+        if unconstrained_first {
+            assert!(store.add(anchor.clone()));
+        }
+        let added = store.add_constrained(ConstrainedTrustAnchor {
+            anchor,
+            constraints,
+        });
+        assert_eq!(added, !unconstrained_first);
+
+        ee.verify_for_usage_with_options(
+            crate::ALL_VERIFICATION_ALGS,
+            &[],
+            &[intermediate.der().clone()],
+            time(),
+            KeyUsage::server_auth(),
+            None,
+            None,
+            &VerificationOptions::new().with_trust_store(&store),
+        )
+        .map(|_| ())
+    }
+
+    fn asn1_oid(oid: &[u8]) -> Vec<u8> {
+        der::asn1_wrap(der::Tag::OID, oid)
+    }
+
+    fn time() -> UnixTime {
+        UnixTime::since_unix_epoch(core::time::Duration::from_secs(0x1fed_f00d))
+    }
+}
@@ -0,0 +1,272 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use pki_types::{CertificateDer, TrustAnchor};
+
+use crate::cert::Cert;
+use crate::error::Error;
+use crate::trust_anchor::{
+    anchor_from_trusted_cert, ConstrainedTrustAnchor, TrustAnchorConstraints,
+};
+
+/// An owned set of trust anchors, indexed by subject name.
+///
+/// Path building considers each trust anchor given as a slice for every certificate in a
+/// candidate path. A `TrustStore` instead looks up only the trust anchors whose subject is the
+/// issuer of the certificate, which is much faster for large sets of trust anchors. Use it with
+/// [`VerificationOptions::with_trust_store()`] or [`VerifierBuilder::with_trust_store()`].
+///
+/// Duplicates are detected by subject and subject public key info together, not by subject
+/// public key info alone: a key certified under several subject names, such as a root that was
+/// renamed, stays reachable under each of them.
+///
+/// Trust anchors added from certificates using [`TrustStore::add_cert()`] keep their subject
+/// key identifier, if any. When several trust anchors share a subject name, those whose subject
+/// key identifier matches the authority key identifier of the certificate being verified are
+/// tried first.
+///
+/// [`VerificationOptions::with_trust_store()`]: crate::VerificationOptions::with_trust_store
+/// [`VerifierBuilder::with_trust_store()`]: crate::VerifierBuilder::with_trust_store
+#[derive(Clone, Debug, Default)]
+pub struct TrustStore {
+    anchors: Vec<StoredAnchor>,
+    by_subject: BTreeMap<Vec<u8>, Vec<usize>>,
+}
+
+impl TrustStore {
+    /// Create an empty trust store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `anchor` to the store.
+    ///
+    /// Returns `false`, leaving the store unchanged, if there is already a trust anchor with
+    /// the same subject and subject public key info in the store. This is the case even if
+    /// their name constraints differ; the first one added is kept.
+    ///
+    /// A [`TrustAnchor`] does not carry a subject key identifier, so `anchor` is tried after the
+    /// trust anchors with the same subject whose key identifier matches, and before those whose
+    /// key identifier differs. Use [`TrustStore::add_cert()`] to keep the key identifier.
+    pub fn add(&mut self, anchor: TrustAnchor<'_>) -> bool {
+        self.insert(anchor, None, None)
+    }
+
+    /// Add `constrained.anchor` to the store, enforcing `constrained.constraints` on paths ending
+    /// at it.
+    ///
+    /// Returns `false` if there is already a trust anchor with the same subject and subject
+    /// public key info in the store, as for [`TrustStore::add()`]. The constraints of the trust
+    /// anchor already in the store, if any, are kept. As for [`TrustStore::add()`], no subject key
+    /// identifier is recorded.
+    pub fn add_constrained(&mut self, constrained: ConstrainedTrustAnchor<'_>) -> bool {
+        self.insert(
+            constrained.anchor,
+            None,
+            Some(constrained.constraints.to_owned()),
+        )
+    }
+
+    /// Add the trust anchor described by `cert` to the store, as obtained from
+    /// [`anchor_from_trusted_cert()`].
+    ///
+    /// Returns `Ok(false)` if there is already a trust anchor with the same subject and subject
+    /// public key info in the store, as for [`TrustStore::add()`].
+    pub fn add_cert(&mut self, cert: &CertificateDer<'_>) -> Result<bool, Error> {
+        let anchor = anchor_from_trusted_cert(cert)?;
+        let key_id = Cert::from_der(untrusted::Input::from(cert.as_ref()))
+            .ok()
+            .and_then(|cert| cert.subject_key_identifier())
+            .map(|skid| skid.as_slice_less_safe().to_vec());
+        Ok(self.insert(anchor, key_id, None))
+    }
+
+    /// The number of trust anchors in the store.
+    pub fn len(&self) -> usize {
+        self.anchors.len()
+    }
+
+    /// Returns `true` if there are no trust anchors in the store.
+    pub fn is_empty(&self) -> bool {
+        self.anchors.is_empty()
+    }
+
+    /// Iterate over the trust anchors in the store, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &TrustAnchor<'static>> {
+        self.anchors.iter().map(|stored| &stored.anchor)
+    }
+
+    /// The trust anchors with the subject `issuer`, in the order they were added, each with its
+    /// subject key identifier, if known, and its constraints, if any.
+    pub(crate) fn issuers(
+        &self,
+        issuer: untrusted::Input<'_>,
+    ) -> impl Iterator<
+        Item = (
+            &TrustAnchor<'static>,
+            Option<untrusted::Input<'_>>,
+            Option<&TrustAnchorConstraints<'static>>,
+        ),
+    > {
+        self.by_subject
+            .get(issuer.as_slice_less_safe())
+            .into_iter()
+            .flatten()
+            .map(|&i| {
+                let stored = &self.anchors[i];
+                (
+                    &stored.anchor,
+                    stored.key_id.as_deref().map(untrusted::Input::from),
+                    stored.constraints.as_ref(),
+                )
+            })
+    }
+
+    fn insert(
+        &mut self,
+        anchor: TrustAnchor<'_>,
+        key_id: Option<Vec<u8>>,
+        constraints: Option<TrustAnchorConstraints<'static>>,
+    ) -> bool {
+        let indices = self
+            .by_subject
+            .entry(anchor.subject.as_ref().to_vec())
+            .or_default();
+        if indices.iter().any(|&i| {
+            self.anchors[i].anchor.subject_public_key_info == anchor.subject_public_key_info
+        }) {
+            return false;
+        }
+
+        indices.push(self.anchors.len());
+        self.anchors.push(StoredAnchor {
+            anchor: anchor.to_owned(),
+            key_id,
+            constraints,
+        });
+        true
+    }
+}
+
+impl<'a> FromIterator<TrustAnchor<'a>> for TrustStore {
+    fn from_iter<T: IntoIterator<Item = TrustAnchor<'a>>>(iter: T) -> Self {
+        let mut store = Self::new();
+        for anchor in iter {
+            store.add(anchor);
+        }
+        store
+    }
+}
+
+#[derive(Clone, Debug)]
+struct StoredAnchor {
+    anchor: TrustAnchor<'static>,
+    key_id: Option<Vec<u8>>,
+    constraints: Option<TrustAnchorConstraints<'static>>,
+}
+
+#[cfg(all(test, any(feature = "ring", feature = "aws-lc-rs")))]
+mod tests {
+    use super::*;
+    use crate::end_entity::EndEntityCert;
+    use crate::test_utils::{
+        end_entity_params, issuer_params, make_end_entity, make_issuer, verify_server_auth,
+    };
+    use crate::verify_cert::{Budget, VerificationOptions};
+    use rcgen::KeyPair;
+    use std::prelude::v1::*;
+
+    #[test]
+    fn add_skips_duplicates() {
+        let first = make_issuer("Trust Anchor");
+        let rekeyed = make_issuer("Trust Anchor");
+        let other = make_issuer("Other Trust Anchor");
+
+        let mut store = TrustStore::new();
+        assert!(store.is_empty());
+        assert_eq!(store.add_cert(first.cert.der()), Ok(true));
+        assert_eq!(store.add_cert(first.cert.der()), Ok(false));
+        let anchor = anchor_from_trusted_cert(first.cert.der()).unwrap();
+        assert!(!store.add(anchor.clone()));
+
+        // The same subject with a different key, or a different subject, is not a duplicate,
+        // even with the same key.
+        let renamed = issuer_params("Renamed Trust Anchor")
+            .self_signed(&first.key_pair)
+            .unwrap();
+        assert_eq!(store.add_cert(rekeyed.cert.der()), Ok(true));
+        assert_eq!(store.add_cert(other.cert.der()), Ok(true));
+        assert_eq!(store.add_cert(renamed.der()), Ok(true));
+        assert_eq!(store.len(), 4);
+        assert_eq!(store.iter().next(), Some(&anchor.to_owned()));
+
+        let store = [anchor.clone(), anchor].into_iter().collect::<TrustStore>();
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn verify_with_trust_store() {
+        // Several trust anchors share a subject name; the end-entity certificate was issued by
+        // the last one added.
+        let anchors = (0..3)
+            .map(|_| make_issuer("Trust Anchor"))
+            .collect::<Vec<_>>();
+        let issuer = anchors.last().unwrap();
+        let mut ee_params = end_entity_params(vec!["example.com".into()]);
+        ee_params.use_authority_key_identifier_extension = true;
+        let ee_key = KeyPair::generate_for(crate::test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+        let ee = ee_params
+            .signed_by(&ee_key, &issuer.cert, &issuer.key_pair)
+            .unwrap();
+        let ee_cert = EndEntityCert::try_from(ee.der()).unwrap();
+
+        let mut store = TrustStore::new();
+        for anchor in &anchors {
+            store.add_cert(anchor.cert.der()).unwrap();
+        }
+        store.add_cert(make_issuer("Other").cert.der()).unwrap();
+
+        // The key identifiers lead straight to the right trust anchor, so a single signature
+        // check is enough.
+        let budget = Budget::default().with_signatures(1);
+        let verify = |store: &TrustStore| {
+            verify_server_auth(
+                &ee_cert,
+                &[],
+                &[],
+                None,
+                &VerificationOptions::new()
+                    .with_budget(budget)
+                    .with_trust_store(store),
+            )
+            .map(|path| path.anchor().subject_public_key_info.to_vec())
+        };
+        let expected = anchor_from_trusted_cert(issuer.cert.der())
+            .unwrap()
+            .subject_public_key_info
+            .to_vec();
+        assert_eq!(verify(&store), Ok(expected));
+
+        // Without key identifiers, trust anchors are tried in the order they were added.
+        let store = anchors
+            .iter()
+            .map(|anchor| anchor_from_trusted_cert(anchor.cert.der()).unwrap())
+            .collect::<TrustStore>();
+        assert_eq!(verify(&store), Err(Error::MaximumSignatureChecksExceeded));
+
+        let unknown = make_issuer("Unknown");
+        let ee = make_end_entity(&unknown.cert, &unknown.key_pair);
+        let ee_cert = EndEntityCert::try_from(ee.cert.der()).unwrap();
+        assert_eq!(
+            verify_server_auth(
+                &ee_cert,
+                &[],
+                &[],
+                None,
+                &VerificationOptions::new().with_trust_store(&store),
+            )
+            .map(|_| ()),
+            Err(Error::UnknownIssuer)
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use pki_types::{CertificateDer, SignatureVerificationAlgorithm, TrustAnchor, UnixTime};
+
+use crate::crl::{RevocationOptions, StapledOcsp};
+use crate::end_entity::EndEntityCert;
+use crate::error::Error;
+#[cfg(feature = "alloc")]
+use crate::intermediate_pool::IntermediatePool;
+use crate::trace::PathTrace;
+#[cfg(feature = "alloc")]
+use crate::trust_store::TrustStore;
+use crate::verify_cert::{ChainOptions, KeyUsage, PathOptions, VerificationOptions, VerifiedPath};
+
+/// Verifies end-entity certificates against a fixed configuration.
+///
+/// A `Verifier` holds the trust anchors, supported signature algorithms, required key usage,
+/// revocation options and [`VerificationOptions`] that would otherwise be passed to each call
+/// of [`EndEntityCert::verify_for_usage_with_options()`]. It is built once using a
+/// [`VerifierBuilder`], and is `Send + Sync` so that it can be shared between threads:
+///
+/// ```
+/// # #[cfg(feature = "ring")] {
+/// use webpki::{KeyUsage, Verifier};
+///
+/// fn assert_send_sync<T: Send + Sync>(_: &T) {}
+///
+/// let verifier = Verifier::builder(webpki::ALL_VERIFICATION_ALGS, &[], KeyUsage::server_auth())
+///     .build();
+/// assert_send_sync(&verifier);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Verifier<'a> {
+    supported_sig_algs: &'a [&'a dyn SignatureVerificationAlgorithm],
+    trust_anchors: &'a [TrustAnchor<'a>],
+    usage: KeyUsage<'a>,
+    revocation: Option<RevocationOptions<'a>>,
+    options: PathOptions<'a>,
+}
+
+// A `Verifier` is meant to be shared between threads, whatever the enabled features.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Verifier<'static>>();
+};
+
+impl<'a> Verifier<'a> {
+    /// Start building a [`Verifier`] that checks paths to one of `trust_anchors` using the
+    /// `supported_sig_algs`, for the key usage `usage`.
+    ///
+    /// See [`VerifierBuilder::new()`].
+    pub fn builder(
+        supported_sig_algs: &'a [&'a dyn SignatureVerificationAlgorithm],
+        trust_anchors: &'a [TrustAnchor<'a>],
+        usage: KeyUsage<'a>,
+    ) -> VerifierBuilder<'a> {
+        VerifierBuilder::new(supported_sig_algs, trust_anchors, usage)
+    }
+
+    /// Verify that `cert` is valid at `time`, using the `intermediate_certs` supplied with it.
+    ///
+    /// This is equivalent to [`EndEntityCert::verify_for_usage_with_options()`] with the
+    /// verifier's configuration.
+    pub fn verify<'p>(
+        &'p self,
+        cert: &'p EndEntityCert<'p>,
+        intermediate_certs: &'p [CertificateDer<'p>],
+        time: UnixTime,
+    ) -> Result<VerifiedPath<'p>, Error> {
+        self.verify_inner(cert, intermediate_certs, time, PerCallOptions::default())
+    }
+
+    /// Like [`Verifier::verify()`], but with `options` that are specific to this verification,
+    /// such as a trace or stapled OCSP responses.
+    pub fn verify_with<'p>(
+        &'p self,
+        cert: &'p EndEntityCert<'p>,
+        intermediate_certs: &'p [CertificateDer<'p>],
+        time: UnixTime,
+        options: PerCallOptions<'p>,
+    ) -> Result<VerifiedPath<'p>, Error> {
+        self.verify_inner(cert, intermediate_certs, time, options)
+    }
+
+    fn verify_inner<'p>(
+        &'p self,
+        cert: &'p EndEntityCert<'p>,
+        intermediate_certs: &'p [CertificateDer<'p>],
+        time: UnixTime,
+        options: PerCallOptions<'p>,
+    ) -> Result<VerifiedPath<'p>, Error> {
+        ChainOptions {
+            trace: options.trace,
+            ..ChainOptions::new(
+                self.supported_sig_algs,
+                self.trust_anchors,
+                intermediate_certs,
+                self.usage,
+                self.revocation,
+                &VerificationOptions {
+                    path: self.options,
+                    trace: None,
+                    stapled_ocsp: options.stapled_ocsp,
+                },
+            )
+        }
+        .build_chain(cert, time, None)
+    }
+}
+
+/// Options specific to a single verification using [`Verifier::verify_with()`].
+///
+/// These can't be set when building the [`Verifier`], as they aren't shared between
+/// verifications.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PerCallOptions<'a> {
+    trace: Option<&'a dyn PathTrace>,
+    stapled_ocsp: StapledOcsp<'a>,
+}
+
+impl<'a> PerCallOptions<'a> {
+    /// Create a new set of options, with neither a trace nor stapled OCSP responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record every candidate issuer tried while building the path in `trace`.
+    ///
+    /// See [`VerificationOptions::with_trace()`].
+    pub fn with_trace(mut self, trace: &'a dyn PathTrace) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// Check the revocation status of the end-entity certificate using `response`, the OCSP
+    /// response stapled to the TLS handshake.
+    ///
+    /// See [`VerificationOptions::with_stapled_ocsp()`].
+    pub fn with_stapled_ocsp(mut self, response: &'a [u8]) -> Self {
+        self.stapled_ocsp.end_entity = Some(response);
+        self
+    }
+
+    /// Check the revocation status of intermediate certificates using `responses`, the OCSP
+    /// responses stapled to the TLS handshake.
+    ///
+    /// See [`VerificationOptions::with_stapled_intermediate_ocsp()`].
+    pub fn with_stapled_intermediate_ocsp(mut self, responses: &'a [&'a [u8]]) -> Self {
+        self.stapled_ocsp.intermediates = responses;
+        self
+    }
+}
+
+/// Builds a [`Verifier`].
+#[derive(Clone, Copy, Debug)]
+pub struct VerifierBuilder<'a> {
+    verifier: Verifier<'a>,
+}
+
+impl<'a> VerifierBuilder<'a> {
+    /// Create a builder for a [`Verifier`] that checks paths to one of `trust_anchors` using
+    /// the `supported_sig_algs`, for the key usage `usage`.
+    ///
+    /// By default revocation is not checked, and the [`VerificationOptions`] are the defaults.
+    pub fn new(
+        supported_sig_algs: &'a [&'a dyn SignatureVerificationAlgorithm],
+        trust_anchors: &'a [TrustAnchor<'a>],
+        usage: KeyUsage<'a>,
+    ) -> Self {
+        Self {
+            verifier: Verifier {
+                supported_sig_algs,
+                trust_anchors,
+                usage,
+                revocation: None,
+                options: PathOptions::default(),
+            },
+        }
+    }
+
+    /// Also consider the trust anchors in `trust_store`, which are looked up by subject name.
+    ///
+    /// See [`VerificationOptions::with_trust_store()`].
+    #[cfg(feature = "alloc")]
+    pub fn with_trust_store(mut self, trust_store: &'a TrustStore) -> Self {
+        self.verifier.options.trust_store = Some(trust_store);
+        self
+    }
+
+    /// Also consider the certificates in `intermediate_pool` as intermediates, alongside
+    /// those supplied with each end-entity certificate.
+    #[cfg(feature = "alloc")]
+    pub fn with_intermediate_pool(mut self, intermediate_pool: &'a IntermediatePool) -> Self {
+        self.verifier.options.intermediate_pool = Some(intermediate_pool);
+        self
+    }
+
+    /// Check the revocation status of certificates according to `revocation`.
+    pub fn with_revocation(mut self, revocation: RevocationOptions<'a>) -> Self {
+        self.verifier.revocation = Some(revocation);
+        self
+    }
+
+    /// Customize how paths are built and checked.
+    ///
+    /// A trace set with [`VerificationOptions::with_trace()`] cannot be shared between threads,
+    /// so it is not kept. Neither are stapled OCSP responses, which are specific to each
+    /// verification; use [`Verifier::verify_with()`] for both. A trust store or intermediate
+    /// pool set with [`VerifierBuilder::with_trust_store()`] or
+    /// [`VerifierBuilder::with_intermediate_pool()`] is kept unless `options` sets another one.
+    pub fn with_options(mut self, options: VerificationOptions<'a>) -> Self {
+        #[cfg(feature = "alloc")]
+        let previous = self.verifier.options;
+        self.verifier.options = options.path;
+        #[cfg(feature = "alloc")]
+        {
+            let path = &mut self.verifier.options;
+            path.trust_store = path.trust_store.or(previous.trust_store);
+            path.intermediate_pool = path.intermediate_pool.or(previous.intermediate_pool);
+        }
+        self
+    }
+
+    /// Construct a [`Verifier`] based on the builder's configuration.
+    pub fn build(self) -> Verifier<'a> {
+        self.verifier
+    }
+}
+
+#[cfg(all(test, feature = "alloc", any(feature = "ring", feature = "aws-lc-rs")))]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_end_entity, make_issuer};
+    use crate::trace::TraceLog;
+    use crate::trust_anchor::anchor_from_trusted_cert;
+    use crate::verify_cert::Budget;
+    use std::prelude::v1::*;
+
+    #[test]
+    fn verifier() {
+        let trust_anchor = make_issuer("Trust Anchor");
+        let anchors = [anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap()];
+        let intermediate = make_issuer("Intermediate");
+        let ee = make_end_entity(&trust_anchor.cert, &trust_anchor.key_pair);
+        let ee_cert = EndEntityCert::try_from(ee.cert.der()).unwrap();
+        let time = UnixTime::since_unix_epoch(core::time::Duration::from_secs(0x1fed_f00d));
+
+        let verifier = Verifier::builder(
+            crate::ALL_VERIFICATION_ALGS,
+            &anchors,
+            KeyUsage::server_auth(),
+        )
+        .build();
+        let intermediates = [intermediate.cert.der().clone()];
+        let path = verifier.verify(&ee_cert, &intermediates, time).unwrap();
+        assert_eq!(path.anchor().subject, anchors[0].subject);
+
+        // The verifier can be used from several threads at once.
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                scope.spawn(|| assert!(verifier.verify(&ee_cert, &[], time).is_ok()));
+            }
+        });
+
+        let trace = TraceLog::new();
+        assert!(verifier
+            .verify_with(
+                &ee_cert,
+                &[],
+                time,
+                PerCallOptions::new().with_trace(&trace)
+            )
+            .is_ok());
+        assert!(!trace.into_entries().is_empty());
+
+        // id-kp-codeSigning, which the end-entity certificate does not have.
+        let code_signing = oid!(1, 3, 6, 1, 5, 5, 7, 3, 3);
+        let verifier = Verifier::builder(
+            crate::ALL_VERIFICATION_ALGS,
+            &anchors,
+            KeyUsage::required(&code_signing),
+        )
+        .build();
+        assert_eq!(
+            verifier.verify(&ee_cert, &[], time).map(|_| ()),
+            Err(Error::RequiredEkuNotFound)
+        );
+
+        let verifier = Verifier::builder(
+            crate::ALL_VERIFICATION_ALGS,
+            &anchors,
+            KeyUsage::server_auth(),
+        )
+        .with_options(VerificationOptions::new().with_budget(Budget::default().with_signatures(0)))
+        .build();
+        assert_eq!(
+            verifier.verify(&ee_cert, &[], time).map(|_| ()),
+            Err(Error::MaximumSignatureChecksExceeded)
+        );
+    }
+}
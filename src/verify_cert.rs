@@ -12,38 +12,207 @@
 // ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
+use core::cmp::Ordering;
+#[cfg(not(feature = "alloc"))]
+use core::marker::PhantomData;
 use core::ops::ControlFlow;
+use core::time::Duration;
 
 use pki_types::{CertificateDer, SignatureVerificationAlgorithm, TrustAnchor, UnixTime};
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::algorithm_policy::{AlgorithmPolicies, AlgorithmPolicy};
 use crate::cert::Cert;
-use crate::crl::RevocationOptions;
+use crate::crl::{
+    CertRevocationList, KeyUsageMode, MustStaplePolicy, OcspHasher, RevocationOptions, StapledOcsp,
+};
 use crate::der::{self, FromDer};
+use crate::distrust::Distrust;
 use crate::end_entity::EndEntityCert;
 use crate::error::Error;
+#[cfg(feature = "alloc")]
+use crate::intermediate_pool::IntermediatePool;
+use crate::pinning::SpkiPins;
+#[cfg(feature = "alloc")]
+use crate::policy::PolicyOptions;
+use crate::ranking::PathRanking;
+#[cfg(feature = "std")]
+use crate::signature_cache::SignatureCache;
+use crate::trace::{Candidate, PathCheck, PathTrace, TraceStep};
+use crate::trust_anchor::{ConstrainedTrustAnchor, TrustAnchorConstraints};
+#[cfg(feature = "alloc")]
+use crate::trust_store::TrustStore;
 use crate::{public_values_eq, signed_data, subject_name};
 
 // Use `'a` for lifetimes that we don't care about, `'p` for lifetimes that become a part of
 // the `VerifiedPath`.
 pub(crate) struct ChainOptions<'a, 'p> {
-    pub(crate) eku: KeyUsage,
+    pub(crate) eku: KeyUsage<'p>,
     pub(crate) supported_sig_algs: &'a [&'a dyn SignatureVerificationAlgorithm],
     pub(crate) trust_anchors: &'p [TrustAnchor<'p>],
-    pub(crate) intermediate_certs: &'p [CertificateDer<'p>],
+    #[cfg(feature = "alloc")]
+    pub(crate) trust_store: Option<&'p TrustStore>,
+    #[cfg(feature = "alloc")]
+    pub(crate) intermediate_pool: Option<&'p IntermediatePool>,
+    pub(crate) constrained_anchors: &'p [ConstrainedTrustAnchor<'p>],
+    pub(crate) distrust: &'a [Distrust<'a>],
+    pub(crate) spki_pins: Option<SpkiPins<'a>>,
+    pub(crate) validity: ValidityPolicy,
+    pub(crate) intermediate_eku: IntermediateEkuMode,
+    pub(crate) strict_key_usage: Option<EndEntityKeyUsage>,
+    pub(crate) algorithms: AlgorithmPolicies<'a>,
+    #[cfg(feature = "std")]
+    pub(crate) signature_cache: Option<&'a SignatureCache<'a>>,
+    pub(crate) intermediates: SuppliedIntermediates<'p>,
     pub(crate) revocation: Option<RevocationOptions<'a>>,
+    pub(crate) stapled_ocsp: StapledOcsp<'a>,
+    pub(crate) must_staple: MustStaplePolicy,
+    pub(crate) ocsp_hashers: &'a [&'a dyn OcspHasher],
+    pub(crate) budget: Budget,
+    pub(crate) ranking: Option<&'a dyn PathRanking>,
+    pub(crate) max_path_depth: usize,
+    pub(crate) trace: Option<&'a dyn PathTrace>,
+    #[cfg(feature = "alloc")]
+    pub(crate) policy: Option<PolicyOptions<'a>>,
 }
 
 impl<'a, 'p: 'a> ChainOptions<'a, 'p> {
+    /// Combine the arguments given to each of the verification entry points with `options`.
+    pub(crate) fn new(
+        supported_sig_algs: &'a [&'a dyn SignatureVerificationAlgorithm],
+        trust_anchors: &'p [TrustAnchor<'p>],
+        intermediate_certs: &'p [CertificateDer<'p>],
+        usage: KeyUsage<'p>,
+        revocation: Option<RevocationOptions<'a>>,
+        options: &VerificationOptions<'p>,
+    ) -> Self {
+        Self {
+            eku: usage,
+            supported_sig_algs,
+            trust_anchors,
+            #[cfg(feature = "alloc")]
+            trust_store: options.path.trust_store,
+            #[cfg(feature = "alloc")]
+            intermediate_pool: options.path.intermediate_pool,
+            constrained_anchors: options.path.constrained_anchors,
+            distrust: options.path.distrust,
+            spki_pins: options.path.spki_pins,
+            validity: options.path.validity,
+            intermediate_eku: options.path.intermediate_eku,
+            strict_key_usage: options.path.strict_key_usage,
+            algorithms: options.path.algorithms,
+            #[cfg(feature = "std")]
+            signature_cache: options.path.signature_cache,
+            intermediates: SuppliedIntermediates::new(
+                intermediate_certs,
+                #[cfg(feature = "alloc")]
+                options.path.intermediate_pool,
+            ),
+            revocation,
+            stapled_ocsp: options.stapled_ocsp,
+            must_staple: options.path.must_staple,
+            ocsp_hashers: options.path.ocsp_hashers,
+            budget: options.path.budget,
+            ranking: options.path.ranking,
+            max_path_depth: options.path.max_path_depth,
+            trace: options.trace,
+            #[cfg(feature = "alloc")]
+            policy: options.path.policy,
+        }
+    }
+
     pub(crate) fn build_chain(
         &self,
         end_entity: &'p EndEntityCert<'p>,
         time: UnixTime,
         verify_path: Option<&dyn Fn(&VerifiedPath<'_>) -> Result<(), Error>>,
     ) -> Result<VerifiedPath<'p>, Error> {
-        let mut path = PartialPath::new(end_entity);
-        match self.build_chain_inner(&mut path, time, verify_path, 0, &mut Budget::default()) {
-            Ok(anchor) => Ok(VerifiedPath::new(end_entity, anchor, path)),
-            Err(ControlFlow::Break(err)) | Err(ControlFlow::Continue(err)) => Err(err),
+        let mut path = PartialPath::new(end_entity, self.max_path_depth);
+        let mut budget = self.budget;
+        let mut best = None;
+        let mut found = None;
+        let mut verify_candidate =
+            |partial: &PartialPath<'p>, anchor: &'p TrustAnchor<'p>, details: &PathDetails<'p>| {
+                let candidate = partial.candidate(anchor, details.clone());
+                if let Some(verify) = verify_path {
+                    verify(&candidate).map_err(ControlFlow::Continue)?;
+                }
+
+                let ranking = match self.ranking {
+                    Some(ranking) => ranking,
+                    None => {
+                        found = Some(details.clone());
+                        return Ok(());
+                    }
+                };
+
+                let better = match &best {
+                    Some(best) => ranking.compare(&candidate, best) == Ordering::Greater,
+                    None => true,
+                };
+                if better {
+                    best = Some(VerifiedPath::new(
+                        end_entity,
+                        anchor,
+                        partial.clone(),
+                        details.clone(),
+                    ));
+                }
+
+                // Reject every candidate when ranking, so that path building carries on
+                // looking for a better path.
+                Err(ControlFlow::Continue(Error::UnknownIssuer))
+            };
+
+        let result = self.build_chain_inner(&mut path, time, &mut verify_candidate, 0, &mut budget);
+        match (result, best) {
+            (Ok(anchor), _) => {
+                let mut details = found.unwrap_or_default();
+                details.budget_usage = budget.usage(&self.budget);
+                Ok(VerifiedPath::new(end_entity, anchor, path, details))
+            }
+            // Any valid path found before running out of budget is still valid, even if
+            // it may not be the best one.
+            (Err(_), Some(mut best)) => {
+                best.details.budget_usage = budget.usage(&self.budget);
+                Ok(best)
+            }
+            (Err(ControlFlow::Break(err)) | Err(ControlFlow::Continue(err)), None) => Err(err),
+        }
+    }
+
+    /// Build every path from `end_entity` to a trust anchor, calling `visit` for each of them.
+    ///
+    /// Returns the number of paths visited, or the most specific error if there are none.
+    pub(crate) fn for_each_chain(
+        &self,
+        end_entity: &'p EndEntityCert<'p>,
+        time: UnixTime,
+        visit: &mut dyn FnMut(&VerifiedPath<'_>) -> ControlFlow<()>,
+    ) -> Result<usize, Error> {
+        let mut path = PartialPath::new(end_entity, self.max_path_depth);
+        let mut budget = self.budget;
+        let mut visited = 0;
+        let mut visit_candidate =
+            |partial: &PartialPath<'p>, anchor: &'p TrustAnchor<'p>, details: &PathDetails<'p>| {
+                visited += 1;
+                // Every candidate is rejected after being visited, so that path building
+                // carries on looking for more paths.
+                match visit(&partial.candidate(anchor, details.clone())) {
+                    ControlFlow::Continue(()) => Err(ControlFlow::Continue(Error::UnknownIssuer)),
+                    ControlFlow::Break(()) => Err(ControlFlow::Break(Error::UnknownIssuer)),
+                }
+            };
+
+        match self.build_chain_inner(&mut path, time, &mut visit_candidate, 0, &mut budget) {
+            // Running out of budget means there may be paths that were not visited.
+            Err(ControlFlow::Break(err)) if err.is_fatal() => Err(err),
+            Err(ControlFlow::Break(err)) | Err(ControlFlow::Continue(err)) if visited == 0 => {
+                Err(err)
+            }
+            _ => Ok(visited),
         }
     }
 
@@ -51,45 +220,83 @@ impl<'a, 'p: 'a> ChainOptions<'a, 'p> {
         &self,
         path: &mut PartialPath<'p>,
         time: UnixTime,
-        verify_path: Option<&dyn Fn(&VerifiedPath<'_>) -> Result<(), Error>>,
+        verify_path: &mut dyn FnMut(
+            &PartialPath<'p>,
+            &'p TrustAnchor<'p>,
+            &PathDetails<'p>,
+        ) -> Result<(), ControlFlow<Error, Error>>,
         sub_ca_count: usize,
         budget: &mut Budget,
     ) -> Result<&'p TrustAnchor<'p>, ControlFlow<Error, Error>> {
         let role = path.node().role();
 
-        check_issuer_independent_properties(path.head(), time, role, sub_ca_count, self.eku.inner)?;
-
-        // TODO: HPKP checks.
-
-        let result =
-            loop_while_non_fatal_error(Error::UnknownIssuer, self.trust_anchors, |trust_anchor| {
-                let trust_anchor_subject = untrusted::Input::from(trust_anchor.subject.as_ref());
-                if !public_values_eq(path.head().issuer, trust_anchor_subject) {
-                    return Err(Error::UnknownIssuer.into());
-                }
-
-                // TODO: check_distrust(trust_anchor_subject, trust_anchor_spki)?;
+        check_issuer_independent_properties(
+            path.head(),
+            time,
+            &self.validity,
+            role,
+            sub_ca_count,
+            self.eku.inner,
+            self.intermediate_eku,
+            self.strict_key_usage,
+            &self.algorithms,
+        )?;
+
+        self.check_head_pinned(path);
+        self.find_issuer(path, time, verify_path, sub_ca_count, budget)
+    }
 
-                let node = path.node();
-                self.check_signed_chain(&node, time, trust_anchor, budget)?;
-                check_signed_chain_name_constraints(&node, trust_anchor, budget)?;
+    /// Note whether the head of `path` has a pinned SPKI, so that each certificate is only
+    /// hashed once however many trust anchors are tried to complete the path.
+    fn check_head_pinned(&self, path: &mut PartialPath<'p>) {
+        if let Some(spki_pins) = &self.spki_pins {
+            if path.pinned.is_none() && spki_pins.contains(path.head().spki) {
+                path.pinned = Some(path.used);
+            }
+        }
+    }
 
-                let verify = match verify_path {
-                    Some(verify) => verify,
-                    None => return Ok(trust_anchor),
-                };
+    /// Extend `path`, the head of which has already been checked, towards a trust anchor.
+    fn find_issuer(
+        &self,
+        path: &mut PartialPath<'p>,
+        time: UnixTime,
+        verify_path: &mut dyn FnMut(
+            &PartialPath<'p>,
+            &'p TrustAnchor<'p>,
+            &PathDetails<'p>,
+        ) -> Result<(), ControlFlow<Error, Error>>,
+        sub_ca_count: usize,
+        budget: &mut Budget,
+    ) -> Result<&'p TrustAnchor<'p>, ControlFlow<Error, Error>> {
+        // Candidates are visited in order of how well their subject key identifier matches the
+        // authority key identifier of the current head, so that when several CAs share the same
+        // subject name (e.g. after a rekey, or when cross-signed) we spend signature checks on the
+        // most likely issuer first.
+        let (issuer, akid) = (path.head().issuer, path.head().authority_key_identifier());
+        let passes = match akid {
+            Some(_) => KeyIdMatch::SEARCH_ORDER,
+            None => &[KeyIdMatch::Unknown],
+        };
 
-                let candidate = VerifiedPath {
-                    end_entity: path.end_entity,
-                    intermediates: Intermediates::Borrowed(&path.intermediates[..path.used]),
-                    anchor: trust_anchor,
-                };
+        let trust_anchors = passes.iter().flat_map(move |pass| {
+            self.trust_anchors_for(issuer)
+                .filter(move |candidate| KeyIdMatch::from_key_ids(akid, candidate.key_id) == *pass)
+        });
 
-                match verify(&candidate) {
-                    Ok(()) => Ok(trust_anchor),
-                    Err(err) => Err(ControlFlow::Continue(err)),
-                }
-            });
+        let result = loop_while_non_fatal_error(Error::UnknownIssuer, trust_anchors, |candidate| {
+            let result = self.try_trust_anchor(path, time, candidate, verify_path, budget);
+            self.trace(
+                path,
+                path.used,
+                Candidate::trust_anchor(candidate.anchor),
+                &result,
+            );
+            match result {
+                Ok(()) => Ok(candidate.anchor),
+                Err((_, err)) => Err(err),
+            }
+        });
 
         let err = match result {
             Ok(anchor) => return Ok(anchor),
@@ -101,64 +308,430 @@ impl<'a, 'p: 'a> ChainOptions<'a, 'p> {
             Err(ControlFlow::Continue(err)) => err,
         };
 
-        loop_while_non_fatal_error(err, self.intermediate_certs, |cert_der| {
-            let potential_issuer = Cert::from_der(untrusted::Input::from(cert_der))?;
-            if !public_values_eq(potential_issuer.subject, path.head().issuer) {
-                return Err(Error::UnknownIssuer.into());
-            }
-
-            // Prevent loops; see RFC 4158 section 5.2.
-            if path.node().iter().any(|prev| {
-                public_values_eq(potential_issuer.spki, prev.cert.spki)
-                    && public_values_eq(potential_issuer.subject, prev.cert.subject)
-            }) {
-                return Err(Error::UnknownIssuer.into());
-            }
+        // Each intermediate is parsed and classified once. Without the `alloc` feature there is
+        // nowhere to sort them, so the supplied intermediates are instead scanned once per pass;
+        // those that fail to parse are only yielded in the `KeyIdMatch::Unknown` pass.
+        #[cfg(feature = "alloc")]
+        let candidates = {
+            let mut candidates = self
+                .intermediates
+                .issuers(issuer)
+                .map(|(cert_der, cert)| IssuerCandidate::Supplied(cert_der, cert))
+                .chain(
+                    // Certificates in the pool are looked up by subject, and only those with
+                    // the right subject are parsed.
+                    self.intermediate_pool
+                        .into_iter()
+                        .flat_map(move |pool| pool.issuers(issuer))
+                        .map(IssuerCandidate::Pooled),
+                )
+                .map(|candidate| (candidate.key_id_match(akid), candidate))
+                .collect::<Vec<_>>();
+            candidates.sort_by_key(|(key_id_match, _)| *key_id_match);
+            candidates.into_iter().map(|(_, candidate)| candidate)
+        };
+        #[cfg(not(feature = "alloc"))]
+        let candidates = passes.iter().flat_map(move |pass| {
+            self.intermediates
+                .issuers(issuer)
+                .map(|(cert_der, cert)| IssuerCandidate::Supplied(cert_der, cert))
+                .filter(move |candidate| candidate.key_id_match(akid) == *pass)
+        });
 
-            let next_sub_ca_count = match role {
-                Role::EndEntity => sub_ca_count,
-                Role::Issuer => sub_ca_count + 1,
+        loop_while_non_fatal_error(err, candidates, |candidate| {
+            let depth = path.used;
+            let (cert_der, potential_issuer) = match candidate {
+                IssuerCandidate::Supplied(cert_der, Ok(cert)) => {
+                    (CertificateDer::from(cert_der.as_ref()), cert)
+                }
+                IssuerCandidate::Supplied(cert_der, Err(err)) => {
+                    let result = Err((PathCheck::Parse, err.into()));
+                    self.trace(path, depth, Candidate::intermediate(cert_der), &result);
+                    return result.map_err(|(_, err)| err);
+                }
+                #[cfg(feature = "alloc")]
+                IssuerCandidate::Pooled(cert) => (cert.der(), cert),
             };
 
-            budget.consume_build_chain_call()?;
-            path.push(potential_issuer)?;
-            let result = self.build_chain_inner(path, time, verify_path, next_sub_ca_count, budget);
-            if result.is_err() {
-                path.pop();
+            let result = self.try_intermediate(
+                path,
+                time,
+                potential_issuer,
+                verify_path,
+                sub_ca_count,
+                budget,
+            );
+            self.trace(path, depth, Candidate::intermediate(&cert_der), &result);
+            result.map_err(|(_, err)| err)
+        })
+    }
+
+    /// The trust anchors with the subject `subject`, from each of the sources of trust anchors.
+    ///
+    /// Note: a `TrustAnchor` carries no subject key identifier, so those given as a slice are
+    /// always considered in the `KeyIdMatch::Unknown` pass, in the order given.
+    fn trust_anchors_for<'s>(
+        &self,
+        subject: untrusted::Input<'s>,
+    ) -> impl Iterator<Item = AnchorCandidate<'p>> + 's
+    where
+        'p: 's,
+    {
+        let subject_eq = move |anchor: &TrustAnchor<'_>| {
+            public_values_eq(subject, untrusted::Input::from(anchor.subject.as_ref()))
+        };
+        let trust_anchors = self
+            .trust_anchors
+            .iter()
+            .filter(move |anchor| subject_eq(anchor))
+            .map(|anchor| AnchorCandidate {
+                anchor,
+                key_id: None,
+                constraints: None,
+            });
+
+        let constrained_anchors = self
+            .constrained_anchors
+            .iter()
+            .filter(move |constrained| subject_eq(&constrained.anchor))
+            .map(|constrained| AnchorCandidate {
+                anchor: &constrained.anchor,
+                key_id: None,
+                constraints: Some(&constrained.constraints),
+            });
+        let trust_anchors = trust_anchors.chain(constrained_anchors);
+
+        // The trust store looks up trust anchors by subject, and keeps their key identifiers.
+        #[cfg(feature = "alloc")]
+        let trust_anchors = trust_anchors.chain(
+            self.trust_store
+                .into_iter()
+                .flat_map(move |store| store.issuers(subject))
+                .map(|(anchor, key_id, constraints)| AnchorCandidate {
+                    anchor,
+                    key_id,
+                    constraints,
+                }),
+        );
+
+        trust_anchors
+    }
+
+    /// Try to complete `path` with the trust anchor `candidate`, returning the check that
+    /// failed, if any.
+    fn try_trust_anchor(
+        &self,
+        path: &PartialPath<'p>,
+        time: UnixTime,
+        candidate: AnchorCandidate<'p>,
+        verify_path: &mut dyn FnMut(
+            &PartialPath<'p>,
+            &'p TrustAnchor<'p>,
+            &PathDetails<'p>,
+        ) -> Result<(), ControlFlow<Error, Error>>,
+        budget: &mut Budget,
+    ) -> Result<(), (PathCheck, ControlFlow<Error, Error>)> {
+        let trust_anchor = candidate.anchor;
+        self.check_distrust(
+            path,
+            untrusted::Input::from(trust_anchor.subject.as_ref()),
+            untrusted::Input::from(trust_anchor.subject_public_key_info.as_ref()),
+        )
+        .map_err(|err| (PathCheck::Distrust, err.into()))?;
+
+        self.algorithms
+            .trust_anchor
+            .check_key(untrusted::Input::from(
+                trust_anchor.subject_public_key_info.as_ref(),
+            ))
+            .map_err(|err| (PathCheck::Properties, err.into()))?;
+
+        let constraints = candidate.constraints;
+        if let Some(constraints) = constraints {
+            check_anchor_constraints(
+                constraints,
+                time,
+                &self.validity,
+                path.used,
+                self.eku.inner,
+                self.strict_key_usage.is_some(),
+            )
+            .map_err(|err| (PathCheck::AnchorConstraints, err.into()))?;
+        }
+
+        // The certificates in the path were checked against the pins as they were added.
+        if let Some(spki_pins) = &self.spki_pins {
+            let anchor_spki = untrusted::Input::from(trust_anchor.subject_public_key_info.as_ref());
+            if path.pinned.is_none() && !spki_pins.contains(anchor_spki) {
+                return Err((PathCheck::Pins, Error::SpkiPinMismatch.into()));
             }
+        }
+
+        let key_purpose = self
+            .key_purpose(path, constraints)
+            .map_err(|err| (PathCheck::KeyPurpose, err.into()))?;
+
+        let node = path.node();
+        self.check_signed_chain(&node, time, trust_anchor, constraints, budget)?;
+        check_signed_chain_name_constraints(&node, trust_anchor, budget)
+            .map_err(|err| (PathCheck::NameConstraints, err))?;
+        let mut details = self
+            .path_details(path, budget)
+            .map_err(|err| (PathCheck::Policy, err.into()))?;
+        details.key_purpose = key_purpose;
+
+        verify_path(path, trust_anchor, &details).map_err(|err| (PathCheck::VerifyPath, err))
+    }
 
-            result
+    /// Find the first of the acceptable key purposes that every certificate in `path`, and
+    /// the `constraints` of its trust anchor, allow.
+    ///
+    /// Each certificate allows at least one of the acceptable key purposes, but they may not
+    /// all allow the same one. Yields `None` if any key purpose is acceptable.
+    fn key_purpose(
+        &self,
+        path: &PartialPath<'p>,
+        constraints: Option<&TrustAnchorConstraints<'_>>,
+    ) -> Result<Option<&'p [u8]>, Error> {
+        let eku = self.eku.inner;
+        if let ExtendedKeyUsage::Any = eku {
+            return Ok(None);
+        }
+
+        let anchor_eku = constraints
+            .and_then(|constraints| constraints.extended_key_usage.as_ref())
+            .map(|eku| untrusted::Input::from(eku.as_ref()));
+
+        eku.purposes()
+            .find(|purpose| {
+                let certs_allow = (0..=path.used).all(|idx| {
+                    let role = match idx {
+                        0 => Role::EndEntity,
+                        _ => Role::Issuer,
+                    };
+                    eku.allows(*purpose, path.get(idx).eku, role, self.intermediate_eku)
+                });
+                let anchor_allows = match anchor_eku {
+                    Some(anchor_eku) => eku_contains(anchor_eku, *purpose, false),
+                    None => true,
+                };
+                certs_allow && anchor_allows
+            })
+            .map(|purpose| Some(purpose.as_slice_less_safe()))
+            .ok_or(Error::RequiredEkuNotFound)
+    }
+
+    /// Determine the [`PathDetails`] of `path`, processing its certificate policies if enabled.
+    fn path_details(
+        &self,
+        path: &PartialPath<'p>,
+        budget: &Budget,
+    ) -> Result<PathDetails<'p>, Error> {
+        let budget_usage = budget.usage(&self.budget);
+
+        // From the certificate issued by the trust anchor, to the end-entity.
+        let mut certs = (0..=path.used).rev().map(|idx| path.get(idx));
+
+        #[cfg(feature = "alloc")]
+        if let Some(policy) = &self.policy {
+            return Ok(PathDetails {
+                budget_usage,
+                policies: Some(policy.process(&certs.collect::<Vec<_>>())?),
+                ..PathDetails::default()
+            });
+        }
+
+        // Without policy processing, a critical policy extension can't be honoured.
+        if certs.any(|cert| cert.critical_policy_extension) {
+            return Err(Error::UnsupportedCriticalExtension);
+        }
+
+        Ok(PathDetails {
+            budget_usage,
+            ..PathDetails::default()
         })
     }
 
+    /// Try to extend `path` with `potential_issuer`, returning the check that failed, if any.
+    fn try_intermediate(
+        &self,
+        path: &mut PartialPath<'p>,
+        time: UnixTime,
+        potential_issuer: Cert<'p>,
+        verify_path: &mut dyn FnMut(
+            &PartialPath<'p>,
+            &'p TrustAnchor<'p>,
+            &PathDetails<'p>,
+        ) -> Result<(), ControlFlow<Error, Error>>,
+        sub_ca_count: usize,
+        budget: &mut Budget,
+    ) -> Result<&'p TrustAnchor<'p>, (PathCheck, ControlFlow<Error, Error>)> {
+        // Prevent loops; see RFC 4158 section 5.2.
+        if path.node().iter().any(|prev| {
+            public_values_eq(potential_issuer.spki, prev.cert.spki)
+                && public_values_eq(potential_issuer.subject, prev.cert.subject)
+        }) {
+            return Err((PathCheck::Loop, Error::UnknownIssuer.into()));
+        }
+
+        self.check_distrust(path, potential_issuer.subject, potential_issuer.spki)
+            .map_err(|err| (PathCheck::Distrust, err.into()))?;
+
+        let next_sub_ca_count = match path.node().role() {
+            Role::EndEntity => sub_ca_count,
+            Role::Issuer => sub_ca_count + 1,
+        };
+
+        budget
+            .consume_build_chain_call()
+            .map_err(|err| (PathCheck::Budget, err.into()))?;
+        path.push(potential_issuer)
+            .map_err(|err| (PathCheck::PathDepth, err))?;
+
+        let result = check_issuer_independent_properties(
+            path.head(),
+            time,
+            &self.validity,
+            Role::Issuer,
+            next_sub_ca_count,
+            self.eku.inner,
+            self.intermediate_eku,
+            self.strict_key_usage,
+            &self.algorithms,
+        )
+        .map_err(|err| (PathCheck::Properties, err.into()))
+        .and_then(|()| {
+            self.check_head_pinned(path);
+            self.find_issuer(path, time, verify_path, next_sub_ca_count, budget)
+                .map_err(|err| (PathCheck::Issuers, err))
+        });
+
+        if result.is_err() {
+            path.pop();
+        }
+
+        result
+    }
+
+    /// Check whether the CA with the given `subject` and `spki` is distrusted for the
+    /// end-entity certificate of `path`.
+    fn check_distrust(
+        &self,
+        path: &PartialPath<'_>,
+        subject: untrusted::Input<'_>,
+        spki: untrusted::Input<'_>,
+    ) -> Result<(), Error> {
+        self.distrust
+            .iter()
+            .try_for_each(|distrust| distrust.check(subject, spki, path.get(0)))
+    }
+
+    /// Record the outcome of trying `issuer` as the issuer of the certificate at `depth` in
+    /// `path`, if tracing is enabled.
+    fn trace<T>(
+        &self,
+        path: &PartialPath<'_>,
+        depth: usize,
+        issuer: Candidate<'_>,
+        result: &Result<T, (PathCheck, ControlFlow<Error, Error>)>,
+    ) {
+        let trace = match self.trace {
+            Some(trace) => trace,
+            None => return,
+        };
+
+        trace.record(&TraceStep {
+            depth,
+            cert: path.get(depth),
+            issuer,
+            result: match result {
+                Ok(_) => Ok(()),
+                Err((check, ControlFlow::Break(err) | ControlFlow::Continue(err))) => {
+                    Err((*check, err.clone()))
+                }
+            },
+        });
+    }
+
+    /// Verify `signed_data` using the key in `spki_value`, unless the signature cache shows
+    /// it was already verified.
+    fn verify_signed_data(
+        &self,
+        spki_value: untrusted::Input<'_>,
+        signed_data: &signed_data::SignedData<'_>,
+        budget: &mut Budget,
+    ) -> Result<(), Error> {
+        #[cfg(feature = "std")]
+        if let Some(cache) = self.signature_cache {
+            return cache.verify_signed_data(
+                self.supported_sig_algs,
+                spki_value,
+                signed_data,
+                budget,
+            );
+        }
+
+        signed_data::verify_signed_data(self.supported_sig_algs, spki_value, signed_data, budget)
+    }
+
     fn check_signed_chain(
         &self,
         path: &PathNode<'_>,
         time: UnixTime,
         trust_anchor: &TrustAnchor<'_>,
+        constraints: Option<&TrustAnchorConstraints<'_>>,
         budget: &mut Budget,
-    ) -> Result<(), ControlFlow<Error, Error>> {
+    ) -> Result<(), (PathCheck, ControlFlow<Error, Error>)> {
         let mut spki_value = untrusted::Input::from(trust_anchor.subject_public_key_info.as_ref());
         let mut issuer_subject = untrusted::Input::from(trust_anchor.subject.as_ref());
-        let mut issuer_key_usage = None; // TODO(XXX): Consider whether to track TrustAnchor KU.
+        let mut issuer_key_usage = constraints
+            .and_then(|constraints| constraints.key_usage.as_ref())
+            .map(|ku| untrusted::Input::from(ku.as_ref()));
         for path in path.iter() {
-            signed_data::verify_signed_data(
-                self.supported_sig_algs,
-                spki_value,
-                &path.cert.signed_data,
-                budget,
-            )?;
+            self.verify_signed_data(spki_value, &path.cert.signed_data, budget)
+                .map_err(|err| (PathCheck::Signature, err.into()))?;
 
-            if let Some(revocation_opts) = &self.revocation {
-                revocation_opts.check(
+            let stapled = self
+                .stapled_ocsp
+                .check(
                     &path,
                     issuer_subject,
                     spki_value,
-                    issuer_key_usage,
+                    self.ocsp_hashers,
                     self.supported_sig_algs,
+                    &self.algorithms.crl,
                     budget,
                     time,
-                )?;
+                )
+                .map_err(|err| (PathCheck::Revocation, err.into()))?;
+
+            if let (None, Role::EndEntity, MustStaplePolicy::Enforce) =
+                (&stapled, path.role(), self.must_staple)
+            {
+                if path.cert.must_staple() {
+                    return Err((
+                        PathCheck::Revocation,
+                        Error::RequiredOcspStapleNotFound.into(),
+                    ));
+                }
+            }
+
+            // A good stapled response is enough to consider the certificate checked.
+            if let (None, Some(revocation_opts)) = (stapled, &self.revocation) {
+                revocation_opts
+                    .check(
+                        &path,
+                        issuer_subject,
+                        spki_value,
+                        issuer_key_usage,
+                        &|crl, budget| self.verify_indirect_crl(crl, time, budget),
+                        self.ocsp_hashers,
+                        self.supported_sig_algs,
+                        &self.algorithms.crl,
+                        budget,
+                        time,
+                    )
+                    .map_err(|err| (PathCheck::Revocation, err.into()))?;
             }
 
             spki_value = path.cert.spki;
@@ -168,6 +741,145 @@ impl<'a, 'p: 'a> ChainOptions<'a, 'p> {
 
         Ok(())
     }
+
+    /// Verify the signature of `crl`, an indirect CRL issued by a CRL issuer other than the
+    /// certificate issuer.
+    ///
+    /// The CRL issuer may be one of the trust anchors, or one of the intermediate certificates
+    /// with a valid path to a trust anchor. Either must allow the cRLSign key usage.
+    fn verify_indirect_crl(
+        &self,
+        crl: &CertRevocationList<'_>,
+        time: UnixTime,
+        budget: &mut Budget,
+    ) -> Result<(), Error> {
+        let crl_issuer = untrusted::Input::from(crl.issuer());
+        let mut error = None;
+        let mut most_specific = |err: Error| {
+            error = Some(match error.take() {
+                Some(error) => Error::most_specific(error, err),
+                None => err,
+            });
+        };
+
+        for candidate in self.trust_anchors_for(crl_issuer) {
+            let key_usage = candidate
+                .constraints
+                .and_then(|constraints| constraints.key_usage.as_ref())
+                .map(|ku| untrusted::Input::from(ku.as_ref()));
+            let spki = untrusted::Input::from(candidate.anchor.subject_public_key_info.as_ref());
+            let result = KeyUsageMode::CrlSign.check(key_usage).and_then(|()| {
+                crl.verify_signature(self.supported_sig_algs, &self.algorithms.crl, spki, budget)
+            });
+            match result {
+                Ok(()) => return Ok(()),
+                Err(err) if err.is_fatal() => return Err(err),
+                Err(err) => most_specific(err),
+            }
+        }
+
+        let candidates = self
+            .intermediates
+            .issuers(crl_issuer)
+            .filter_map(|(_, cert)| cert.ok());
+        #[cfg(feature = "alloc")]
+        let candidates = candidates.chain(
+            self.intermediate_pool
+                .into_iter()
+                .flat_map(|pool| pool.issuers(crl_issuer)),
+        );
+        for cert in candidates.filter(|cert| public_values_eq(cert.subject, crl_issuer)) {
+            let result = KeyUsageMode::CrlSign
+                .check(cert.key_usage)
+                .and_then(|()| {
+                    crl.verify_signature(
+                        self.supported_sig_algs,
+                        &self.algorithms.crl,
+                        cert.spki,
+                        budget,
+                    )
+                })
+                .and_then(|()| {
+                    // Only report why the CRL issuer is not valid if it is fatal; otherwise it
+                    // could be mistaken for a problem with the certificate being checked.
+                    self.check_crl_issuer(&cert, time, budget)
+                        .map_err(|err| match err.is_fatal() {
+                            true => err,
+                            false => Error::UnknownCrlIssuer,
+                        })
+                });
+            match result {
+                Ok(()) => return Ok(()),
+                Err(err) if err.is_fatal() => return Err(err),
+                Err(err) => most_specific(err),
+            }
+        }
+
+        Err(error.unwrap_or(Error::UnknownCrlIssuer))
+    }
+
+    /// Check that `cert`, the certificate of a CRL issuer, has a valid path to a trust anchor.
+    ///
+    /// The path is built like a path for an end-entity certificate, except that `cert` may be a
+    /// CA certificate, that it is not checked for revocation, and that the extended key usage
+    /// of the certificates in it is not checked.
+    fn check_crl_issuer(
+        &self,
+        cert: &Cert<'_>,
+        time: UnixTime,
+        budget: &mut Budget,
+    ) -> Result<(), Error> {
+        let algorithms = &self.algorithms.intermediate;
+        algorithms.check_signature_algorithm(cert.signed_data.algorithm)?;
+        algorithms.check_key(cert.spki)?;
+        cert.validity.read_all(Error::BadDer, |value| {
+            check_validity(value, time, Role::Issuer, &self.validity)
+        })?;
+        // The CRL issuer may or may not be a CA certificate, and is at the bottom of its path,
+        // so its pathLenConstraint, if any, can't be violated. Its basic constraints must still
+        // be well-formed.
+        untrusted::read_all_optional(cert.basic_constraints, Error::BadDer, |value| {
+            read_basic_constraints(value).map(|_| ())
+        })?;
+
+        let der = cert.der();
+        let crl_issuer = EndEntityCert::try_from(&der)?;
+        let options = ChainOptions {
+            eku: KeyUsage::any(),
+            supported_sig_algs: self.supported_sig_algs,
+            trust_anchors: self.trust_anchors,
+            #[cfg(feature = "alloc")]
+            trust_store: self.trust_store,
+            #[cfg(feature = "alloc")]
+            intermediate_pool: self.intermediate_pool,
+            constrained_anchors: self.constrained_anchors,
+            distrust: self.distrust,
+            spki_pins: None,
+            validity: self.validity,
+            intermediate_eku: IntermediateEkuMode::Ignore,
+            strict_key_usage: None,
+            algorithms: self.algorithms,
+            #[cfg(feature = "std")]
+            signature_cache: self.signature_cache,
+            intermediates: self.intermediates.clone(),
+            revocation: None,
+            stapled_ocsp: StapledOcsp::default(),
+            must_staple: MustStaplePolicy::Ignore,
+            ocsp_hashers: &[],
+            budget: *budget,
+            ranking: None,
+            max_path_depth: self.max_path_depth,
+            trace: None,
+            #[cfg(feature = "alloc")]
+            policy: None,
+        };
+
+        let mut path = PartialPath::new(&crl_issuer, self.max_path_depth);
+        match options.find_issuer(&mut path, time, &mut |_, _, _| Ok(()), 0, budget) {
+            Ok(_) => Ok(()),
+            Err(ControlFlow::Break(err)) | Err(ControlFlow::Continue(err)) => Err(err),
+        }
+    }
 }
 
 /// Path from end-entity certificate to trust anchor that's been verified.
@@ -177,6 +889,7 @@ pub struct VerifiedPath<'p> {
     end_entity: &'p EndEntityCert<'p>,
     intermediates: Intermediates<'p>,
     anchor: &'p TrustAnchor<'p>,
+    details: PathDetails<'p>,
 }
 
 impl<'p> VerifiedPath<'p> {
@@ -184,6 +897,7 @@ impl<'p> VerifiedPath<'p> {
         end_entity: &'p EndEntityCert<'p>,
         anchor: &'p TrustAnchor<'p>,
         partial: PartialPath<'p>,
+        details: PathDetails<'p>,
     ) -> Self {
         Self {
             end_entity,
@@ -192,6 +906,7 @@ impl<'p> VerifiedPath<'p> {
                 used: partial.used,
             },
             anchor,
+            details,
         }
     }
 
@@ -211,6 +926,46 @@ impl<'p> VerifiedPath<'p> {
     pub fn anchor(&self) -> &'p TrustAnchor<'p> {
         self.anchor
     }
+
+    /// Yields how much of the path building [`Budget`] was used to find this path.
+    ///
+    /// This includes the work spent on candidate paths that were rejected before this one
+    /// was found.
+    pub fn budget_usage(&self) -> BudgetUsage {
+        self.details.budget_usage
+    }
+
+    /// Yields the OID of the extended key usage purpose for which this path was verified.
+    ///
+    /// This is the first of the purposes acceptable to the [`KeyUsage`] given for verification
+    /// that every certificate in the path allows. Certificates without an extended key usage
+    /// extension allow every purpose, unless it is required by [`KeyUsage::required()`] or
+    /// [`KeyUsage::required_any_of()`].
+    pub fn key_purpose(&self) -> Option<&'p [u8]> {
+        self.details.key_purpose
+    }
+
+    /// Yields the certificate policies in force for this path, if certificate policy processing
+    /// was enabled using [`VerificationOptions::with_policies()`].
+    ///
+    /// These are the policies, of those that are acceptable, for which the path is valid. If
+    /// they include [`PolicyOptions::ANY_POLICY`], the path is valid for every acceptable policy.
+    #[cfg(feature = "alloc")]
+    pub fn policies(&self) -> Option<&[&'p [u8]]> {
+        self.details.policies.as_deref()
+    }
+}
+
+/// What was determined about a candidate path while checking it, which becomes part of the
+/// [`VerifiedPath`].
+#[derive(Clone, Default)]
+pub(crate) struct PathDetails<'p> {
+    budget_usage: BudgetUsage,
+    key_purpose: Option<&'p [u8]>,
+    #[cfg(feature = "alloc")]
+    policies: Option<Vec<&'p [u8]>>,
+    #[cfg(not(feature = "alloc"))]
+    policies: PhantomData<&'p [u8]>,
 }
 
 /// Iterator over a path's intermediate certificates.
@@ -249,17 +1004,14 @@ impl DoubleEndedIterator for IntermediateIterator<'_> {
 
 #[allow(clippy::large_enum_variant)]
 enum Intermediates<'a> {
-    Owned {
-        certs: [Option<Cert<'a>>; MAX_SUB_CA_COUNT],
-        used: usize,
-    },
+    Owned { certs: PathBuffer<'a>, used: usize },
     Borrowed(&'a [Option<Cert<'a>>]),
 }
 
 impl<'a> AsRef<[Option<Cert<'a>>]> for Intermediates<'a> {
     fn as_ref(&self) -> &[Option<Cert<'a>>] {
         match self {
-            Intermediates::Owned { certs, used } => &certs[..*used],
+            Intermediates::Owned { certs, used } => &certs.as_ref()[..*used],
             Intermediates::Borrowed(certs) => certs,
         }
     }
@@ -286,13 +1038,382 @@ fn check_signed_chain_name_constraints(
     Ok(())
 }
 
-pub(crate) struct Budget {
+/// Check the constraints on a trust anchor that is the issuer of a path with `intermediates`
+/// intermediate certificates.
+fn check_anchor_constraints(
+    constraints: &TrustAnchorConstraints<'_>,
+    time: UnixTime,
+    validity: &ValidityPolicy,
+    intermediates: usize,
+    eku: ExtendedKeyUsage<'_>,
+    strict_key_usage: bool,
+) -> Result<(), Error> {
+    if let Some((not_before, not_after)) = constraints.validity {
+        validity.check_period(time, not_before, not_after)?;
+    }
+
+    if matches!(constraints.path_len, Some(len) if intermediates > len) {
+        return Err(Error::PathLenConstraintViolated);
+    }
+
+    // Unlike for certificates, the absence of an EKU constraint never fails the check.
+    if let Some(extended_key_usage) = &constraints.extended_key_usage {
+        untrusted::Input::from(extended_key_usage.as_ref())
+            .read_all(Error::BadDer, |value| eku.check(Some(value)))?;
+    }
+
+    if strict_key_usage {
+        let key_usage = constraints.key_usage.as_ref();
+        KeyUsageMode::CertSign.check(key_usage.map(|ku| untrusted::Input::from(ku.as_ref())))?;
+    }
+
+    Ok(())
+}
+
+/// Options controlling how path building is performed.
+///
+/// Use with [`EndEntityCert::verify_for_usage_with_options()`]. The [`Default`] options
+/// match the behaviour of [`EndEntityCert::verify_for_usage()`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VerificationOptions<'a> {
+    pub(crate) path: PathOptions<'a>,
+    pub(crate) trace: Option<&'a dyn PathTrace>,
+    pub(crate) stapled_ocsp: StapledOcsp<'a>,
+}
+
+/// The [`VerificationOptions`] other than the trace and stapled OCSP responses, which can be
+/// shared between verifications and threads.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PathOptions<'a> {
+    pub(crate) budget: Budget,
+    pub(crate) ranking: Option<&'a dyn PathRanking>,
+    pub(crate) max_path_depth: usize,
+    #[cfg(feature = "alloc")]
+    pub(crate) policy: Option<PolicyOptions<'a>>,
+    pub(crate) constrained_anchors: &'a [ConstrainedTrustAnchor<'a>],
+    pub(crate) distrust: &'a [Distrust<'a>],
+    pub(crate) spki_pins: Option<SpkiPins<'a>>,
+    pub(crate) validity: ValidityPolicy,
+    pub(crate) intermediate_eku: IntermediateEkuMode,
+    pub(crate) strict_key_usage: Option<EndEntityKeyUsage>,
+    pub(crate) algorithms: AlgorithmPolicies<'a>,
+    #[cfg(feature = "std")]
+    pub(crate) signature_cache: Option<&'a SignatureCache<'a>>,
+    pub(crate) must_staple: MustStaplePolicy,
+    pub(crate) ocsp_hashers: &'a [&'a dyn OcspHasher],
+    #[cfg(feature = "alloc")]
+    pub(crate) trust_store: Option<&'a TrustStore>,
+    #[cfg(feature = "alloc")]
+    pub(crate) intermediate_pool: Option<&'a IntermediatePool>,
+}
+
+impl<'a> VerificationOptions<'a> {
+    /// Create a new [`VerificationOptions`] with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Customize the limits on the work performed while building a path.
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.path.budget = budget;
+        self
+    }
+
+    /// Choose the best of all valid paths according to `ranking`, rather than the first found.
+    ///
+    /// This means path building carries on after finding a valid path, so may use more of
+    /// the [`Budget`]. If the budget runs out after a valid path was found, the best path
+    /// found so far is yielded.
+    pub fn with_ranking(mut self, ranking: &'a dyn PathRanking) -> Self {
+        self.path.ranking = Some(ranking);
+        self
+    }
+
+    /// Customize the maximum number of intermediate certificates in a path.
+    ///
+    /// The default of 6 suits the Web PKI, but some PKIs (e.g. those bridging several
+    /// organisations) legitimately need deeper paths. Paths up to the default depth are built
+    /// without allocating; deeper limits require the `alloc` feature, without which the
+    /// default depth is never exceeded.
+    pub fn with_max_path_depth(mut self, max_path_depth: usize) -> Self {
+        self.path.max_path_depth = max_path_depth;
+        self
+    }
+
+    /// Record every candidate issuer tried while building a path, and why it was rejected.
+    pub fn with_trace(mut self, trace: &'a dyn PathTrace) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// Check the revocation status of the end-entity certificate using `response`, the DER
+    /// encoding of an OCSP response stapled to a TLS handshake (i.e. the `status_request`
+    /// extension).
+    ///
+    /// The response is checked as described for [`OcspResponse`], against the issuer found
+    /// while building each candidate path. A response that fails to parse, or that covers the
+    /// certificate but fails those checks, is ignored as if it were not stapled. If the
+    /// response says the certificate is good, it is considered checked and the
+    /// [`RevocationOptions`] are not consulted for it; if it says it is revoked, the path is
+    /// rejected with [`Error::CertRevoked`]. Otherwise, the [`RevocationOptions`] apply as
+    /// usual, if any, as does the [`MustStaplePolicy`].
+    ///
+    /// [`OcspResponse`]: crate::OcspResponse
+    pub fn with_stapled_ocsp(mut self, response: &'a [u8]) -> Self {
+        self.stapled_ocsp.end_entity = Some(response);
+        self
+    }
+
+    /// Check the revocation status of intermediate certificates using `responses`, the DER
+    /// encodings of OCSP responses stapled to a TLS handshake (i.e. the `status_request_v2`
+    /// extension).
+    ///
+    /// Each intermediate certificate in a candidate path is checked using any of the responses
+    /// that covers it, as described for [`VerificationOptions::with_stapled_ocsp()`].
+    pub fn with_stapled_intermediate_ocsp(mut self, responses: &'a [&'a [u8]]) -> Self {
+        self.stapled_ocsp.intermediates = responses;
+        self
+    }
+
+    /// Customize whether end-entity certificates requiring a stapled OCSP response ("OCSP
+    /// Must-Staple") are rejected without one.
+    ///
+    /// With [`MustStaplePolicy::Enforce`], a path is rejected with
+    /// [`Error::RequiredOcspStapleNotFound`] if its end-entity certificate has a TLS feature
+    /// extension listing `status_request` or `status_request_v2`, and no response set with
+    /// [`VerificationOptions::with_stapled_ocsp()`] says the certificate is good.
+    pub fn with_must_staple_policy(mut self, policy: MustStaplePolicy) -> Self {
+        self.path.must_staple = policy;
+        self
+    }
+
+    /// Match OCSP responses to certificates using `hashers`, e.g. `webpki::ring::OCSP_SHA1`
+    /// and `webpki::ring::OCSP_SHA256`.
+    ///
+    /// Each single response in an OCSP response identifies the issuer of the certificate it
+    /// is about by hashes of the issuer's name and key. A single response is only used if one
+    /// of `hashers` supports its hash algorithm, so without any, OCSP responses given through
+    /// [`RevocationOptions`] or stapled are never used. Most responders use SHA-1.
+    pub fn with_ocsp_hashers(mut self, hashers: &'a [&'a dyn OcspHasher]) -> Self {
+        self.path.ocsp_hashers = hashers;
+        self
+    }
+
+    /// Process certificate policies, as described in RFC 5280 section 6.1.
+    ///
+    /// Paths that fail policy processing are rejected, and the policies in force for the
+    /// verified path are available from [`VerifiedPath::policies()`].
+    #[cfg(feature = "alloc")]
+    pub fn with_policies(mut self, policy: PolicyOptions<'a>) -> Self {
+        self.path.policy = Some(policy);
+        self
+    }
+
+    /// Also consider the trust anchors in `constrained_anchors`, enforcing their constraints on
+    /// paths ending at them.
+    ///
+    /// The constraints travel with each trust anchor, so they apply whenever it is used. A trust
+    /// anchor that is also given unconstrained, e.g. in the slice of trust anchors, is trusted
+    /// without constraints as well. See [`constrained_anchor_from_trusted_cert()`] for a way to
+    /// obtain constraints from a trusted certificate, and [`TrustStore::add_constrained()`] for
+    /// keeping them in a trust store.
+    ///
+    /// [`constrained_anchor_from_trusted_cert()`]: crate::constrained_anchor_from_trusted_cert
+    pub fn with_constrained_anchors(
+        mut self,
+        constrained_anchors: &'a [ConstrainedTrustAnchor<'a>],
+    ) -> Self {
+        self.path.constrained_anchors = constrained_anchors;
+        self
+    }
+
+    /// Also consider the trust anchors in `trust_store`, alongside those given as a slice.
+    ///
+    /// See [`TrustStore`].
+    #[cfg(feature = "alloc")]
+    pub fn with_trust_store(mut self, trust_store: &'a TrustStore) -> Self {
+        self.path.trust_store = Some(trust_store);
+        self
+    }
+
+    /// Also consider the certificates in `intermediate_pool` as intermediates, alongside those
+    /// supplied for verification.
+    ///
+    /// See [`IntermediatePool`].
+    #[cfg(feature = "alloc")]
+    pub fn with_intermediate_pool(mut self, intermediate_pool: &'a IntermediatePool) -> Self {
+        self.path.intermediate_pool = Some(intermediate_pool);
+        self
+    }
+
+    /// Reject paths through any of the trust anchors or intermediates distrusted by `distrust`.
+    pub fn with_distrust(mut self, distrust: &'a [Distrust<'a>]) -> Self {
+        self.path.distrust = distrust;
+        self
+    }
+
+    /// Reject paths in which none of the certificates, including the trust anchor, has one of
+    /// the subject public keys pinned by `spki_pins`.
+    pub fn with_spki_pins(mut self, spki_pins: SpkiPins<'a>) -> Self {
+        self.path.spki_pins = Some(spki_pins);
+        self
+    }
+
+    /// Skip verifying signatures that were already successfully verified, according to
+    /// `signature_cache`, and record those that are.
+    ///
+    /// See [`SignatureCache`].
+    #[cfg(feature = "std")]
+    pub fn with_signature_cache(mut self, signature_cache: &'a SignatureCache<'a>) -> Self {
+        self.path.signature_cache = Some(signature_cache);
+        self
+    }
+
+    /// Customize how the validity periods of certificates are checked.
+    pub fn with_validity_policy(mut self, validity: ValidityPolicy) -> Self {
+        self.path.validity = validity;
+        self
+    }
+
+    /// Customize how the extended key usage extensions of intermediate certificates are
+    /// checked.
+    pub fn with_intermediate_eku(mut self, intermediate_eku: IntermediateEkuMode) -> Self {
+        self.path.intermediate_eku = intermediate_eku;
+        self
+    }
+
+    /// Check the X.509 key usage extensions of certificates.
+    ///
+    /// Every issuer, including constrained trust anchors with a key usage constraint, must
+    /// allow `keyCertSign`, and the end-entity certificate must allow `end_entity`. Otherwise
+    /// paths are rejected with [`Error::IssuerNotCertSigner`] or
+    /// [`Error::RequiredKeyUsageNotFound`]. As for CRL issuers, certificates without a key
+    /// usage extension allow every key usage.
+    pub fn with_strict_key_usage(mut self, end_entity: EndEntityKeyUsage) -> Self {
+        self.path.strict_key_usage = Some(end_entity);
+        self
+    }
+
+    /// Restrict the public key of the end-entity certificate, and the algorithm its issuer
+    /// signed it with, according to `policy`.
+    ///
+    /// The keys of the other certificates in the path are not subject to this policy.
+    pub fn with_end_entity_algorithm_policy(mut self, policy: AlgorithmPolicy<'a>) -> Self {
+        self.path.algorithms.end_entity = policy;
+        self
+    }
+
+    /// Restrict the public keys of intermediate certificates, and the algorithms their issuers
+    /// signed them with, according to `policy`.
+    ///
+    /// The certificates of the issuers of indirect CRLs are also subject to this policy, since
+    /// their keys sign CRLs rather than identify an end entity.
+    ///
+    /// Trust anchors are not subject to this policy; see
+    /// [`VerificationOptions::with_trust_anchor_algorithm_policy()`].
+    pub fn with_intermediate_algorithm_policy(mut self, policy: AlgorithmPolicy<'a>) -> Self {
+        self.path.algorithms.intermediate = policy;
+        self
+    }
+
+    /// Restrict the public keys of trust anchors according to `policy`.
+    ///
+    /// Trust anchors are not signed, so only the key restrictions of `policy` apply.
+    pub fn with_trust_anchor_algorithm_policy(mut self, policy: AlgorithmPolicy<'a>) -> Self {
+        self.path.algorithms.trust_anchor = policy;
+        self
+    }
+
+    /// Restrict the algorithms CRLs and OCSP responses are signed with, and the public keys
+    /// of their issuers and responders, according to `policy`.
+    ///
+    /// This only has an effect when checking revocation using CRLs or OCSP responses.
+    pub fn with_crl_algorithm_policy(mut self, policy: AlgorithmPolicy<'a>) -> Self {
+        self.path.algorithms.crl = policy;
+        self
+    }
+}
+
+impl Default for PathOptions<'_> {
+    fn default() -> Self {
+        Self {
+            budget: Budget::default(),
+            ranking: None,
+            max_path_depth: MAX_SUB_CA_COUNT,
+            #[cfg(feature = "alloc")]
+            policy: None,
+            constrained_anchors: &[],
+            distrust: &[],
+            spki_pins: None,
+            validity: ValidityPolicy::default(),
+            intermediate_eku: IntermediateEkuMode::default(),
+            strict_key_usage: None,
+            algorithms: AlgorithmPolicies::default(),
+            #[cfg(feature = "std")]
+            signature_cache: None,
+            must_staple: MustStaplePolicy::default(),
+            ocsp_hashers: &[],
+            #[cfg(feature = "alloc")]
+            trust_store: None,
+            #[cfg(feature = "alloc")]
+            intermediate_pool: None,
+        }
+    }
+}
+
+/// Limits on the work performed while building a path.
+///
+/// Path building explores candidate issuers depth-first, and the number of candidate paths can
+/// grow exponentially with the number of (cross-)certificates available. Exceeding any of these
+/// limits fails path building with one of [`Error::MaximumSignatureChecksExceeded`],
+/// [`Error::MaximumPathBuildCallsExceeded`] or [`Error::MaximumNameConstraintComparisonsExceeded`].
+///
+/// The [`Default`] limits are intended to be suitable for the Web PKI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
     signatures: usize,
     build_chain_calls: usize,
     name_constraint_comparisons: usize,
+    /// Not a limit: the number of signatures found in a signature cache so far.
+    cached_signatures: usize,
 }
 
 impl Budget {
+    /// Customize the maximum number of signatures that may be verified, including the
+    /// signatures of any CRLs used for revocation checking.
+    pub fn with_signatures(mut self, signatures: usize) -> Self {
+        self.signatures = signatures;
+        self
+    }
+
+    /// Customize the maximum number of candidate issuers that may be considered.
+    pub fn with_build_chain_calls(mut self, build_chain_calls: usize) -> Self {
+        self.build_chain_calls = build_chain_calls;
+        self
+    }
+
+    /// Customize the maximum number of comparisons of names against name constraints.
+    pub fn with_name_constraint_comparisons(mut self, name_constraint_comparisons: usize) -> Self {
+        self.name_constraint_comparisons = name_constraint_comparisons;
+        self
+    }
+
+    /// The amount of `limits` consumed, given that `self` remains.
+    fn usage(&self, limits: &Self) -> BudgetUsage {
+        BudgetUsage {
+            signatures: limits.signatures.saturating_sub(self.signatures),
+            build_chain_calls: limits
+                .build_chain_calls
+                .saturating_sub(self.build_chain_calls),
+            name_constraint_comparisons: limits
+                .name_constraint_comparisons
+                .saturating_sub(self.name_constraint_comparisons),
+            cached_signatures: self
+                .cached_signatures
+                .saturating_sub(limits.cached_signatures),
+        }
+    }
+
     #[inline]
     pub(crate) fn consume_signature(&mut self) -> Result<(), Error> {
         self.signatures = self
@@ -302,6 +1423,12 @@ impl Budget {
         Ok(())
     }
 
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn record_cached_signature(&mut self) {
+        self.cached_signatures = self.cached_signatures.saturating_add(1);
+    }
+
     #[inline]
     fn consume_build_chain_call(&mut self) -> Result<(), Error> {
         self.build_chain_calls = self
@@ -337,56 +1464,196 @@ impl Default for Budget {
             // This limit is taken from golang crypto/x509's default, see:
             // <https://github.com/golang/go/blob/ac17bb6f13979f2ab9fcd45f0758b43ed72d0973/src/crypto/x509/verify.go#L588-L592>
             name_constraint_comparisons: 250_000,
+
+            cached_signatures: 0,
+        }
+    }
+}
+
+/// How much of each [`Budget`] limit was used while building a path.
+///
+/// See [`VerifiedPath::budget_usage()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BudgetUsage {
+    /// The number of signatures verified.
+    pub signatures: usize,
+    /// The number of candidate issuers considered.
+    pub build_chain_calls: usize,
+    /// The number of name constraint comparisons performed.
+    pub name_constraint_comparisons: usize,
+    /// The number of signatures found in a signature cache set using
+    /// `VerificationOptions::with_signature_cache()`, rather than verified.
+    ///
+    /// These are not included in [`BudgetUsage::signatures`].
+    pub cached_signatures: usize,
+}
+
+/// A policy for checking the validity periods of certificates.
+///
+/// The [`Default`] policy only requires the verification time to be within the validity
+/// period of every certificate in the path, exactly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ValidityPolicy {
+    max_lifetime: Option<Duration>,
+    clock_skew: Duration,
+}
+
+impl ValidityPolicy {
+    /// Create a new [`ValidityPolicy`] with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject end-entity certificates whose validity period is longer than `max_lifetime`.
+    ///
+    /// As for the CA/Browser Forum Baseline Requirements, the validity period includes both
+    /// the notBefore and notAfter times, so e.g. a certificate valid for exactly 398 days has
+    /// a notAfter time 398 days less one second after its notBefore time. Such certificates
+    /// are rejected with [`Error::CertLifetimeTooLong`].
+    pub fn with_max_lifetime(mut self, max_lifetime: Duration) -> Self {
+        self.max_lifetime = Some(max_lifetime);
+        self
+    }
+
+    /// Accept certificates, and constrained trust anchors, that are valid within `clock_skew`
+    /// of the verification time.
+    ///
+    /// This suits devices whose clocks can't be relied upon. Certificates that are not valid
+    /// even allowing for the skew are rejected with [`Error::CertNotValidWithinSkew`] rather
+    /// than [`Error::CertExpired`] or [`Error::CertNotValidYet`].
+    pub fn with_clock_skew(mut self, clock_skew: Duration) -> Self {
+        self.clock_skew = clock_skew;
+        self
+    }
+
+    /// Check that `time` is within the validity period from `not_before` to `not_after`.
+    pub(crate) fn check_period(
+        &self,
+        time: UnixTime,
+        not_before: UnixTime,
+        not_after: UnixTime,
+    ) -> Result<(), Error> {
+        if self.clock_skew.is_zero() {
+            if time < not_before {
+                return Err(Error::CertNotValidYet { time, not_before });
+            }
+            if time > not_after {
+                return Err(Error::CertExpired { time, not_after });
+            }
+            return Ok(());
+        }
+
+        let (time_secs, skew_secs) = (time.as_secs(), self.clock_skew.as_secs());
+        if time_secs.saturating_add(skew_secs) < not_before.as_secs()
+            || time_secs.saturating_sub(skew_secs) > not_after.as_secs()
+        {
+            return Err(Error::CertNotValidWithinSkew {
+                time,
+                not_before,
+                not_after,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check that the validity period from `not_before` to `not_after` is not too long.
+    fn check_lifetime(&self, not_before: UnixTime, not_after: UnixTime) -> Result<(), Error> {
+        let max_lifetime = match self.max_lifetime {
+            Some(max_lifetime) => max_lifetime,
+            None => return Ok(()),
+        };
+
+        let lifetime = Duration::from_secs(
+            not_after
+                .as_secs()
+                .saturating_sub(not_before.as_secs())
+                .saturating_add(1),
+        );
+        match lifetime > max_lifetime {
+            true => Err(Error::CertLifetimeTooLong {
+                not_before,
+                not_after,
+                max_lifetime,
+            }),
+            false => Ok(()),
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn check_issuer_independent_properties(
     cert: &Cert<'_>,
     time: UnixTime,
+    validity: &ValidityPolicy,
     role: Role,
     sub_ca_count: usize,
-    eku: ExtendedKeyUsage,
+    eku: ExtendedKeyUsage<'_>,
+    intermediate_eku: IntermediateEkuMode,
+    strict_key_usage: Option<EndEntityKeyUsage>,
+    algorithms: &AlgorithmPolicies<'_>,
 ) -> Result<(), Error> {
-    // TODO: check_distrust(trust_anchor_subject, trust_anchor_spki)?;
-    // TODO: Check signature algorithm like mozilla::pkix.
-    // TODO: Check SPKI like mozilla::pkix.
-    // TODO: check for active distrust like mozilla::pkix.
+    let algorithms = match role {
+        Role::EndEntity => &algorithms.end_entity,
+        Role::Issuer => &algorithms.intermediate,
+    };
+    algorithms.check_signature_algorithm(cert.signed_data.algorithm)?;
+    algorithms.check_key(cert.spki)?;
 
-    // For cert validation, we ignore the KeyUsage extension. For CA
+    // By default, we ignore the KeyUsage extension for cert validation. For CA
     // certificates, BasicConstraints.cA makes KeyUsage redundant. Firefox
-    // and other common browsers do not check KeyUsage for end-entities,
-    // though it would be kind of nice to ensure that a KeyUsage without
-    // the keyEncipherment bit could not be used for RSA key exchange.
+    // and other common browsers do not check KeyUsage for end-entities.
+    // Stricter checking is opt-in, see `VerificationOptions::with_strict_key_usage()`.
+    if let Some(end_entity) = strict_key_usage {
+        let mode = match role {
+            Role::EndEntity => end_entity.mode(),
+            Role::Issuer => KeyUsageMode::CertSign,
+        };
+        mode.check(cert.key_usage)?;
+    }
 
-    cert.validity
-        .read_all(Error::BadDer, |value| check_validity(value, time))?;
+    cert.validity.read_all(Error::BadDer, |value| {
+        check_validity(value, time, role, validity)
+    })?;
     untrusted::read_all_optional(cert.basic_constraints, Error::BadDer, |value| {
         check_basic_constraints(value, role, sub_ca_count)
     })?;
-    untrusted::read_all_optional(cert.eku, Error::BadDer, |value| eku.check(value))?;
+    untrusted::read_all_optional(cert.eku, Error::BadDer, |value| {
+        match (role, intermediate_eku) {
+            (Role::EndEntity, _) | (Role::Issuer, IntermediateEkuMode::SameAsEndEntity) => {
+                eku.check(value)
+            }
+            (Role::Issuer, IntermediateEkuMode::Restrict) => eku.check_issuer(value),
+            (Role::Issuer, IntermediateEkuMode::Ignore) => {
+                if let Some(value) = value {
+                    value.skip_to_end();
+                }
+                Ok(())
+            }
+        }
+    })?;
 
     Ok(())
 }
 
 // https://tools.ietf.org/html/rfc5280#section-4.1.2.5
-fn check_validity(input: &mut untrusted::Reader<'_>, time: UnixTime) -> Result<(), Error> {
+fn check_validity(
+    input: &mut untrusted::Reader<'_>,
+    time: UnixTime,
+    role: Role,
+    validity: &ValidityPolicy,
+) -> Result<(), Error> {
     let not_before = UnixTime::from_der(input)?;
     let not_after = UnixTime::from_der(input)?;
 
     if not_before > not_after {
         return Err(Error::InvalidCertValidity);
     }
-    if time < not_before {
-        return Err(Error::CertNotValidYet { time, not_before });
+    validity.check_period(time, not_before, not_after)?;
+    if role == Role::EndEntity {
+        validity.check_lifetime(not_before, not_after)?;
     }
-    if time > not_after {
-        return Err(Error::CertExpired { time, not_after });
-    }
-
-    // TODO: mozilla::pkix allows the TrustDomain to check not_before and
-    // not_after, to enforce things like a maximum validity period. We should
-    // do something similar.
 
     Ok(())
 }
@@ -397,35 +1664,40 @@ fn check_basic_constraints(
     role: Role,
     sub_ca_count: usize,
 ) -> Result<(), Error> {
-    let (is_ca, path_len_constraint) = match input {
-        Some(input) => {
-            let is_ca = bool::from_der(input)?;
-
-            // https://bugzilla.mozilla.org/show_bug.cgi?id=985025: RFC 5280
-            // says that a certificate must not have pathLenConstraint unless
-            // it is a CA certificate, but some real-world end-entity
-            // certificates have pathLenConstraint.
-            let path_len_constraint = if !input.at_end() {
-                Some(usize::from(u8::from_der(input)?))
-            } else {
-                None
-            };
-
-            (is_ca, path_len_constraint)
-        }
-        None => (false, None),
-    };
-
-    match (role, is_ca, path_len_constraint) {
-        (Role::EndEntity, true, _) => Err(Error::CaUsedAsEndEntity),
-        (Role::Issuer, false, _) => Err(Error::EndEntityUsedAsCa),
-        (Role::Issuer, true, Some(len)) if sub_ca_count > len => {
+    match (role, read_basic_constraints(input)?) {
+        (Role::EndEntity, (true, _)) => Err(Error::CaUsedAsEndEntity),
+        (Role::Issuer, (false, _)) => Err(Error::EndEntityUsedAsCa),
+        (Role::Issuer, (true, Some(len))) if sub_ca_count > len => {
             Err(Error::PathLenConstraintViolated)
         }
         _ => Ok(()),
     }
 }
 
+/// Read the cA flag and the pathLenConstraint, if any, of a basic constraints extension.
+fn read_basic_constraints(
+    input: Option<&mut untrusted::Reader<'_>>,
+) -> Result<(bool, Option<usize>), Error> {
+    let input = match input {
+        Some(input) => input,
+        None => return Ok((false, None)),
+    };
+
+    let is_ca = bool::from_der(input)?;
+
+    // https://bugzilla.mozilla.org/show_bug.cgi?id=985025: RFC 5280
+    // says that a certificate must not have pathLenConstraint unless
+    // it is a CA certificate, but some real-world end-entity
+    // certificates have pathLenConstraint.
+    let path_len_constraint = if !input.at_end() {
+        Some(usize::from(u8::from_der(input)?))
+    } else {
+        None
+    };
+
+    Ok((is_ca, path_len_constraint))
+}
+
 /// The expected key usage of a certificate.
 ///
 /// This type represents the expected key usage of an end entity certificate. Although for most
@@ -434,12 +1706,12 @@ fn check_basic_constraints(
 /// is present, the certificate MUST only be used for one of the purposes indicated.
 ///
 /// <https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.12>
-#[derive(Clone, Copy)]
-pub struct KeyUsage {
-    inner: ExtendedKeyUsage,
+#[derive(Clone, Copy, Debug)]
+pub struct KeyUsage<'a> {
+    inner: ExtendedKeyUsage<'a>,
 }
 
-impl KeyUsage {
+impl<'a> KeyUsage<'a> {
     /// Construct a new [`KeyUsage`] as appropriate for server certificate authentication.
     ///
     /// As specified in <https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.12>, this does not require the certificate to specify the eKU extension.
@@ -455,34 +1727,115 @@ impl KeyUsage {
     }
 
     /// Construct a new [`KeyUsage`] requiring a certificate to support the specified OID.
-    pub const fn required(oid: &'static [u8]) -> Self {
+    pub const fn required(oid: &'a [u8]) -> Self {
         Self {
-            inner: ExtendedKeyUsage::Required(KeyPurposeId::new(oid)),
+            inner: ExtendedKeyUsage::Required(KeyPurposes::One(KeyPurposeId::new(oid))),
         }
     }
 
     /// Construct a new [`KeyUsage`] requiring a certificate to support the specified OID, if the certificate has EKUs.
-    pub const fn required_if_present(oid: &'static [u8]) -> Self {
+    pub const fn required_if_present(oid: &'a [u8]) -> Self {
+        Self {
+            inner: ExtendedKeyUsage::RequiredIfPresent(KeyPurposes::One(KeyPurposeId::new(oid))),
+        }
+    }
+
+    /// Construct a new [`KeyUsage`] requiring a certificate to support any of the specified OIDs.
+    ///
+    /// The OIDs are given in order of preference; see [`VerifiedPath::key_purpose()`].
+    pub const fn required_any_of(oids: &'a [&'a [u8]]) -> Self {
+        Self {
+            inner: ExtendedKeyUsage::Required(KeyPurposes::AnyOf(oids)),
+        }
+    }
+
+    /// Construct a new [`KeyUsage`] requiring a certificate to support any of the specified
+    /// OIDs, if the certificate has EKUs.
+    ///
+    /// The OIDs are given in order of preference; see [`VerifiedPath::key_purpose()`].
+    pub const fn required_if_present_any_of(oids: &'a [&'a [u8]]) -> Self {
+        Self {
+            inner: ExtendedKeyUsage::RequiredIfPresent(KeyPurposes::AnyOf(oids)),
+        }
+    }
+
+    /// Construct a new [`KeyUsage`] accepting certificates whatever their EKUs.
+    pub(crate) const fn any() -> Self {
         Self {
-            inner: ExtendedKeyUsage::RequiredIfPresent(KeyPurposeId::new(oid)),
+            inner: ExtendedKeyUsage::Any,
+        }
+    }
+}
+
+/// How the extended key usage (EKU) extensions of intermediate certificates are checked.
+///
+/// The EKU extension of an end-entity certificate is always checked according to the
+/// [`KeyUsage`] given for verification. Intermediate certificates are not bound by RFC 5280 to
+/// the purposes in their EKU extension, but in practice (e.g. by Chrome and Mozilla) an
+/// intermediate's EKU extension is taken to restrict the purposes of the paths through it.
+/// Use with [`VerificationOptions::with_intermediate_eku()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IntermediateEkuMode {
+    /// Check intermediate certificates exactly like the end-entity certificate.
+    ///
+    /// In particular, [`KeyUsage::required()`] requires every intermediate to have an EKU
+    /// extension, and `anyExtendedKeyUsage` is not accepted in place of the required purpose.
+    #[default]
+    SameAsEndEntity,
+
+    /// Require the EKU extension of each intermediate certificate, if present, to include the
+    /// required purpose or `anyExtendedKeyUsage`.
+    ///
+    /// Intermediates without an EKU extension are not restricted. Paths through an
+    /// intermediate that doesn't allow the required purpose are rejected with
+    /// [`Error::RequiredEkuNotFound`].
+    Restrict,
+
+    /// Ignore the EKU extensions of intermediate certificates.
+    Ignore,
+}
+
+/// The use of an end-entity certificate's key that its X.509 key usage extension must allow.
+///
+/// See [`VerificationOptions::with_strict_key_usage()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EndEntityKeyUsage {
+    /// The `digitalSignature` key usage, e.g. for TLS 1.3 or ECDHE key exchange.
+    DigitalSignature,
+
+    /// The `keyEncipherment` key usage, e.g. for RSA key transport.
+    KeyEncipherment,
+}
+
+impl EndEntityKeyUsage {
+    fn mode(self) -> KeyUsageMode {
+        match self {
+            Self::DigitalSignature => KeyUsageMode::DigitalSignature,
+            Self::KeyEncipherment => KeyUsageMode::KeyEncipherment,
         }
     }
 }
 
 /// Extended Key Usage (EKU) of a certificate.
-#[derive(Clone, Copy)]
-enum ExtendedKeyUsage {
-    /// The certificate must contain the specified [`KeyPurposeId`] as EKU.
-    Required(KeyPurposeId),
+#[derive(Clone, Copy, Debug)]
+enum ExtendedKeyUsage<'a> {
+    /// The certificate must contain one of the specified [`KeyPurposes`] as EKU.
+    Required(KeyPurposes<'a>),
+
+    /// If the certificate has EKUs, then one of the specified [`KeyPurposes`] must be included.
+    RequiredIfPresent(KeyPurposes<'a>),
 
-    /// If the certificate has EKUs, then the specified [`KeyPurposeId`] must be included.
-    RequiredIfPresent(KeyPurposeId),
+    /// The certificate's EKUs are not checked, as for the certificates of CRL issuers.
+    Any,
 }
 
-impl ExtendedKeyUsage {
+impl<'a> ExtendedKeyUsage<'a> {
     // https://tools.ietf.org/html/rfc5280#section-4.2.1.12
     fn check(&self, input: Option<&mut untrusted::Reader<'_>>) -> Result<(), Error> {
         let input = match (input, self) {
+            (_, Self::Any) => return Ok(()),
             (Some(input), _) => input,
             (None, Self::RequiredIfPresent(_)) => return Ok(()),
             (None, Self::Required(_)) => return Err(Error::RequiredEkuNotFound),
@@ -500,56 +1853,290 @@ impl ExtendedKeyUsage {
             }
         }
 
-        Ok(())
+        Ok(())
+    }
+
+    /// Check the EKU extension of an intermediate certificate, according to
+    /// [`IntermediateEkuMode::Restrict`].
+    fn check_issuer(&self, input: Option<&mut untrusted::Reader<'_>>) -> Result<(), Error> {
+        let input = match (input, self) {
+            (_, Self::Any) | (None, _) => return Ok(()),
+            (Some(input), _) => input,
+        };
+
+        loop {
+            let value = der::expect_tag(input, der::Tag::OID)?;
+            if self.key_purpose_id_equals(value) || public_values_eq(value, EKU_ANY.into()) {
+                input.skip_to_end();
+                return Ok(());
+            }
+
+            if input.at_end() {
+                return Err(Error::RequiredEkuNotFound);
+            }
+        }
+    }
+
+    /// Whether a certificate with the EKU extension `input`, in the given `role`, allows
+    /// `purpose`.
+    fn allows(
+        &self,
+        purpose: untrusted::Input<'_>,
+        input: Option<untrusted::Input<'_>>,
+        role: Role,
+        intermediate_eku: IntermediateEkuMode,
+    ) -> bool {
+        if let Self::Any = self {
+            return true;
+        }
+
+        let (required, accept_any) = match (role, intermediate_eku) {
+            (Role::EndEntity, _) | (Role::Issuer, IntermediateEkuMode::SameAsEndEntity) => {
+                (matches!(self, Self::Required(_)), false)
+            }
+            (Role::Issuer, IntermediateEkuMode::Restrict) => (false, true),
+            (Role::Issuer, IntermediateEkuMode::Ignore) => return true,
+        };
+
+        match input {
+            Some(input) => eku_contains(input, purpose, accept_any),
+            None => !required,
+        }
+    }
+
+    fn key_purpose_id_equals(&self, value: untrusted::Input<'_>) -> bool {
+        self.purposes()
+            .any(|purpose| public_values_eq(purpose, value))
+    }
+
+    /// The acceptable key purposes, in order of preference.
+    fn purposes(&self) -> impl Iterator<Item = untrusted::Input<'a>> {
+        let (one, any_of) = match self {
+            Self::Required(purposes) | Self::RequiredIfPresent(purposes) => match purposes {
+                KeyPurposes::One(id) => (Some(id.oid_value), &[][..]),
+                KeyPurposes::AnyOf(oids) => (None, *oids),
+            },
+            Self::Any => (None, &[][..]),
+        };
+
+        one.into_iter()
+            .chain(any_of.iter().map(|oid| untrusted::Input::from(oid)))
+    }
+}
+
+/// Whether `eku`, the value of an EKU extension, includes `purpose` or, if `accept_any`,
+/// `anyExtendedKeyUsage`.
+pub(crate) fn eku_contains(
+    eku: untrusted::Input<'_>,
+    purpose: untrusted::Input<'_>,
+    accept_any: bool,
+) -> bool {
+    let mut reader = untrusted::Reader::new(eku);
+    while let Ok(value) = der::expect_tag(&mut reader, der::Tag::OID) {
+        if public_values_eq(value, purpose)
+            || (accept_any && public_values_eq(value, EKU_ANY.into()))
+        {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// The key purposes acceptable to an [`ExtendedKeyUsage`].
+#[derive(Clone, Copy, Debug)]
+enum KeyPurposes<'a> {
+    /// A single key purpose.
+    One(KeyPurposeId<'a>),
+    /// Any of several key purposes, given as OIDs in bytes.
+    AnyOf(&'a [&'a [u8]]),
+}
+
+/// An OID value indicating an Extended Key Usage (EKU) key purpose.
+#[derive(Clone, Copy, Debug)]
+struct KeyPurposeId<'a> {
+    oid_value: untrusted::Input<'a>,
+}
+
+impl<'a> KeyPurposeId<'a> {
+    /// Construct a new [`KeyPurposeId`].
+    ///
+    /// `oid` is the OBJECT IDENTIFIER in bytes.
+    const fn new(oid: &'a [u8]) -> Self {
+        Self {
+            oid_value: untrusted::Input::from(oid),
+        }
+    }
+}
+
+impl PartialEq<Self> for KeyPurposeId<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        public_values_eq(self.oid_value, other.oid_value)
+    }
+}
+
+impl Eq for KeyPurposeId<'_> {}
+
+// id-pkix            OBJECT IDENTIFIER ::= { 1 3 6 1 5 5 7 }
+// id-kp              OBJECT IDENTIFIER ::= { id-pkix 3 }
+
+// anyExtendedKeyUsage OBJECT IDENTIFIER ::= { id-ce-extKeyUsage 0 }
+const EKU_ANY: &[u8] = &oid!(2, 5, 29, 37, 0);
+
+// id-kp-serverAuth   OBJECT IDENTIFIER ::= { id-kp 1 }
+const EKU_SERVER_AUTH: &[u8] = &oid!(1, 3, 6, 1, 5, 5, 7, 3, 1);
+
+// id-kp-clientAuth   OBJECT IDENTIFIER ::= { id-kp 2 }
+const EKU_CLIENT_AUTH: &[u8] = &oid!(1, 3, 6, 1, 5, 5, 7, 3, 2);
+
+/// A trust anchor that may be the issuer of the head of a partial path.
+#[derive(Clone, Copy)]
+struct AnchorCandidate<'p> {
+    anchor: &'p TrustAnchor<'p>,
+    /// The subject key identifier of the trust anchor, if known.
+    key_id: Option<untrusted::Input<'p>>,
+    /// The constraints on paths ending at the trust anchor, if any.
+    constraints: Option<&'p TrustAnchorConstraints<'p>>,
+}
+
+/// A certificate that may be the issuer of the head of a partial path.
+enum IssuerCandidate<'p> {
+    /// One of the intermediates supplied for verification, and the result of parsing it.
+    Supplied(&'p CertificateDer<'p>, Result<Cert<'p>, Error>),
+    /// A certificate from the [`IntermediatePool`].
+    #[cfg(feature = "alloc")]
+    Pooled(Cert<'p>),
+}
+
+impl IssuerCandidate<'_> {
+    /// Compare the candidate's subject key identifier with `akid`, that of the certificate it
+    /// may have issued. A candidate that failed to parse has no known subject key identifier.
+    fn key_id_match(&self, akid: Option<untrusted::Input<'_>>) -> KeyIdMatch {
+        match self {
+            Self::Supplied(_, Ok(cert)) => {
+                KeyIdMatch::from_key_ids(akid, cert.subject_key_identifier())
+            }
+            Self::Supplied(_, Err(_)) => KeyIdMatch::Unknown,
+            #[cfg(feature = "alloc")]
+            Self::Pooled(cert) => KeyIdMatch::from_key_ids(akid, cert.subject_key_identifier()),
+        }
+    }
+}
+
+/// The intermediate certificates supplied for a verification, other than duplicates.
+///
+/// With the `alloc` feature, these are parsed once when the verification starts, and those
+/// that are also in the [`IntermediatePool`] are left out. Otherwise, there is nowhere to keep
+/// the parsed certificates, so they are parsed again every time an issuer is looked for.
+#[derive(Clone)]
+pub(crate) struct SuppliedIntermediates<'p> {
+    #[cfg(not(feature = "alloc"))]
+    certs: &'p [CertificateDer<'p>],
+    #[cfg(feature = "alloc")]
+    parsed: Vec<(&'p CertificateDer<'p>, Result<Cert<'p>, Error>)>,
+}
+
+impl<'p> SuppliedIntermediates<'p> {
+    #[cfg(not(feature = "alloc"))]
+    fn new(certs: &'p [CertificateDer<'p>]) -> Self {
+        Self { certs }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn new(certs: &'p [CertificateDer<'p>], pool: Option<&IntermediatePool>) -> Self {
+        let mut parsed = Vec::with_capacity(certs.len());
+        for (i, cert_der) in certs.iter().enumerate() {
+            if certs[..i].contains(cert_der) {
+                continue;
+            }
+
+            let cert = Cert::from_der(untrusted::Input::from(cert_der));
+            if let (Ok(cert), Some(pool)) = (&cert, pool) {
+                if pool.contains(cert) {
+                    continue;
+                }
+            }
+
+            parsed.push((cert_der, cert));
+        }
+
+        Self { parsed }
     }
 
-    fn key_purpose_id_equals(&self, value: untrusted::Input<'_>) -> bool {
-        public_values_eq(
-            match self {
-                Self::Required(eku) => *eku,
-                Self::RequiredIfPresent(eku) => *eku,
-            }
-            .oid_value,
-            value,
-        )
+    /// The certificates that may have issued a certificate with the issuer name `issuer`, each
+    /// with the result of parsing it.
+    ///
+    /// Certificates that fail to parse are always included, since their subject is unknown.
+    #[cfg(not(feature = "alloc"))]
+    fn issuers<'s>(
+        &'s self,
+        issuer: untrusted::Input<'s>,
+    ) -> impl Iterator<Item = (&'p CertificateDer<'p>, Result<Cert<'p>, Error>)> + 's {
+        let certs = self.certs;
+        certs
+            .iter()
+            .enumerate()
+            .filter(move |(i, cert_der)| !certs[..*i].contains(cert_der))
+            .map(|(_, cert_der)| (cert_der, Cert::from_der(untrusted::Input::from(cert_der))))
+            .filter(move |(_, cert)| match cert {
+                Ok(cert) => public_values_eq(cert.subject, issuer),
+                Err(_) => true,
+            })
     }
-}
-
-/// An OID value indicating an Extended Key Usage (EKU) key purpose.
-#[derive(Clone, Copy)]
-struct KeyPurposeId {
-    oid_value: untrusted::Input<'static>,
-}
 
-impl KeyPurposeId {
-    /// Construct a new [`KeyPurposeId`].
+    /// The certificates that may have issued a certificate with the issuer name `issuer`, each
+    /// with the result of parsing it.
     ///
-    /// `oid` is the OBJECT IDENTIFIER in bytes.
-    const fn new(oid: &'static [u8]) -> Self {
-        Self {
-            oid_value: untrusted::Input::from(oid),
-        }
+    /// Certificates that fail to parse are always included, since their subject is unknown.
+    #[cfg(feature = "alloc")]
+    fn issuers<'s>(
+        &'s self,
+        issuer: untrusted::Input<'s>,
+    ) -> impl Iterator<Item = (&'p CertificateDer<'p>, Result<Cert<'p>, Error>)> + 's {
+        self.parsed
+            .iter()
+            .filter(move |(_, cert)| match cert {
+                Ok(cert) => public_values_eq(cert.subject, issuer),
+                Err(_) => true,
+            })
+            .map(|(cert_der, cert)| (*cert_der, cert.clone()))
     }
 }
 
-impl PartialEq<Self> for KeyPurposeId {
-    fn eq(&self, other: &Self) -> bool {
-        public_values_eq(self.oid_value, other.oid_value)
-    }
+/// How well a candidate issuer's subject key identifier matches the authority key identifier of
+/// the certificate it may have issued.
+///
+/// Key identifiers are not authenticated, and are sometimes missing or wrong in real-world
+/// certificates, so they are only used to order candidate issuers (see RFC 4158 section 3.5.12)
+/// and never to reject a candidate outright.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum KeyIdMatch {
+    /// The candidate's SKID is equal to the certificate's AKID keyIdentifier.
+    Match,
+    /// One of the key identifiers is absent, so no conclusion can be drawn.
+    Unknown,
+    /// Both key identifiers are present and differ.
+    Mismatch,
 }
 
-impl Eq for KeyPurposeId {}
-
-// id-pkix            OBJECT IDENTIFIER ::= { 1 3 6 1 5 5 7 }
-// id-kp              OBJECT IDENTIFIER ::= { id-pkix 3 }
-
-// id-kp-serverAuth   OBJECT IDENTIFIER ::= { id-kp 1 }
-const EKU_SERVER_AUTH: &[u8] = &oid!(1, 3, 6, 1, 5, 5, 7, 3, 1);
+impl KeyIdMatch {
+    /// The order in which candidate issuers are considered.
+    const SEARCH_ORDER: &'static [Self] = &[Self::Match, Self::Unknown, Self::Mismatch];
 
-// id-kp-clientAuth   OBJECT IDENTIFIER ::= { id-kp 2 }
-const EKU_CLIENT_AUTH: &[u8] = &oid!(1, 3, 6, 1, 5, 5, 7, 3, 2);
+    /// Compare the AKID keyIdentifier of a certificate with the SKID of a potential issuer.
+    pub(crate) fn from_key_ids(
+        akid: Option<untrusted::Input<'_>>,
+        skid: Option<untrusted::Input<'_>>,
+    ) -> Self {
+        match (akid, skid) {
+            (Some(akid), Some(skid)) if public_values_eq(akid, skid) => Self::Match,
+            (Some(_), Some(_)) => Self::Mismatch,
+            _ => Self::Unknown,
+        }
+    }
+}
 
-fn loop_while_non_fatal_error<'a, V: IntoIterator + 'a>(
+fn loop_while_non_fatal_error<'a, V: IntoIterator>(
     default_error: Error,
     values: V,
     mut f: impl FnMut(V::Item) -> Result<&'a TrustAnchor<'a>, ControlFlow<Error, Error>>,
@@ -572,34 +2159,49 @@ fn loop_while_non_fatal_error<'a, V: IntoIterator + 'a>(
 ///
 /// This represents a partial path because it does not yet contain the trust anchor. It stores
 /// the end-entity certificates, and an array of intermediate certificates.
+#[derive(Clone)]
 pub(crate) struct PartialPath<'a> {
     end_entity: &'a EndEntityCert<'a>,
     /// Intermediate certificates, in order from end-entity to trust anchor.
     ///
     /// Invariant: all values below `used` are `Some`.
-    intermediates: [Option<Cert<'a>>; MAX_SUB_CA_COUNT],
+    intermediates: PathBuffer<'a>,
     /// The number of `Some` values in `intermediates`.
     ///
     /// The next `Cert` passed to `push()` will be placed at `intermediates[used]`.
     /// If this value is 0, the path contains only the end-entity certificate.
     used: usize,
+    /// The maximum number of intermediate certificates.
+    max_depth: usize,
+    /// The index of the first certificate in the path found to have a pinned SPKI, if any.
+    pinned: Option<usize>,
 }
 
 impl<'a> PartialPath<'a> {
-    pub(crate) fn new(end_entity: &'a EndEntityCert<'a>) -> Self {
+    pub(crate) fn new(end_entity: &'a EndEntityCert<'a>, max_depth: usize) -> Self {
+        // Without `alloc`, paths can't be any deeper than the fixed-size buffer allows.
+        #[cfg(not(feature = "alloc"))]
+        let max_depth = max_depth.min(MAX_SUB_CA_COUNT);
+
         Self {
             end_entity,
-            intermediates: Default::default(),
+            intermediates: PathBuffer::new(max_depth),
             used: 0,
+            max_depth,
+            pinned: None,
         }
     }
 
     pub(crate) fn push(&mut self, cert: Cert<'a>) -> Result<(), ControlFlow<Error, Error>> {
-        if self.used >= MAX_SUB_CA_COUNT {
+        if self.used >= self.max_depth {
             return Err(Error::MaximumPathDepthExceeded.into());
         }
 
-        self.intermediates[self.used] = Some(cert);
+        match &mut self.intermediates {
+            PathBuffer::Fixed(certs) => certs[self.used] = Some(cert),
+            #[cfg(feature = "alloc")]
+            PathBuffer::Growable(certs) => certs.push(Some(cert)),
+        }
         self.used += 1;
         Ok(())
     }
@@ -610,8 +2212,29 @@ impl<'a> PartialPath<'a> {
             return;
         }
 
+        // The certificates below the one removed were checked before it, and weren't pinned.
+        if self.pinned == Some(self.used) {
+            self.pinned = None;
+        }
+
         self.used -= 1;
-        self.intermediates[self.used] = None;
+        match &mut self.intermediates {
+            PathBuffer::Fixed(certs) => certs[self.used] = None,
+            #[cfg(feature = "alloc")]
+            PathBuffer::Growable(certs) => {
+                certs.pop();
+            }
+        }
+    }
+
+    /// View this path, completed by `anchor`, as a candidate [`VerifiedPath`].
+    fn candidate(&self, anchor: &'a TrustAnchor<'a>, details: PathDetails<'a>) -> VerifiedPath<'_> {
+        VerifiedPath {
+            end_entity: self.end_entity,
+            intermediates: Intermediates::Borrowed(&self.intermediates.as_ref()[..self.used]),
+            anchor,
+            details,
+        }
     }
 
     pub(crate) fn node(&self) -> PathNode<'_> {
@@ -634,12 +2257,48 @@ impl<'a> PartialPath<'a> {
     fn get(&self, idx: usize) -> &Cert<'a> {
         match idx {
             0 => self.end_entity,
-            _ => self.intermediates[idx - 1].as_ref().unwrap(),
+            _ => self.intermediates.as_ref()[idx - 1].as_ref().unwrap(),
+        }
+    }
+}
+
+/// Storage for the intermediate certificates of a [`PartialPath`].
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+enum PathBuffer<'a> {
+    /// Used for paths up to the default maximum depth, avoiding allocation.
+    Fixed([Option<Cert<'a>>; MAX_SUB_CA_COUNT]),
+    /// Used for deeper paths.
+    ///
+    /// Invariant: the length is the number of intermediates in the path, so all values are `Some`.
+    #[cfg(feature = "alloc")]
+    Growable(Vec<Option<Cert<'a>>>),
+}
+
+impl PathBuffer<'_> {
+    #[cfg_attr(not(feature = "alloc"), allow(unused_variables))]
+    fn new(max_depth: usize) -> Self {
+        #[cfg(feature = "alloc")]
+        if max_depth > MAX_SUB_CA_COUNT {
+            return Self::Growable(Vec::new());
+        }
+
+        Self::Fixed(Default::default())
+    }
+}
+
+impl<'a> AsRef<[Option<Cert<'a>>]> for PathBuffer<'a> {
+    fn as_ref(&self) -> &[Option<Cert<'a>>] {
+        match self {
+            PathBuffer::Fixed(certs) => certs,
+            #[cfg(feature = "alloc")]
+            PathBuffer::Growable(certs) => certs,
         }
     }
 }
 
-const MAX_SUB_CA_COUNT: usize = 6;
+/// The default maximum number of intermediate certificates in a path.
+pub(crate) const MAX_SUB_CA_COUNT: usize = 6;
 
 pub(crate) struct PathNode<'a> {
     /// The path we're iterating.
@@ -708,8 +2367,10 @@ mod tests {
     #[test]
     fn eku_key_purpose_id() {
         assert!(
-            ExtendedKeyUsage::RequiredIfPresent(KeyPurposeId::new(EKU_SERVER_AUTH))
-                .key_purpose_id_equals(KeyPurposeId::new(EKU_SERVER_AUTH).oid_value)
+            ExtendedKeyUsage::RequiredIfPresent(KeyPurposes::One(KeyPurposeId::new(
+                EKU_SERVER_AUTH
+            )))
+            .key_purpose_id_equals(KeyPurposeId::new(EKU_SERVER_AUTH).oid_value)
         )
     }
 
@@ -734,18 +2395,36 @@ mod tests {
 
     #[test]
     fn longest_allowed_path() {
-        assert!(build_and_verify_linear_chain(1).is_ok());
-        assert!(build_and_verify_linear_chain(2).is_ok());
-        assert!(build_and_verify_linear_chain(3).is_ok());
-        assert!(build_and_verify_linear_chain(4).is_ok());
-        assert!(build_and_verify_linear_chain(5).is_ok());
-        assert!(build_and_verify_linear_chain(6).is_ok());
+        assert!(build_and_verify_linear_chain(1, None).is_ok());
+        assert!(build_and_verify_linear_chain(2, None).is_ok());
+        assert!(build_and_verify_linear_chain(3, None).is_ok());
+        assert!(build_and_verify_linear_chain(4, None).is_ok());
+        assert!(build_and_verify_linear_chain(5, None).is_ok());
+        assert!(build_and_verify_linear_chain(6, None).is_ok());
     }
 
     #[test]
     fn path_too_long() {
         assert!(matches!(
-            build_and_verify_linear_chain(7),
+            build_and_verify_linear_chain(7, None),
+            Err(ControlFlow::Continue(Error::MaximumPathDepthExceeded))
+        ));
+    }
+
+    #[test]
+    fn configurable_path_depth() {
+        let options = VerificationOptions::new().with_max_path_depth(10);
+        assert!(build_and_verify_linear_chain(7, Some(options)).is_ok());
+        assert!(build_and_verify_linear_chain(10, Some(options)).is_ok());
+        assert!(matches!(
+            build_and_verify_linear_chain(11, Some(options)),
+            Err(ControlFlow::Continue(Error::MaximumPathDepthExceeded))
+        ));
+
+        let options = VerificationOptions::new().with_max_path_depth(2);
+        assert!(build_and_verify_linear_chain(2, Some(options)).is_ok());
+        assert!(matches!(
+            build_and_verify_linear_chain(3, Some(options)),
             Err(ControlFlow::Continue(Error::MaximumPathDepthExceeded))
         ));
     }
@@ -809,7 +2488,7 @@ mod tests {
             &intermediates_der,
             &ee_cert,
             None,
-            Some(passing_budget),
+            Some(VerificationOptions::new().with_budget(passing_budget)),
         )
         .unwrap();
         assert_eq!(path.anchor().subject, anchors.first().unwrap().subject);
@@ -827,7 +2506,7 @@ mod tests {
             &intermediates_der,
             &ee_cert,
             None,
-            Some(failing_budget),
+            Some(VerificationOptions::new().with_budget(failing_budget)),
         );
 
         assert!(matches!(
@@ -974,6 +2653,510 @@ mod tests {
         assert_eq!(path_intermediates[1].issuer(), trust_anchor_cert.subject());
     }
 
+    #[test]
+    fn akid_prioritizes_matching_issuer() {
+        // Create two intermediates that share a subject name but have distinct keys, as happens
+        // when a CA is rekeyed. Only the second one issued the end entity certificate.
+        let trust_anchor = make_issuer("Trust Anchor");
+        let trust_anchors = &[anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap()];
+
+        let mut intermediates = Vec::with_capacity(2);
+        for _ in 0..2 {
+            let key_pair = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+            let cert = issuer_params("Rekeyed Intermediate")
+                .signed_by(&key_pair, &trust_anchor.cert, &trust_anchor.key_pair)
+                .unwrap();
+            intermediates.push(CertifiedKey { cert, key_pair });
+        }
+
+        let issuer = intermediates.last().unwrap();
+        let mut ee_params = test_utils::end_entity_params(vec!["example.com".into()]);
+        ee_params.use_authority_key_identifier_extension = true;
+        let ee_key = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+        let ee = ee_params
+            .signed_by(&ee_key, &issuer.cert, &issuer.key_pair)
+            .unwrap();
+        let ee_cert = EndEntityCert::try_from(ee.der()).unwrap();
+
+        let intermediates_der = intermediates
+            .iter()
+            .map(|issuer| issuer.cert.der().clone())
+            .collect::<Vec<_>>();
+
+        // Two signature checks are enough for EE -> second intermediate -> trust anchor: the
+        // first intermediate is never tried, despite being listed first.
+        let budget = Budget {
+            signatures: 2,
+            ..Budget::default()
+        };
+        let path = verify_chain(
+            trust_anchors,
+            &intermediates_der,
+            &ee_cert,
+            None,
+            Some(VerificationOptions::new().with_budget(budget)),
+        )
+        .unwrap();
+        let path_intermediates = path.intermediate_certificates().collect::<Vec<_>>();
+        assert_eq!(path_intermediates.len(), 1);
+        assert_eq!(path_intermediates[0].der(), intermediates_der[1]);
+    }
+
+    #[test]
+    fn unparsable_intermediate_traced_once() {
+        use crate::trace::{Candidate, TraceLog};
+
+        // The end-entity certificate has an authority key identifier, so candidate issuers are
+        // ordered by key identifier; one that can't be parsed is still only tried once.
+        let trust_anchor = make_issuer("Trust Anchor");
+        let trust_anchors = &[anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap()];
+        let issuer = make_issuer("Unknown");
+        let mut ee_params = test_utils::end_entity_params(vec!["example.com".into()]);
+        ee_params.use_authority_key_identifier_extension = true;
+        let ee_key = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+        let ee = ee_params
+            .signed_by(&ee_key, &issuer.cert, &issuer.key_pair)
+            .unwrap();
+        let ee_cert = EndEntityCert::try_from(ee.der()).unwrap();
+
+        let garbage = CertificateDer::from(&b"not a certificate"[..]);
+        let intermediates = [garbage.clone()];
+        let trace = TraceLog::new();
+        let result = verify_chain(
+            trust_anchors,
+            &intermediates,
+            &ee_cert,
+            None,
+            Some(VerificationOptions::new().with_trace(&trace)),
+        );
+        assert!(matches!(
+            result,
+            Err(ControlFlow::Continue(Error::TrailingData(_)))
+        ));
+
+        let entries = trace.into_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].issuer, Candidate::Intermediate(garbage));
+    }
+
+    #[test]
+    fn all_paths() {
+        // Build a PKI where the end entity's issuer B is cross-signed by two intermediates A and
+        // C, each issued by the trust anchor, giving two valid paths:
+        //   EE -> B (issued by A) -> A -> TA
+        //   EE -> B (issued by C) -> C -> TA
+        let trust_anchor = make_issuer("Trust Anchor");
+        let trust_anchors = &[anchor_from_trusted_cert(trust_anchor.cert.der()).unwrap()];
+
+        let mut cross_signers = Vec::with_capacity(2);
+        for name in ["Intermediate A", "Intermediate C"] {
+            let key_pair = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+            let cert = issuer_params(name)
+                .signed_by(&key_pair, &trust_anchor.cert, &trust_anchor.key_pair)
+                .unwrap();
+            cross_signers.push(CertifiedKey { cert, key_pair });
+        }
+
+        let intermediate_b_key = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+        let mut intermediates = Vec::new();
+        let mut intermediate_b = None;
+        for issuer in &cross_signers {
+            intermediates.push(issuer.cert.der().clone());
+            let cert = issuer_params("Intermediate B")
+                .signed_by(&intermediate_b_key, &issuer.cert, &issuer.key_pair)
+                .unwrap();
+            intermediates.push(cert.der().clone());
+            intermediate_b = Some(cert);
+        }
+
+        let ee = make_end_entity(&intermediate_b.unwrap(), &intermediate_b_key);
+        let ee_cert = &EndEntityCert::try_from(ee.cert.der()).unwrap();
+
+        let time = UnixTime::since_unix_epoch(core::time::Duration::from_secs(0x1fed_f00d));
+        let for_each_chain =
+            |intermediates: &[CertificateDer<'_>],
+             visit: &mut dyn FnMut(&VerifiedPath<'_>) -> ControlFlow<()>| {
+                ee_cert.verify_all_paths_for_usage(
+                    crate::ALL_VERIFICATION_ALGS,
+                    trust_anchors,
+                    intermediates,
+                    time,
+                    KeyUsage::server_auth(),
+                    None,
+                    &VerificationOptions::new(),
+                    visit,
+                )
+            };
+
+        // Both paths should be visited, each with two intermediates.
+        let mut issuers = Vec::new();
+        let visited = for_each_chain(&intermediates, &mut |path| {
+            let path_intermediates = path.intermediate_certificates().collect::<Vec<_>>();
+            assert_eq!(path_intermediates.len(), 2);
+            issuers.push(path_intermediates[0].issuer().to_vec());
+            ControlFlow::Continue(())
+        });
+        assert_eq!(visited, Ok(2));
+        assert_eq!(
+            issuers,
+            cross_signers
+                .iter()
+                .map(
+                    |issuer| Cert::from_der(untrusted::Input::from(issuer.cert.der()))
+                        .unwrap()
+                        .subject()
+                        .to_vec()
+                )
+                .collect::<Vec<_>>()
+        );
+
+        // Listing the same certificates twice does not yield duplicate paths.
+        let duplicated = intermediates
+            .iter()
+            .chain(intermediates.iter())
+            .cloned()
+            .collect::<Vec<_>>();
+        let visited = for_each_chain(&duplicated, &mut |_| ControlFlow::Continue(()));
+        assert_eq!(visited, Ok(2));
+
+        // The visitor can stop path building early.
+        let visited = for_each_chain(&duplicated, &mut |_| ControlFlow::Break(()));
+        assert_eq!(visited, Ok(1));
+
+        // Without any valid path, the most specific error is returned.
+        let visited = for_each_chain(&[], &mut |_| ControlFlow::Continue(()));
+        assert_eq!(visited, Err(Error::UnknownIssuer));
+    }
+
+    #[test]
+    fn validity_policy() {
+        let ca = make_issuer("Trust Anchor");
+        let anchors = [anchor_from_trusted_cert(ca.cert.der()).unwrap().to_owned()];
+
+        let ee = |not_before: (i32, u8, u8), not_after: (i32, u8, u8)| {
+            let mut params = test_utils::end_entity_params(vec!["example.com".into()]);
+            params.not_before = rcgen::date_time_ymd(not_before.0, not_before.1, not_before.2);
+            params.not_after = rcgen::date_time_ymd(not_after.0, not_after.1, not_after.2);
+            let key_pair = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+            params
+                .signed_by(&key_pair, &ca.cert, &ca.key_pair)
+                .unwrap()
+                .der()
+                .clone()
+        };
+        let verify = |ee: &CertificateDer<'_>, validity| {
+            let ee = EndEntityCert::try_from(ee).unwrap();
+            let options = VerificationOptions::new().with_validity_policy(validity);
+            verify_chain(&anchors, &[], &ee, None, Some(options)).map(|_| ())
+        };
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        // Valid for 365 days and a second, including both notBefore and notAfter.
+        let year = ee((1986, 1, 1), (1987, 1, 1));
+        assert!(verify(&year, ValidityPolicy::new()).is_ok());
+        assert!(verify(&year, ValidityPolicy::new().with_max_lifetime(day * 366)).is_ok());
+        assert!(matches!(
+            verify(&year, ValidityPolicy::new().with_max_lifetime(day * 365)),
+            Err(ControlFlow::Continue(Error::CertLifetimeTooLong { max_lifetime, .. }))
+                if max_lifetime == day * 365
+        ));
+
+        // The verification time is early on 23 December 1986.
+        let not_yet_valid = ee((1986, 12, 24), (1987, 12, 24));
+        assert!(matches!(
+            verify(&not_yet_valid, ValidityPolicy::new()),
+            Err(ControlFlow::Continue(Error::CertNotValidYet { .. }))
+        ));
+        assert!(verify(
+            &not_yet_valid,
+            ValidityPolicy::new().with_clock_skew(day * 2)
+        )
+        .is_ok());
+        assert!(matches!(
+            verify(
+                &not_yet_valid,
+                ValidityPolicy::new().with_clock_skew(day / 2)
+            ),
+            Err(ControlFlow::Continue(Error::CertNotValidWithinSkew { .. }))
+        ));
+
+        let expired = ee((1985, 12, 22), (1986, 12, 22));
+        assert!(matches!(
+            verify(&expired, ValidityPolicy::new()),
+            Err(ControlFlow::Continue(Error::CertExpired { .. }))
+        ));
+        assert!(verify(&expired, ValidityPolicy::new().with_clock_skew(day * 2)).is_ok());
+        assert!(matches!(
+            verify(&expired, ValidityPolicy::new().with_clock_skew(day / 2)),
+            Err(ControlFlow::Continue(Error::CertNotValidWithinSkew { .. }))
+        ));
+    }
+
+    #[test]
+    fn intermediate_eku_mode() {
+        use rcgen::ExtendedKeyUsagePurpose::{Any, ClientAuth, ServerAuth};
+
+        let ca = make_issuer("Trust Anchor");
+        let anchors = [anchor_from_trusted_cert(ca.cert.der()).unwrap().to_owned()];
+
+        let verify = |intermediate_ekus: Vec<rcgen::ExtendedKeyUsagePurpose>,
+                      usage: KeyUsage<'_>,
+                      mode: IntermediateEkuMode| {
+            let mut params = issuer_params("Intermediate");
+            params.extended_key_usages = intermediate_ekus;
+            let key_pair = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+            let intermediate = params.signed_by(&key_pair, &ca.cert, &ca.key_pair).unwrap();
+
+            let mut params = test_utils::end_entity_params(vec!["example.com".into()]);
+            params.extended_key_usages = vec![ServerAuth];
+            let ee_key_pair = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+            let ee = params
+                .signed_by(&ee_key_pair, &intermediate, &key_pair)
+                .unwrap();
+
+            EndEntityCert::try_from(ee.der())
+                .unwrap()
+                .verify_for_usage_with_options(
+                    crate::ALL_VERIFICATION_ALGS,
+                    &anchors,
+                    &[intermediate.der().clone()],
+                    UnixTime::since_unix_epoch(core::time::Duration::from_secs(0x1fed_f00d)),
+                    usage,
+                    None,
+                    None,
+                    &VerificationOptions::new().with_intermediate_eku(mode),
+                )
+                .map(|_| ())
+        };
+
+        use IntermediateEkuMode::{Ignore, Restrict, SameAsEndEntity};
+        let server_auth = KeyUsage::server_auth();
+        let required = KeyUsage::required(EKU_SERVER_AUTH);
+        let not_found = Err(Error::RequiredEkuNotFound);
+
+        // An intermediate restricted to another purpose.
+        assert_eq!(
+            verify(vec![ClientAuth], server_auth, SameAsEndEntity),
+            not_found
+        );
+        assert_eq!(verify(vec![ClientAuth], server_auth, Restrict), not_found);
+        assert_eq!(verify(vec![ClientAuth], server_auth, Ignore), Ok(()));
+
+        // anyExtendedKeyUsage only allows every purpose when restricting.
+        assert_eq!(verify(vec![Any], server_auth, SameAsEndEntity), not_found);
+        assert_eq!(verify(vec![Any], server_auth, Restrict), Ok(()));
+        assert_eq!(
+            verify(vec![Any, ServerAuth], server_auth, SameAsEndEntity),
+            Ok(())
+        );
+
+        // An intermediate without an EKU extension doesn't restrict the path, but doesn't
+        // satisfy a required EKU either.
+        assert_eq!(verify(vec![], required, SameAsEndEntity), not_found);
+        assert_eq!(verify(vec![], required, Restrict), Ok(()));
+        assert_eq!(verify(vec![], required, Ignore), Ok(()));
+        assert_eq!(verify(vec![ServerAuth], required, Restrict), Ok(()));
+    }
+
+    #[test]
+    fn key_purpose_any_of() {
+        use rcgen::ExtendedKeyUsagePurpose::{self as Purpose, ClientAuth, ServerAuth};
+
+        const DEVICE_IDENTITY: &[u8] = &oid!(1, 3, 6, 1, 4, 1, 99, 1);
+        static CLIENT_OR_DEVICE: &[&[u8]] = &[EKU_CLIENT_AUTH, DEVICE_IDENTITY];
+        let device = || Purpose::Other(vec![1, 3, 6, 1, 4, 1, 99, 1]);
+
+        let ca = make_issuer("Trust Anchor");
+        let anchors = [anchor_from_trusted_cert(ca.cert.der()).unwrap().to_owned()];
+
+        let verify =
+            |intermediate_ekus: Vec<Purpose>, ee_ekus: Vec<Purpose>, usage: KeyUsage<'_>| {
+                let mut params = issuer_params("Intermediate");
+                params.extended_key_usages = intermediate_ekus;
+                let key_pair = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+                let intermediate = params.signed_by(&key_pair, &ca.cert, &ca.key_pair).unwrap();
+
+                let mut params = test_utils::end_entity_params(vec!["example.com".into()]);
+                params.extended_key_usages = ee_ekus;
+                let ee_key_pair = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+                let ee = params
+                    .signed_by(&ee_key_pair, &intermediate, &key_pair)
+                    .unwrap();
+
+                EndEntityCert::try_from(ee.der())
+                    .unwrap()
+                    .verify_for_usage(
+                        crate::ALL_VERIFICATION_ALGS,
+                        &anchors,
+                        &[intermediate.der().clone()],
+                        UnixTime::since_unix_epoch(core::time::Duration::from_secs(0x1fed_f00d)),
+                        usage,
+                        None,
+                        None,
+                    )
+                    .map(|path| path.key_purpose().map(<[u8]>::to_vec))
+            };
+
+        // The EKUs are required, so the intermediate must have them too.
+        let any_of = KeyUsage::required_any_of(CLIENT_OR_DEVICE);
+        let both = || vec![ClientAuth, device()];
+        assert_eq!(
+            verify(both(), vec![device()], any_of),
+            Ok(Some(DEVICE_IDENTITY.to_vec()))
+        );
+        assert_eq!(
+            verify(both(), vec![ClientAuth], any_of),
+            Ok(Some(EKU_CLIENT_AUTH.to_vec()))
+        );
+        assert_eq!(
+            verify(both(), vec![ServerAuth], any_of),
+            Err(Error::RequiredEkuNotFound)
+        );
+
+        // The most preferred purpose allowed by every certificate is reported.
+        assert_eq!(
+            verify(both(), vec![device(), ClientAuth], any_of),
+            Ok(Some(EKU_CLIENT_AUTH.to_vec()))
+        );
+        assert_eq!(
+            verify(vec![device()], vec![device(), ClientAuth], any_of),
+            Ok(Some(DEVICE_IDENTITY.to_vec()))
+        );
+
+        // Each certificate allows one of the purposes, but not the same one.
+        assert_eq!(
+            verify(vec![ClientAuth], vec![device()], any_of),
+            Err(Error::RequiredEkuNotFound)
+        );
+
+        // Without EKUs, every purpose is allowed if not required.
+        assert_eq!(
+            verify(
+                vec![],
+                vec![],
+                KeyUsage::required_if_present_any_of(CLIENT_OR_DEVICE)
+            ),
+            Ok(Some(EKU_CLIENT_AUTH.to_vec()))
+        );
+        assert_eq!(
+            verify(vec![], vec![], KeyUsage::server_auth()),
+            Ok(Some(EKU_SERVER_AUTH.to_vec()))
+        );
+    }
+
+    #[test]
+    fn strict_key_usage() {
+        use crate::trust_anchor::ConstrainedTrustAnchor;
+        use rcgen::KeyUsagePurpose::{
+            self as Purpose, DigitalSignature, KeyCertSign, KeyEncipherment,
+        };
+
+        let ca = make_issuer("Trust Anchor");
+        let anchors = [anchor_from_trusted_cert(ca.cert.der()).unwrap().to_owned()];
+
+        let verify = |intermediate_kus: Vec<Purpose>,
+                      ee_kus: Vec<Purpose>,
+                      options: VerificationOptions<'_>| {
+            let mut params = issuer_params("Intermediate");
+            params.key_usages = intermediate_kus;
+            let key_pair = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+            let intermediate = params.signed_by(&key_pair, &ca.cert, &ca.key_pair).unwrap();
+
+            let mut params = test_utils::end_entity_params(vec!["example.com".into()]);
+            params.key_usages = ee_kus;
+            let ee_key_pair = KeyPair::generate_for(test_utils::RCGEN_SIGNATURE_ALG).unwrap();
+            let ee = params
+                .signed_by(&ee_key_pair, &intermediate, &key_pair)
+                .unwrap();
+
+            // Constrained trust anchors are used instead of, not as well as, the unconstrained.
+            let anchors = match options.path.constrained_anchors.is_empty() {
+                true => &anchors[..],
+                false => &[],
+            };
+            EndEntityCert::try_from(ee.der())
+                .unwrap()
+                .verify_for_usage_with_options(
+                    crate::ALL_VERIFICATION_ALGS,
+                    anchors,
+                    &[intermediate.der().clone()],
+                    UnixTime::since_unix_epoch(core::time::Duration::from_secs(0x1fed_f00d)),
+                    KeyUsage::server_auth(),
+                    None,
+                    None,
+                    &options,
+                )
+                .map(|_| ())
+        };
+
+        // Key usage is ignored by default.
+        let default = VerificationOptions::new();
+        assert_eq!(verify(vec![DigitalSignature], vec![], default), Ok(()));
+        assert_eq!(
+            verify(vec![KeyCertSign], vec![KeyCertSign], default),
+            Ok(())
+        );
+
+        let signature =
+            VerificationOptions::new().with_strict_key_usage(EndEntityKeyUsage::DigitalSignature);
+        let encipherment =
+            VerificationOptions::new().with_strict_key_usage(EndEntityKeyUsage::KeyEncipherment);
+        assert_eq!(
+            verify(vec![DigitalSignature], vec![], signature),
+            Err(Error::IssuerNotCertSigner)
+        );
+        assert_eq!(verify(vec![KeyCertSign], vec![], signature), Ok(()));
+        assert_eq!(
+            verify(vec![KeyCertSign], vec![DigitalSignature], signature),
+            Ok(())
+        );
+        assert_eq!(
+            verify(vec![KeyCertSign], vec![KeyEncipherment], signature),
+            Err(Error::RequiredKeyUsageNotFound)
+        );
+        assert_eq!(
+            verify(vec![KeyCertSign], vec![KeyEncipherment], encipherment),
+            Ok(())
+        );
+        assert_eq!(
+            verify(vec![KeyCertSign], vec![DigitalSignature], encipherment),
+            Err(Error::RequiredKeyUsageNotFound)
+        );
+
+        // A key usage constraint on the trust anchor must also allow keyCertSign.
+        let constrained = |key_usage: &'static [u8]| ConstrainedTrustAnchor {
+            anchor: anchors[0].clone(),
+            constraints: TrustAnchorConstraints::new().with_key_usage(key_usage.into()),
+        };
+        // BIT STRING with only digitalSignature, then also keyCertSign.
+        let digital_signature = [constrained(&[0x03, 0x02, 0x07, 0x80])];
+        let cert_sign = [constrained(&[0x03, 0x02, 0x02, 0x84])];
+        assert_eq!(
+            verify(
+                vec![KeyCertSign],
+                vec![],
+                signature.with_constrained_anchors(&digital_signature)
+            ),
+            Err(Error::IssuerNotCertSigner)
+        );
+        assert_eq!(
+            verify(
+                vec![KeyCertSign],
+                vec![],
+                signature.with_constrained_anchors(&cert_sign)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            verify(
+                vec![KeyCertSign],
+                vec![],
+                default.with_constrained_anchors(&digital_signature)
+            ),
+            Ok(())
+        );
+    }
+
     fn build_and_verify_degenerate_chain(
         intermediate_count: usize,
         trust_anchor: ChainTrustAnchor,
@@ -1016,7 +3199,10 @@ mod tests {
         InChain,
     }
 
-    fn build_and_verify_linear_chain(chain_length: usize) -> Result<(), ControlFlow<Error, Error>> {
+    fn build_and_verify_linear_chain(
+        chain_length: usize,
+        options: Option<VerificationOptions<'_>>,
+    ) -> Result<(), ControlFlow<Error, Error>> {
         let ca_cert = make_issuer(format!("Bogus Subject {chain_length}"));
         let intermediate_chain = build_linear_chain(&ca_cert, chain_length, false);
 
@@ -1066,7 +3252,7 @@ mod tests {
             &intermediate_chain.chain,
             &ee_cert,
             Some(&expected_chain),
-            None,
+            options,
         )
         .map(|_| ())
     }
@@ -1115,28 +3301,40 @@ mod tests {
         intermediate_certs: &'a [CertificateDer<'a>],
         ee_cert: &'a EndEntityCert<'a>,
         verify_path: Option<&dyn Fn(&VerifiedPath<'_>) -> Result<(), Error>>,
-        budget: Option<Budget>,
+        options: Option<VerificationOptions<'a>>,
     ) -> Result<VerifiedPath<'a>, ControlFlow<Error, Error>> {
         use core::time::Duration;
 
         let time = UnixTime::since_unix_epoch(Duration::from_secs(0x1fed_f00d));
-        let mut path = PartialPath::new(ee_cert);
-        let opts = ChainOptions {
-            eku: KeyUsage::server_auth(),
-            supported_sig_algs: crate::ALL_VERIFICATION_ALGS,
+        let options = options.unwrap_or_default();
+        let mut path = PartialPath::new(ee_cert, options.path.max_path_depth);
+        let opts = ChainOptions::new(
+            crate::ALL_VERIFICATION_ALGS,
             trust_anchors,
             intermediate_certs,
-            revocation: None,
-        };
+            KeyUsage::server_auth(),
+            None,
+            &options,
+        );
 
-        match opts.build_chain_inner(
-            &mut path,
-            time,
-            verify_path,
-            0,
-            &mut budget.unwrap_or_default(),
-        ) {
-            Ok(anchor) => Ok(VerifiedPath::new(ee_cert, anchor, path)),
+        let mut budget = opts.budget;
+        let mut verify_candidate =
+            |partial: &PartialPath<'a>, anchor: &'a TrustAnchor<'a>, details: &PathDetails<'a>| {
+                match verify_path {
+                    Some(verify) => verify(&partial.candidate(anchor, details.clone()))
+                        .map_err(ControlFlow::Continue),
+                    None => Ok(()),
+                }
+            };
+
+        match opts.build_chain_inner(&mut path, time, &mut verify_candidate, 0, &mut budget) {
+            Ok(anchor) => {
+                let details = PathDetails {
+                    budget_usage: budget.usage(&opts.budget),
+                    ..PathDetails::default()
+                };
+                Ok(VerifiedPath::new(ee_cert, anchor, path, details))
+            }
             Err(err) => Err(err),
         }
     }
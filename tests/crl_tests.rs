@@ -84,10 +84,15 @@ fn parse_missing_exts_crl() {
 
 #[test]
 fn parse_delta_crl() {
-    // Parsing a CRL with an extension indicating its a delta CRL should error.
+    // Parsing a CRL with an extension indicating its a delta CRL should succeed, recording
+    // the CRL number of the complete CRL it updates.
     let crl = include_bytes!("crls/crl.delta.der");
-    let res = BorrowedCertRevocationList::from_der(&crl[..]);
-    assert!(matches!(res, Err(Error::UnsupportedDeltaCrl)));
+    let crl = CertRevocationList::from(
+        BorrowedCertRevocationList::from_der(&crl[..]).expect("failed to parse delta CRL"),
+    );
+    let number = &[0x17, 0x1c, 0xce, 0x3d, 0xe4, 0x82, 0xba, 0x61][..];
+    assert_eq!(crl.crl_number(), Some(number));
+    assert_eq!(crl.base_crl_number(), Some(number));
 }
 
 #[test]
@@ -267,9 +272,9 @@ fn parse_entry_invalidity_date_crl() {
 
 #[test]
 fn parse_entry_indirect_issuer_crl() {
-    // Parsing a CRL that includes a revoked entry that has a issuer certificate extension
-    // shouldn't error up-front - we expect the error to be surfaced when we iterate the revoked
-    // certs.
+    // Parsing a CRL that includes a revoked entry that has a malformed issuer certificate
+    // extension shouldn't error up-front - we expect the error to be surfaced when we iterate
+    // the revoked certs.
     let crl = include_bytes!("crls/crl.entry.issuer.ext.der");
     let borrowed_crl = BorrowedCertRevocationList::from_der(&crl[..]).unwrap();
 
@@ -277,10 +282,10 @@ fn parse_entry_indirect_issuer_crl() {
     {
         // Building an owned CRL should error up front since it will process the revoked certs.
         let res = borrowed_crl.to_owned();
-        assert!(matches!(res, Err(Error::UnsupportedIndirectCrl)));
+        assert!(matches!(res, Err(Error::BadDer)));
     }
 
     let crl: CertRevocationList = borrowed_crl.into();
     let res = crl.find_serial(REVOKED_SERIAL);
-    assert!(matches!(res, Err(Error::UnsupportedIndirectCrl)));
+    assert!(matches!(res, Err(Error::BadDer)));
 }
@@ -3,7 +3,7 @@
 use core::time::Duration;
 
 use pki_types::{CertificateDer, UnixTime};
-use webpki::{anchor_from_trusted_cert, KeyUsage};
+use webpki::{anchor_from_trusted_cert, KeyUsage, ObjectIdentifier};
 
 fn check_cert(
     ee: &[u8],
@@ -76,3 +76,26 @@ pub fn verify_custom_eku_required_if_present() {
     let ca = include_bytes!("client_auth/cert_with_both_ekus_accepted_for_client_auth.ca.der");
     check_cert(ee, ca, eku, time, Ok(()));
 }
+
+#[test]
+pub fn verify_custom_eku_from_str() {
+    let time = UnixTime::since_unix_epoch(Duration::from_secs(1_609_459_200)); //  Jan 1 01:00:00 CET 2021
+
+    let ee = include_bytes!("misc/mdoc_eku.ee.der");
+    let ca = include_bytes!("misc/mdoc_eku.ca.der");
+
+    // The OID is only available at runtime, e.g. from configuration.
+    let oid = String::from("1.0.18013.5.1.2")
+        .parse::<ObjectIdentifier<'_>>()
+        .unwrap();
+    check_cert(ee, ca, KeyUsage::required(oid.as_ref()), time, Ok(()));
+
+    let oid = "1.0.18013.5.1.3".parse::<ObjectIdentifier<'_>>().unwrap();
+    check_cert(
+        ee,
+        ca,
+        KeyUsage::required(oid.as_ref()),
+        time,
+        Err(webpki::Error::RequiredEkuNotFound),
+    );
+}
@@ -17,7 +17,7 @@
 use core::time::Duration;
 
 use pki_types::{CertificateDer, UnixTime};
-use webpki::{anchor_from_trusted_cert, KeyUsage};
+use webpki::{anchor_from_trusted_cert, Budget, KeyUsage, VerificationOptions};
 
 /* Checks we can verify netflix's cert chain.  This is notable
  * because they're rooted at a Verisign v1 root. */
@@ -47,6 +47,51 @@ fn netflix() {
         .is_ok());
 }
 
+#[cfg(feature = "alloc")]
+#[test]
+fn netflix_budget() {
+    let ee: &[u8] = include_bytes!("netflix/ee.der");
+    let inter = CertificateDer::from(&include_bytes!("netflix/inter.der")[..]);
+    let ca = CertificateDer::from(&include_bytes!("netflix/ca.der")[..]);
+
+    let anchors = [anchor_from_trusted_cert(&ca).unwrap()];
+
+    let time = UnixTime::since_unix_epoch(Duration::from_secs(1_492_441_716)); // 2017-04-17T15:08:36Z
+
+    let ee = CertificateDer::from(ee);
+    let cert = webpki::EndEntityCert::try_from(&ee).unwrap();
+    let intermediates = [inter];
+    let verify = |budget: Budget| {
+        cert.verify_for_usage_with_options(
+            webpki::ALL_VERIFICATION_ALGS,
+            &anchors,
+            &intermediates,
+            time,
+            KeyUsage::server_auth(),
+            None,
+            None,
+            &VerificationOptions::new().with_budget(budget),
+        )
+    };
+
+    // The default budget is sufficient, and we can see how much of it was used.
+    let path = verify(Budget::default()).unwrap();
+    let usage = path.budget_usage();
+    assert_eq!(usage.signatures, 2);
+    assert_eq!(usage.build_chain_calls, 1);
+    assert_eq!(usage.name_constraint_comparisons, 0);
+
+    // Each of the limits can be tightened.
+    assert_eq!(
+        verify(Budget::default().with_signatures(1)).err(),
+        Some(webpki::Error::MaximumSignatureChecksExceeded)
+    );
+    assert_eq!(
+        verify(Budget::default().with_build_chain_calls(0)).err(),
+        Some(webpki::Error::MaximumPathBuildCallsExceeded)
+    );
+}
+
 /* This is notable because it is a popular use of IP address subjectAltNames. */
 #[cfg(feature = "alloc")]
 #[test]